@@ -0,0 +1,16 @@
+PRAGMA foreign_keys = ON;
+-- ----------------------------
+-- Table structure for gemini_schedule
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_schedule" (
+  "schedule_id" TEXT NOT NULL,
+  "schedule_name" TEXT,
+  "schedule_prompt" TEXT,
+  "watch_file" TEXT,
+  "repeat_minutes" INTEGER DEFAULT 0,
+  "next_run_time" DATE,
+  "enabled" INTEGER DEFAULT 1,
+  PRIMARY KEY ("schedule_id")
+);
+
+PRAGMA foreign_keys = OFF;
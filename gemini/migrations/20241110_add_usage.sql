@@ -0,0 +1,14 @@
+PRAGMA foreign_keys = ON;
+-- ----------------------------
+-- Table structure for gemini_usage
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_usage" (
+  "usage_date" DATE NOT NULL,
+  "model" TEXT NOT NULL,
+  "request_count" INTEGER DEFAULT 0,
+  "prompt_tokens" INTEGER DEFAULT 0,
+  "response_tokens" INTEGER DEFAULT 0,
+  PRIMARY KEY ("usage_date", "model")
+);
+
+PRAGMA foreign_keys = OFF;
@@ -0,0 +1,21 @@
+PRAGMA foreign_keys = ON;
+-- ----------------------------
+-- Table structure for gemini_message_image
+-- 消息与图片的关联，相同内容的图片只保存一条图片记录
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_message_image" (
+  "record_id" TEXT NOT NULL,
+  "image_record_id" TEXT NOT NULL,
+  "image_path" TEXT,
+  PRIMARY KEY ("record_id"),
+  FOREIGN KEY ("record_id") REFERENCES "gemini_message_record" ("record_id") ON DELETE CASCADE ON UPDATE CASCADE
+);
+CREATE INDEX IF NOT EXISTS "idx_gemini_message_image_image_record_id" ON "gemini_message_image" ("image_record_id");
+-- ----------------------------
+-- move existing image records into gemini_message_image
+-- ----------------------------
+INSERT OR IGNORE INTO "gemini_message_image" ("record_id", "image_record_id", "image_path")
+SELECT "record_id", "image_record_id", "image_path" FROM "gemini_image_record" WHERE "record_id" IS NOT NULL;
+UPDATE "gemini_image_record" SET "record_id" = NULL WHERE "record_id" IS NOT NULL;
+
+PRAGMA foreign_keys = OFF;
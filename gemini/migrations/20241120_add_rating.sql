@@ -0,0 +1,11 @@
+-- ----------------------------
+-- Table structure for gemini_message_rating
+-- 对回复的评价，Good 或 Bad，评价为 Bad 时可以附带原因
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_message_rating" (
+  "record_id" TEXT NOT NULL,
+  "rating" TEXT NOT NULL,
+  "rating_reason" TEXT,
+  PRIMARY KEY ("record_id"),
+  FOREIGN KEY ("record_id") REFERENCES "gemini_message_record" ("record_id") ON DELETE CASCADE ON UPDATE CASCADE
+);
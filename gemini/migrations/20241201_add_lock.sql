@@ -0,0 +1,9 @@
+-- ----------------------------
+-- Table structure for gemini_conversation_lock
+-- 锁定的会话，锁定后不能发送新消息或删除会话
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_conversation_lock" (
+  "conversation_id" TEXT NOT NULL,
+  PRIMARY KEY ("conversation_id"),
+  FOREIGN KEY ("conversation_id") REFERENCES "gemini_conversation" ("conversation_id") ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,10 @@
+-- ----------------------------
+-- Table structure for gemini_conversation_pin
+-- 置顶的会话，按 pin_order 升序显示在聊天列表顶部
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_conversation_pin" (
+  "conversation_id" TEXT NOT NULL,
+  "pin_order" INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY ("conversation_id"),
+  FOREIGN KEY ("conversation_id") REFERENCES "gemini_conversation" ("conversation_id") ON DELETE CASCADE ON UPDATE CASCADE
+);
@@ -0,0 +1,10 @@
+-- ----------------------------
+-- Table structure for gemini_message_raw
+-- 接口返回的未经整理的回复原文，与记录内容不同时才保存，在原始视图中显示
+-- ----------------------------
+CREATE TABLE IF NOT EXISTS "gemini_message_raw" (
+  "record_id" TEXT NOT NULL,
+  "raw_content" TEXT NOT NULL,
+  PRIMARY KEY ("record_id"),
+  FOREIGN KEY ("record_id") REFERENCES "gemini_message_record" ("record_id") ON DELETE CASCADE ON UPDATE CASCADE
+);
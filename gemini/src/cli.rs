@@ -0,0 +1,182 @@
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use gemini_api::model::blocking::Gemini;
+
+use crate::utils::{
+    batch_utils::{batch_results, batch_status, read_batch_file, submit_batch, BatchState},
+    client_utils::GeminiBuilder,
+    clipboard_utils::{read_clipboard, write_clipboard},
+    git_utils::{git_diff, review_message, DEFAULT_REVIEW_PROMPT},
+    live_utils::{LiveEvent, LiveSession, DEFAULT_LIVE_MODEL},
+    store_utils::{read_config, StoreData},
+    suggest_utils::{clean_command, shell_widget, suggest_message},
+    usage_utils::track_usage,
+};
+
+/// 处理命令行子命令，如果不是子命令则返回 None，进入 TUI 界面
+pub fn run_subcommand(args: &[String]) -> Option<Result<()>> {
+    match args.first().map(String::as_str) {
+        Some("review") => Some(review(args.get(1).map(String::as_str))),
+        Some("clip") => Some(clip(&args[1..].join(" "))),
+        Some("batch") => Some(batch(args.get(1).map(String::as_str))),
+        Some("suggest") => Some(suggest(&args[1..])),
+        Some("live") => Some(live()),
+        _ => None,
+    }
+}
+
+/// 根据配置文件构造 Gemini 客户端
+fn gemini_from_config() -> Result<(Gemini, StoreData)> {
+    let Ok(store_data) = read_config() else {
+        bail!("配置文件不存在，请先运行 gemini 完成初始化")
+    };
+    Ok((GeminiBuilder::from_config(&store_data).build(), store_data))
+}
+
+/// 审查 git 改动并输出审查结果
+fn review(range: Option<&str>) -> Result<()> {
+    let (mut gemini, store_data) = gemini_from_config()?;
+    let diff = git_diff(range)?;
+    let prompt = store_data.review_prompt.unwrap_or(DEFAULT_REVIEW_PROMPT.into());
+    let (response, _) = gemini.send_simple_message(review_message(&prompt, &diff))?;
+    track_usage(&gemini, &response);
+    println!("{}", response);
+    Ok(())
+}
+
+/// 以剪贴板内容为上下文执行指令，并将回复写回剪贴板
+fn clip(instruction: &str) -> Result<()> {
+    if instruction.trim().is_empty() {
+        bail!("用法: gemini clip \"指令\"")
+    }
+    let content = read_clipboard()?;
+    if content.trim().is_empty() {
+        bail!("剪贴板为空")
+    }
+    let (mut gemini, _) = gemini_from_config()?;
+    let message = format!(
+        "{}\nReply with only the result, without any explanation.\n\n{}",
+        instruction.trim(),
+        content
+    );
+    let (response, _) = gemini.send_simple_message(message)?;
+    track_usage(&gemini, &response);
+    write_clipboard(response.trim())?;
+    println!("{}", response.trim());
+    Ok(())
+}
+
+/// 根据描述输出建议的命令，不带描述时修正上一条失败的命令，`--init <shell>` 输出 shell 集成脚本
+fn suggest(args: &[String]) -> Result<()> {
+    if args.first().map(String::as_str) == Some("--init") {
+        let Some(shell) = args.get(1) else {
+            bail!("用法: gemini suggest --init <zsh|fish>")
+        };
+        print!("{}", shell_widget(shell)?);
+        return Ok(());
+    }
+    let message = suggest_message(&args.join(" "))?;
+    let (mut gemini, _) = gemini_from_config()?;
+    // 建议命令不需要配置中的系统提示词
+    gemini.set_system_instruction(String::new());
+    let (response, _) = gemini.send_simple_message(message)?;
+    track_usage(&gemini, &response);
+    println!("{}", clean_command(&response));
+    Ok(())
+}
+
+/// 通过 Live API 进行流式对话，回复边生成边输出，模型回复期间输入新消息会打断当前回复，输入结束（Ctrl+D）时退出
+fn live() -> Result<()> {
+    let Ok(store_data) = read_config() else {
+        bail!("配置文件不存在，请先运行 gemini 完成初始化")
+    };
+    let model = store_data.live_model.unwrap_or(DEFAULT_LIVE_MODEL.into());
+    let mut session = LiveSession::connect(&store_data.key, &model, store_data.system_instruction.as_deref())?;
+    eprintln!("connected to {}, press Ctrl+D to quit", model);
+    // 在单独的线程中读取输入，使读取回复与输入消息互不阻塞
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let mut stdout = io::stdout();
+    loop {
+        match rx.try_recv() {
+            Ok(line) if !line.trim().is_empty() => session.send_text(&line)?,
+            Ok(_) | Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => break,
+        }
+        for event in session.poll()? {
+            match event {
+                LiveEvent::Text(text) => print!("{}", text),
+                LiveEvent::Interrupted => println!(" [interrupted]"),
+                LiveEvent::TurnComplete => println!(),
+                LiveEvent::SetupComplete => {}
+            }
+        }
+        stdout.flush()?;
+    }
+    session.close();
+    Ok(())
+}
+
+/// 批量任务状态的查询间隔
+const BATCH_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 提交 JSONL 文件中的提示词作为批量任务，等待完成后将结果写入 `<文件名>.results.jsonl`
+///
+/// 参数为 `batches/` 开头的任务名称时，继续等待已提交的任务
+fn batch(arg: Option<&str>) -> Result<()> {
+    let Some(arg) = arg else {
+        bail!("用法: gemini batch <prompts.jsonl|batches/任务名称>")
+    };
+    let Ok(store_data) = read_config() else {
+        bail!("配置文件不存在，请先运行 gemini 完成初始化")
+    };
+    let (name, output) = if arg.starts_with("batches/") {
+        (
+            arg.to_string(),
+            format!("{}.results.jsonl", arg.trim_start_matches("batches/")),
+        )
+    } else {
+        let path = Path::new(arg);
+        let requests = read_batch_file(path, &store_data)?;
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let count = requests.len();
+        let name = submit_batch(&store_data.key, &store_data.model.to_string(), &stem, requests)?;
+        eprintln!("submitted {} requests as {}", count, name);
+        (
+            name,
+            path.with_file_name(format!("{}.results.jsonl", stem))
+                .display()
+                .to_string(),
+        )
+    };
+    loop {
+        match batch_status(&store_data.key, &name)? {
+            (BatchState::Succeeded, operation) => {
+                let results = batch_results(&store_data.key, &operation)?;
+                let lines: Vec<String> = results.iter().map(|result| result.to_string()).collect();
+                fs::write(&output, lines.join("\n") + "\n")?;
+                println!("{}", output);
+                return Ok(());
+            }
+            (BatchState::Failed(state), _) => bail!("批量任务 {} 执行失败：{}", name, state),
+            (BatchState::Running(state), _) => {
+                eprintln!("{}: {}", name, state);
+                thread::sleep(BATCH_POLL_INTERVAL);
+            }
+        }
+    }
+}
@@ -1,14 +1,44 @@
+mod cli;
 mod model;
 mod ui;
 mod utils;
 
-use anyhow::Result;
-use ui::page::main_page::UI;
+// 终端客户端依赖终端、SQLite 与系统剪贴板，只能编译为本地程序
+#[cfg(target_family = "wasm")]
+compile_error!("gemini 是终端程序，不支持 WebAssembly 目标");
+
+use std::env;
+
+use anyhow::{bail, Result};
+use ui::page::{main_page::UI, view_page::ViewUI};
+use utils::import_utils::load_exported_conversation;
 
 fn main() -> Result<()> {
+    // 处理命令行子命令
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(result) = cli::run_subcommand(&args) {
+        return result;
+    }
+    // 只读查看导出的会话
+    if args.first().map(String::as_str) == Some("--view") {
+        let Some(path) = args.get(1) else {
+            bail!("用法: gemini --view <export.json|export.md>")
+        };
+        let (title, chat_history) = load_exported_conversation(path)?;
+        let terminal = ratatui::init();
+        let app_result = ViewUI::new(title, chat_history).run(terminal);
+        ratatui::restore();
+        return app_result;
+    }
+    // 离线模式不访问网络，用于开发与测试界面
+    let ui = if args.first().map(String::as_str) == Some("--offline") {
+        UI::offline()
+    } else {
+        UI::default()
+    };
     // Setup terminal
     let terminal = ratatui::init();
-    let app_result = UI::default().run(terminal);
+    let app_result = ui.run(terminal);
     ratatui::restore();
     app_result
 }
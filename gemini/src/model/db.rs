@@ -1,8 +1,8 @@
 #![allow(dead_code)]
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
 
-use super::view::Sender;
+use super::view::{Rating, Sender};
 
 /// 一个聊天记录项
 #[derive(Debug, Clone)]
@@ -15,6 +15,12 @@ pub struct Conversation {
     pub conversation_start_time: DateTime<Local>,
     /// 最后一条聊天记录发送时间
     pub conversation_modify_time: DateTime<Local>,
+    /// 最后一条聊天记录内容，仅在查询会话列表时填充
+    pub last_message: String,
+    /// 是否已锁定，锁定后不能发送新消息或删除会话
+    pub locked: bool,
+    /// 置顶顺序，未置顶时为空
+    pub pin_order: Option<i64>,
     /// 聊天记录
     pub conversation_records: Vec<MessageRecord>,
 }
@@ -36,6 +42,10 @@ pub struct MessageRecord {
     pub sort_index: u8,
     /// 图片内容
     pub image_record: Option<ImageRecord>,
+    /// 对回复的评价
+    pub rating: Option<Rating>,
+    /// 未经整理的回复原文，与记录内容相同时为 None
+    pub raw_content: Option<String>,
 }
 
 /// 单条聊天记录携带的图片
@@ -50,3 +60,37 @@ pub struct ImageRecord {
     /// 图片类型
     pub image_type: String,
 }
+
+/// 定时任务
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    /// 定时任务 id
+    pub schedule_id: String,
+    /// 任务名称，用作生成会话的标题
+    pub schedule_name: String,
+    /// 发送的提示词
+    pub schedule_prompt: String,
+    /// 监视的文件，执行时将文件内容附加到提示词后
+    pub watch_file: String,
+    /// 重复间隔（分钟），为 0 时只执行一次
+    pub repeat_minutes: i64,
+    /// 下一次执行时间
+    pub next_run_time: DateTime<Local>,
+    /// 是否启用
+    pub enabled: bool,
+}
+
+/// 每天每个模型的用量
+#[derive(Debug, Clone)]
+pub struct Usage {
+    /// 日期
+    pub usage_date: NaiveDate,
+    /// 模型名称
+    pub model: String,
+    /// 请求次数
+    pub request_count: i64,
+    /// 提示词 token 数
+    pub prompt_tokens: i64,
+    /// 回复 token 数
+    pub response_tokens: i64,
+}
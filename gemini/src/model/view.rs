@@ -1,5 +1,9 @@
+use std::path::PathBuf;
+
 use chrono::{DateTime, Local};
 
+use crate::utils::tool_utils::ToolCall;
+
 /// 单条聊天消息
 ///
 /// 包含消息状态、消息内容、发送者、发送时间等信息
@@ -13,6 +17,12 @@ pub struct ChatMessage {
     pub sender: Sender,
     /// 发送时间
     pub date_time: DateTime<Local>,
+    /// 数据库中的消息记录 id，尚未保存时为 None，用户消息在发送时生成 id，收到回复后以该 id 保存
+    pub record_id: Option<String>,
+    /// 回复中的图片、音频等附件，不会保存到数据库，也不会发送给模型
+    pub attachments: Vec<Attachment>,
+    /// 接口返回的未经整理的回复原文，在原始视图中显示，为 None 时与消息内容相同
+    pub raw: Option<String>,
 }
 
 /// 发送者类型
@@ -22,6 +32,88 @@ pub enum Sender {
     User(String),
     /// AI 回复的消息
     Bot,
+    /// 用户添加的本地备注，不会发送给模型
+    Note,
     /// 处理其他类型的消息，一般不会用到，用作标记作用
     Never,
 }
+
+/// 对回复的评价
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rating {
+    /// 好的回复
+    Good,
+    /// 差的回复，参数为原因，可以为空
+    Bad(String),
+}
+
+/// 一次聊天请求的回复
+#[derive(Debug, Clone, Default)]
+pub struct ChatReply {
+    /// 回复文本
+    pub text: String,
+    /// 思考模型的思考摘要，只在界面中显示，不会保存到数据库，也不会加入上下文
+    pub reasoning: Option<String>,
+    /// 回复中的图片、音频等附件
+    pub attachments: Vec<Attachment>,
+    /// 模型请求调用的本地工具
+    pub tool_call: Option<ToolCall>,
+    /// 带引用脚注与来源列表的回复，只在界面中显示，不会保存到数据库，也不会加入上下文
+    pub citations: Option<String>,
+}
+
+impl ChatReply {
+    /// 只有文本的回复
+    pub fn text(text: String) -> Self {
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+/// 回复中的非文本内容，如图片与音频，保存在程序所在目录的 data 文件夹中
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    /// 附件类型
+    pub kind: AttachmentKind,
+    /// 接口返回的 MIME 类型
+    pub mime_type: String,
+    /// 保存的文件路径
+    pub path: PathBuf,
+    /// 图片附件的缩略图像素，其他附件为 None
+    pub thumbnail: Option<Vec<Vec<[u8; 3]>>>,
+}
+
+/// 附件类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttachmentKind {
+    /// 图片
+    Image,
+    /// 音频
+    Audio,
+    /// 其他类型的文件
+    File,
+}
+
+impl AttachmentKind {
+    /// 根据 MIME 类型判断附件类型
+    pub fn from_mime(mime_type: &str) -> Self {
+        if mime_type.starts_with("image/") {
+            Self::Image
+        } else if mime_type.starts_with("audio/") {
+            Self::Audio
+        } else {
+            Self::File
+        }
+    }
+
+    /// 用于显示的类型名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::File => "file",
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use std::ops::RangeInclusive;
+
+use ratatui::{
+    crossterm::event::{self, KeyModifiers},
+    layout::{Constraint, Position, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::component::input::{input_trait::InputTextComponent, text_area::TextArea, text_field::TextField};
+use crate::utils::char_utils::fuzzy_score;
+
+/// 表单字段的标识符，由使用表单的页面定义
+pub trait FieldIdentifier: Copy + PartialEq {
+    /// 校验字段内容，不合法时返回提示
+    fn validate(&self, _content: &str) -> Option<&'static str> {
+        None
+    }
+}
+
+/// 表单字段的类型
+pub enum FieldKind {
+    /// 文本输入，单行或多行
+    Text(Box<dyn InputTextComponent>),
+    /// 开关，Space 或 Enter 切换
+    Checkbox(bool),
+    /// 从选项中选择，Left/Right 切换，Enter 打开下拉列表并输入关键字过滤
+    Select {
+        options: Vec<String>,
+        selected: usize,
+        /// 下拉列表的过滤关键字，打开下拉列表时为 Some
+        filter: Option<TextField>,
+        /// 下拉列表中高亮的匹配项下标
+        highlighted: usize,
+    },
+    /// 数值滑块，Left/Right 按步长调整，Home/End 跳到最小值与最大值
+    Slider {
+        range: RangeInclusive<f32>,
+        step: f32,
+        value: f32,
+    },
+}
+
+/// 带标签的表单字段
+pub struct FormField<I> {
+    /// 标识符
+    pub identifier: I,
+    /// 提示文本
+    pub label: String,
+    /// 布局属性
+    pub layout: Constraint,
+    /// 字段类型及当前的值
+    kind: FieldKind,
+    /// 创建字段时的内容，用于判断是否修改
+    original: String,
+}
+
+impl<I: FieldIdentifier> FormField<I> {
+    /// 创建表单字段，记录初始内容
+    pub fn new(identifier: I, label: &str, layout: Constraint, kind: FieldKind) -> Self {
+        let mut field = Self {
+            identifier,
+            label: label.into(),
+            layout,
+            kind,
+            original: String::new(),
+        };
+        field.original = field.value();
+        field
+    }
+
+    /// 创建单行文本字段
+    pub fn text(identifier: I, label: &str, layout: Constraint, content: String) -> Self {
+        Self::new(
+            identifier,
+            label,
+            layout,
+            FieldKind::Text(Box::new(TextField::new(content))),
+        )
+    }
+
+    /// 创建多行文本字段
+    pub fn text_area(identifier: I, label: &str, layout: Constraint, content: String) -> Self {
+        Self::new(
+            identifier,
+            label,
+            layout,
+            FieldKind::Text(Box::new(TextArea::new(content))),
+        )
+    }
+
+    /// 创建开关字段
+    pub fn checkbox(identifier: I, label: &str, layout: Constraint, checked: bool) -> Self {
+        Self::new(identifier, label, layout, FieldKind::Checkbox(checked))
+    }
+
+    /// 创建选择字段，`value` 不在选项中时作为第一项加入选项
+    pub fn select(identifier: I, label: &str, layout: Constraint, options: Vec<String>, value: &str) -> Self {
+        let mut field = Self::new(
+            identifier,
+            label,
+            layout,
+            FieldKind::Select {
+                options: vec![value.to_string()],
+                selected: 0,
+                filter: None,
+                highlighted: 0,
+            },
+        );
+        field.set_options(options);
+        field
+    }
+
+    /// 创建滑块字段，`value` 会被限制在范围内
+    pub fn slider(
+        identifier: I,
+        label: &str,
+        layout: Constraint,
+        range: RangeInclusive<f32>,
+        step: f32,
+        value: f32,
+    ) -> Self {
+        let value = value.clamp(*range.start(), *range.end());
+        Self::new(identifier, label, layout, FieldKind::Slider { range, step, value })
+    }
+
+    /// 字段内容，开关为 on/off，选择为选中的选项，滑块为数值
+    pub fn value(&self) -> String {
+        match &self.kind {
+            FieldKind::Text(input) => input.get_content(),
+            FieldKind::Checkbox(checked) => if *checked { "on" } else { "off" }.into(),
+            FieldKind::Select { options, selected, .. } => options.get(*selected).cloned().unwrap_or_default(),
+            // 去掉按步长累加产生的浮点误差
+            FieldKind::Slider { value, .. } => ((value * 1000.0).round() / 1000.0).to_string(),
+        }
+    }
+
+    /// 内容是否已修改
+    pub fn is_dirty(&self) -> bool {
+        self.value() != self.original
+    }
+
+    /// 校验当前内容，不合法时返回提示
+    pub fn error(&self) -> Option<&'static str> {
+        self.identifier.validate(&self.value())
+    }
+
+    /// 替换选择字段的选项，保留当前的值，不在新选项中时作为第一项加入
+    pub fn set_options(&mut self, mut new_options: Vec<String>) {
+        let value = self.value();
+        if let FieldKind::Select { options, selected, .. } = &mut self.kind {
+            if !new_options.contains(&value) {
+                new_options.insert(0, value.clone());
+            }
+            *selected = new_options
+                .iter()
+                .position(|option| *option == value)
+                .unwrap_or_default();
+            *options = new_options;
+        }
+    }
+
+    /// 下拉列表是否打开，打开时所有按键都应交给该字段处理
+    pub fn is_dropdown_open(&self) -> bool {
+        matches!(self.kind, FieldKind::Select { filter: Some(_), .. })
+    }
+
+    /// 使用另一个字段的初始内容，以便重新填入数据后仍显示修改状态
+    pub fn keep_original(&mut self, other: &Self) {
+        self.original = other.original.clone();
+    }
+
+    /// 处理按键事件
+    pub fn handle_key(&mut self, key: event::KeyEvent) {
+        match &mut self.kind {
+            FieldKind::Text(input) => Self::handle_text_key(input.as_mut(), key),
+            FieldKind::Checkbox(checked) => {
+                if matches!(key.code, event::KeyCode::Char(' ') | event::KeyCode::Enter) {
+                    *checked = !*checked;
+                }
+            }
+            FieldKind::Select {
+                options,
+                selected,
+                filter: filter @ None,
+                highlighted,
+            } => {
+                let count = options.len().max(1);
+                match key.code {
+                    event::KeyCode::Left => *selected = (*selected + count - 1) % count,
+                    event::KeyCode::Right => *selected = (*selected + 1) % count,
+                    event::KeyCode::Char(' ') | event::KeyCode::Enter => {
+                        *filter = Some(TextField::new(String::new()));
+                        *highlighted = *selected;
+                    }
+                    _ => {}
+                }
+            }
+            FieldKind::Select {
+                options,
+                selected,
+                filter: Some(query),
+                highlighted,
+            } => {
+                let matches = Self::matches(options, &query.get_content());
+                match key.code {
+                    event::KeyCode::Esc => self.close_dropdown(),
+                    event::KeyCode::Up => *highlighted = highlighted.saturating_sub(1),
+                    event::KeyCode::Down => *highlighted = (*highlighted + 1).min(matches.len().saturating_sub(1)),
+                    // 选中高亮的匹配项，没有匹配项时使用输入的关键字
+                    event::KeyCode::Enter => {
+                        let content = query.get_content().trim().to_string();
+                        match matches.get(*highlighted) {
+                            Some(index) => *selected = *index,
+                            None if !content.is_empty() => {
+                                options.push(content);
+                                *selected = options.len() - 1;
+                            }
+                            None => {}
+                        }
+                        self.close_dropdown();
+                    }
+                    event::KeyCode::Backspace => {
+                        query.delete_pre_char();
+                        *highlighted = 0;
+                    }
+                    event::KeyCode::Char(x) => {
+                        query.enter_char(x);
+                        *highlighted = 0;
+                    }
+                    _ => {}
+                }
+            }
+            FieldKind::Slider { range, step, value } => {
+                let next = match key.code {
+                    event::KeyCode::Left => *value - *step,
+                    event::KeyCode::Right => *value + *step,
+                    event::KeyCode::Home => *range.start(),
+                    event::KeyCode::End => *range.end(),
+                    _ => return,
+                };
+                *value = ((next / *step).round() * *step).clamp(*range.start(), *range.end());
+            }
+        }
+    }
+
+    /// 下拉列表的最大高度，包含边框
+    const MAX_DROPDOWN_HEIGHT: u16 = 12;
+
+    /// 关闭下拉列表
+    fn close_dropdown(&mut self) {
+        if let FieldKind::Select { filter, .. } = &mut self.kind {
+            *filter = None;
+        }
+    }
+
+    /// 与关键字匹配的选项下标，按匹配得分排序，得分相同时保持原有顺序
+    fn matches(options: &[String], query: &str) -> Vec<usize> {
+        let mut matches: Vec<(i64, usize)> = options
+            .iter()
+            .enumerate()
+            .filter_map(|(index, option)| Some((fuzzy_score(query, option)?, index)))
+            .collect();
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+        matches.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// 处理文本字段的按键事件
+    fn handle_text_key(input: &mut dyn InputTextComponent, key: event::KeyEvent) {
+        match key.code {
+            event::KeyCode::Enter => input.handle_enter_key(),
+            event::KeyCode::Backspace => input.delete_pre_char(),
+            event::KeyCode::Delete => input.delete_suf_char(),
+            event::KeyCode::Left => input.move_cursor_left(input.get_current_char()),
+            event::KeyCode::Right => input.move_cursor_right(input.get_next_char()),
+            event::KeyCode::Up => input.move_cursor_up(),
+            event::KeyCode::Down => input.move_cursor_down(),
+            event::KeyCode::Home => {
+                if key.modifiers.contains(KeyModifiers::CONTROL) {
+                    input.home_of_multiline()
+                } else {
+                    input.home_of_cursor()
+                }
+            }
+            event::KeyCode::End => {
+                if key.modifiers.contains(KeyModifiers::CONTROL) {
+                    input.end_of_multiline()
+                } else {
+                    input.end_of_cursor()
+                }
+            }
+            event::KeyCode::Char(x) => input.enter_char(x),
+            _ => {}
+        }
+    }
+
+    /// 绘制字段，不合法时边框显示为红色，被选中时显示为绿色，否则显示为白色
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, selected: bool) {
+        let error = self.error();
+        let block_style = if error.is_some() {
+            Style::default().fg(Color::Red)
+        } else if selected {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        // 修改过的字段标题后显示 `*`，不合法时显示提示
+        let mut title = self.label.clone();
+        if self.is_dirty() {
+            title.push_str(" *");
+        }
+        if let Some(error) = error {
+            title.push_str(&format!(" ({})", error));
+        }
+        let block = Block::default().title(title).style(block_style).borders(Borders::ALL);
+        let width = (area.width as usize).saturating_sub(2);
+        let height = (area.height as usize).saturating_sub(2);
+        let content = self.value();
+        let text = match &mut self.kind {
+            FieldKind::Text(input) => {
+                input.set_width_height(width, height);
+                input.should_show_text()
+            }
+            FieldKind::Checkbox(checked) => format!("[{}] {}", if *checked { "x" } else { " " }, content),
+            FieldKind::Select { .. } if content.is_empty() => "< none >".into(),
+            FieldKind::Select { .. } => format!("< {} >", content),
+            FieldKind::Slider { range, value, .. } => {
+                let track = width.saturating_sub(content.chars().count() + 1).max(1);
+                let ratio = (*value - *range.start()) / (*range.end() - *range.start());
+                let filled = ((track - 1) as f32 * ratio).round() as usize;
+                format!("{}●{} {}", "━".repeat(filled), "─".repeat(track - 1 - filled), content)
+            }
+        };
+        let paragraph = Paragraph::new(text)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(paragraph, area);
+        if let (true, FieldKind::Text(input)) = (selected, &self.kind) {
+            let (x, y) = input.get_cursor_position();
+            frame.set_cursor_position(Position::new(area.x + x as u16 + 1, area.y + y as u16 + 1));
+        }
+    }
+    /// 在字段下方绘制打开的下拉列表，下方空间不足时绘制在上方，`bounds` 为可用区域
+    pub fn draw_dropdown(&self, frame: &mut Frame, area: Rect, bounds: Rect) {
+        let FieldKind::Select {
+            options,
+            selected,
+            filter: Some(query),
+            highlighted,
+        } = &self.kind
+        else {
+            return;
+        };
+        let query = query.get_content();
+        let matches = Self::matches(options, &query);
+        let height = (matches.len().max(1) as u16 + 2).min(Self::MAX_DROPDOWN_HEIGHT);
+        let below = bounds.bottom().saturating_sub(area.bottom());
+        let above = area.y.saturating_sub(bounds.y);
+        let rect = if below >= height || below >= above {
+            Rect::new(area.x, area.bottom(), area.width, height.min(below))
+        } else {
+            Rect::new(area.x, area.y - height.min(above), area.width, height.min(above))
+        };
+        let items: Vec<ListItem> = if matches.is_empty() {
+            vec![ListItem::new(format!("Enter: use \"{}\"", query)).dark_gray()]
+        } else {
+            // 滚动列表，保证高亮的匹配项可见
+            let visible = (rect.height as usize).saturating_sub(2).max(1);
+            let skip = (*highlighted + 1).saturating_sub(visible);
+            matches
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .map(|(i, index)| {
+                    let name = match options[*index].as_str() {
+                        "" => "none",
+                        name => name,
+                    };
+                    let item = ListItem::new(name.to_string());
+                    let item = if *index == *selected { item.green() } else { item };
+                    if i == *highlighted {
+                        item.reversed()
+                    } else {
+                        item
+                    }
+                })
+                .collect()
+        };
+        let list = List::new(items).block(
+            Block::bordered()
+                .title(format!("filter: {}", query))
+                .border_style(Color::Blue),
+        );
+        frame.render_widget(Clear, rect);
+        frame.render_widget(list, rect);
+    }
+}
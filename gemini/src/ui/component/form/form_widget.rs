@@ -0,0 +1,133 @@
+use ratatui::{
+    crossterm::event,
+    layout::{
+        Constraint::{self, Fill},
+        Layout, Rect,
+    },
+    Frame,
+};
+
+use super::form_field::{FieldIdentifier, FormField};
+
+/// 一行表单字段及该行的布局属性
+pub type FormRow<I> = (Constraint, Vec<FormField<I>>);
+
+/// 表单，字段先纵向再横向排列，Tab/Shift+Tab 切换字段，其余按键交给选中的字段处理
+pub struct Form<I> {
+    /// 各行字段
+    rows: Vec<FormRow<I>>,
+    /// 选中的字段下标
+    selected: usize,
+}
+
+impl<I: FieldIdentifier> Form<I> {
+    /// 创建表单，选中第一个字段
+    pub fn new(rows: Vec<FormRow<I>>) -> Self {
+        Self { rows, selected: 0 }
+    }
+
+    /// 所有字段
+    pub fn fields(&self) -> impl Iterator<Item = &FormField<I>> {
+        self.rows.iter().flat_map(|(_, fields)| fields.iter())
+    }
+
+    /// 所有字段
+    fn fields_mut(&mut self) -> impl Iterator<Item = &mut FormField<I>> {
+        self.rows.iter_mut().flat_map(|(_, fields)| fields.iter_mut())
+    }
+
+    /// 选中下一个字段
+    pub fn next_field(&mut self) {
+        let count = self.fields().count().max(1);
+        self.selected = (self.selected + 1) % count;
+    }
+
+    /// 选中上一个字段
+    pub fn prev_field(&mut self) {
+        let count = self.fields().count().max(1);
+        self.selected = (self.selected + count - 1) % count;
+    }
+
+    /// 选中第一个字段
+    pub fn select_first(&mut self) {
+        self.selected = 0;
+    }
+
+    /// 选中指定的字段
+    pub fn select(&mut self, identifier: I) {
+        let index = self.fields().position(|field| field.identifier == identifier);
+        if let Some(index) = index {
+            self.selected = index;
+        }
+    }
+
+    /// 选中字段的下拉列表是否打开，打开时所有按键都应交给表单处理
+    pub fn is_dropdown_open(&self) -> bool {
+        self.fields()
+            .nth(self.selected)
+            .is_some_and(|field| field.is_dropdown_open())
+    }
+
+    /// 替换选择字段的选项，保留字段当前的值
+    pub fn set_options(&mut self, identifier: I, options: Vec<String>) {
+        if let Some(field) = self.fields_mut().find(|field| field.identifier == identifier) {
+            field.set_options(options);
+        }
+    }
+
+    /// 是否存在修改过的字段
+    pub fn is_dirty(&self) -> bool {
+        self.fields().any(|field| field.is_dirty())
+    }
+
+    /// 第一个不合法的字段及提示
+    pub fn first_error(&self) -> Option<(I, String)> {
+        self.fields()
+            .find_map(|field| Some((field.identifier, format!("Invalid {}: {}", field.label, field.error()?))))
+    }
+
+    /// 使用另一个表单中对应字段的初始内容，两个表单的字段应一一对应
+    pub fn keep_original(&mut self, other: &Self) {
+        for (field, old) in self.fields_mut().zip(other.fields()) {
+            field.keep_original(old);
+        }
+    }
+
+    /// 处理按键事件
+    pub fn handle_key(&mut self, key: event::KeyEvent) {
+        match key.code {
+            event::KeyCode::Tab if !self.is_dropdown_open() => self.next_field(),
+            event::KeyCode::BackTab if !self.is_dropdown_open() => self.prev_field(),
+            _ => {
+                let index = self.selected;
+                if let Some(field) = self.fields_mut().nth(index) {
+                    field.handle_key(key);
+                }
+            }
+        }
+    }
+
+    /// 绘制表单
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        // 最后留出空白区域，避免各行被拉伸
+        let v_list: Vec<Constraint> = self.rows.iter().map(|(layout, _)| *layout).chain([Fill(0)]).collect();
+        let areas = Layout::vertical(v_list).split(area);
+        let mut index = 0;
+        let mut selected_area = None;
+        for ((_, fields), row_area) in self.rows.iter_mut().zip(areas.iter()) {
+            let h_list: Vec<Constraint> = fields.iter().map(|field| field.layout).collect();
+            let h_areas = Layout::horizontal(h_list).split(*row_area);
+            for (field, field_area) in fields.iter_mut().zip(h_areas.iter()) {
+                if index == self.selected {
+                    selected_area = Some(*field_area);
+                }
+                field.draw(frame, *field_area, index == self.selected);
+                index += 1;
+            }
+        }
+        // 下拉列表绘制在所有字段之上
+        if let (Some(field), Some(field_area)) = (self.fields().nth(self.selected), selected_area) {
+            field.draw_dropdown(frame, field_area, area);
+        }
+    }
+}
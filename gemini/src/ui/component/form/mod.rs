@@ -0,0 +1,2 @@
+pub mod form_field;
+pub mod form_widget;
@@ -1,7 +1,19 @@
-use crate::utils::char_utils::{c_len, s_length};
+use crate::utils::char_utils::c_len;
 
 use super::input_trait::InputTextComponent;
 
+/// 单行输入框中换行符的显示符号
+const NEWLINE_SYMBOL: char = '↵';
+
+/// 单行输入框中字符的显示宽度，换行符显示为一个符号
+fn char_width(c: char) -> usize {
+    if c == '\n' {
+        1
+    } else {
+        c_len(c)
+    }
+}
+
 /// 单行输入框相关属性
 #[derive(Default)]
 pub struct TextField {
@@ -32,8 +44,8 @@ impl InputTextComponent for TextField {
 
         for index in start..input.chars().count() {
             if let Some(c) = input.chars().nth(index) {
-                result.push(c);
-                width += c_len(c);
+                result.push(if c == '\n' { NEWLINE_SYMBOL } else { c });
+                width += char_width(c);
                 if width > self.width {
                     break;
                 }
@@ -48,7 +60,7 @@ impl InputTextComponent for TextField {
         let mut width = 0;
         for index in 0..left {
             if let Some(c) = self.input_buffer.chars().nth(index) {
-                width += c_len(c);
+                width += char_width(c);
             }
         }
         // 坐标减去左侧隐藏的宽度为真实指针坐标
@@ -59,13 +71,13 @@ impl InputTextComponent for TextField {
     fn end_of_cursor(&mut self) {
         let input = self.input_buffer.clone();
         self.input_buffer_index = input.chars().count();
-        self.cursor_position_x = s_length(input);
+        self.cursor_position_x = input.chars().map(char_width).sum();
         let mut width = 0;
         self.left_index = self.input_buffer_index;
         for index in (0..self.input_buffer_index).rev() {
             if let Some(c) = self.input_buffer.chars().nth(index) {
                 // 计算当前宽度
-                width += c_len(c);
+                width += char_width(c);
                 if width <= self.width {
                     self.left_index -= 1;
                 } else {
@@ -103,7 +115,7 @@ impl InputTextComponent for TextField {
         }
         // 光标有变化
         if origin_cursor_index != self.input_buffer_index {
-            self.cursor_position_x = self.cursor_position_x.saturating_sub(c_len(c));
+            self.cursor_position_x = self.cursor_position_x.saturating_sub(char_width(c));
         }
     }
 
@@ -118,7 +130,7 @@ impl InputTextComponent for TextField {
         }
         // 光标有变化
         if origin_cursor_index != self.input_buffer_index {
-            self.cursor_position_x = self.cursor_position_x.saturating_add(c_len(c));
+            self.cursor_position_x = self.cursor_position_x.saturating_add(char_width(c));
         }
     }
 
@@ -208,7 +220,7 @@ impl TextField {
         let mut width = 0;
         for i in left..current_index {
             if let Some(c) = input.chars().nth(i) {
-                width += c_len(c);
+                width += char_width(c);
             }
         }
         width > self.width
@@ -221,7 +233,7 @@ impl TextField {
         let mut width = 0;
         for i in left..max {
             if let Some(c) = self.input_buffer.clone().chars().nth(i) {
-                width += c_len(c);
+                width += char_width(c);
             }
         }
         // 如果宽度小于输入框宽度，则左指针左移一位
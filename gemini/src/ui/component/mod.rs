@@ -1,3 +1,6 @@
+pub(crate) mod form;
 pub(crate) mod input;
 pub(crate) mod popup;
+pub(crate) mod progress;
 pub(crate) mod scroll;
+pub(crate) mod task;
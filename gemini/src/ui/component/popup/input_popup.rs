@@ -1,23 +1,62 @@
+use std::{fs, path::Path};
+
 use ratatui::{
     crossterm::event,
     layout::{Alignment, Rect},
-    style::{Color, Style},
-    widgets::{block::Title, Block, Borders, Clear, Paragraph},
+    style::{Color, Modifier, Style},
+    widgets::{block::Title, Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
 use crate::ui::component::input::{input_trait::InputTextComponent, text_field::TextField};
+use crate::utils::path_utils::{complete_path, expand_tilde, list_dir};
 use ratatui::widgets::block::title::Position as TitlePosition;
 
 use ratatui::layout::Position as CursorPosition;
 
 pub struct InputPopup {
+    // 标题
+    pub title: String,
     // 提示文本
     pub input_text: TextField,
     pub width: usize,
     pub height: usize,
     // 边框颜色
     pub border_color: Color,
+    // 路径补全的候选项
+    candidates: Vec<String>,
+    // 目录浏览模式
+    browser: Option<FileBrowser>,
+}
+
+/// 目录浏览模式的状态
+struct FileBrowser {
+    /// 当前浏览的目录
+    dir: String,
+    /// 目录下的文件名称以及是否为目录
+    entries: Vec<(String, bool)>,
+    /// 选中的文件下标
+    selected: usize,
+}
+
+impl FileBrowser {
+    /// 打开目录，目录无法读取时返回 None
+    fn open(dir: &str) -> Option<Self> {
+        let dir = fs::canonicalize(expand_tilde(dir)).ok()?.to_string_lossy().into_owned();
+        let entries = list_dir(&dir).ok()?;
+        Some(Self {
+            dir,
+            entries,
+            selected: 0,
+        })
+    }
+
+    /// 选中项的完整路径
+    fn selected_path(&self) -> Option<(String, bool)> {
+        let (name, is_dir) = self.entries.get(self.selected)?;
+        let path = Path::new(&self.dir).join(name);
+        Some((path.to_string_lossy().into_owned(), *is_dir))
+    }
 }
 
 impl InputPopup {
@@ -25,10 +64,70 @@ impl InputPopup {
         let mut input_text = TextField::new(title);
         input_text.set_width_height(width - 2, height - 2);
         Self {
+            title: "Image Path Or URL".into(),
             input_text,
             width,
             height,
             border_color: Color::Blue,
+            candidates: vec![],
+            browser: None,
+        }
+    }
+
+    /// 弹窗总高度，包含补全候选项或目录浏览列表
+    pub fn total_height(&self) -> usize {
+        let rows = match &self.browser {
+            Some(browser) => browser.entries.len(),
+            None => self.candidates.len(),
+        };
+        if rows == 0 {
+            self.height
+        } else {
+            self.height + rows.min(Self::MAX_LIST_ROWS) + 2
+        }
+    }
+
+    /// 替换输入内容，光标移动到末尾
+    fn set_content(&mut self, content: String) {
+        self.input_text = TextField::new(content);
+        self.input_text.set_width_height(self.width - 2, self.height - 2);
+    }
+
+    /// 补全输入的路径
+    fn complete(&mut self) {
+        let (completed, candidates) = complete_path(&self.input_text.get_content());
+        self.set_content(completed);
+        self.candidates = candidates;
+    }
+
+    /// 打开目录浏览模式，从输入路径所在目录开始浏览
+    fn open_browser(&mut self) {
+        let input = expand_tilde(&self.input_text.get_content());
+        let path = Path::new(&input);
+        let dir = if path.is_dir() {
+            input.clone()
+        } else {
+            path.parent()
+                .map(|parent| parent.to_string_lossy().into_owned())
+                .filter(|parent| !parent.is_empty())
+                .unwrap_or(".".into())
+        };
+        self.candidates.clear();
+        self.browser = FileBrowser::open(&dir);
+    }
+
+    /// 在目录浏览模式中打开选中的目录，选中文件时将路径填入输入框并退出浏览模式
+    fn enter_browser_selected(&mut self) {
+        let Some((path, is_dir)) = self.browser.as_ref().and_then(FileBrowser::selected_path) else {
+            return;
+        };
+        if is_dir {
+            if let Some(browser) = FileBrowser::open(&path) {
+                self.browser = Some(browser);
+            }
+        } else {
+            self.browser = None;
+            self.set_content(path);
         }
     }
 
@@ -39,8 +138,7 @@ impl InputPopup {
 
     // 保存
     pub fn save(&mut self) -> String {
-        // TODO: 保存输入内容
-        self.input_text.get_content()
+        expand_tilde(self.input_text.get_content().trim())
     }
 
     // 取消
@@ -50,12 +148,25 @@ impl InputPopup {
 }
 
 impl InputPopup {
+    /// 补全候选项与目录浏览列表最多显示的行数
+    const MAX_LIST_ROWS: usize = 10;
+
     pub fn draw(&self, frame: &mut Frame, area: Rect) {
         // 先清空弹窗区域内容
         frame.render_widget(Clear, area);
+        let list_area = Rect {
+            y: area.y + self.height as u16,
+            height: area.height.saturating_sub(self.height as u16),
+            ..area
+        };
+        let area = Rect {
+            height: area.height.min(self.height as u16),
+            ..area
+        };
+        self.draw_list(frame, list_area);
         let input_block = Block::bordered()
             .title(
-                Title::from("Image Path Or URL")
+                Title::from(self.title.as_str())
                     .position(TitlePosition::Top)
                     .alignment(Alignment::Left),
             )
@@ -81,6 +192,51 @@ impl InputPopup {
         let (x, y) = self.input_text.get_cursor_position();
         frame.set_cursor_position(CursorPosition::new(area.x + x as u16 + 1, area.y + y as u16 + 1));
     }
+
+    /// 绘制补全候选项或目录浏览列表
+    fn draw_list(&self, frame: &mut Frame, area: Rect) {
+        if area.height == 0 {
+            return;
+        }
+        let (title, items, selected) = match &self.browser {
+            Some(browser) => {
+                let items = browser
+                    .entries
+                    .iter()
+                    .map(|(name, is_dir)| {
+                        let name = if *is_dir { format!("{}/", name) } else { name.clone() };
+                        ListItem::new(name).style(if *is_dir { Color::LightBlue } else { Color::White })
+                    })
+                    .collect::<Vec<_>>();
+                (browser.dir.as_str(), items, Some(browser.selected))
+            }
+            None => {
+                let items = self
+                    .candidates
+                    .iter()
+                    .map(|name| ListItem::new(name.as_str()))
+                    .collect();
+                ("Candidates", items, None)
+            }
+        };
+        let block = Block::bordered()
+            .title(Title::from(title).alignment(Alignment::Left))
+            .title(
+                Title::from(if self.browser.is_some() {
+                    "Open (Enter) Up (Backspace) Back (ESC)"
+                } else {
+                    ""
+                })
+                .position(TitlePosition::Bottom)
+                .alignment(Alignment::Left),
+            )
+            .border_style(self.border_color);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(selected);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
 }
 
 pub enum InputPopupHandleEvent {
@@ -91,7 +247,23 @@ pub enum InputPopupHandleEvent {
 
 impl InputPopup {
     pub fn handle_key(&mut self, key: event::KeyEvent) -> InputPopupHandleEvent {
+        if self.browser.is_some() {
+            self.handle_browser_key(key);
+            return InputPopupHandleEvent::Nothing;
+        }
+        // 除补全外的按键都会清除补全候选项
+        if key.code != event::KeyCode::Tab {
+            self.candidates.clear();
+        }
         match key.code {
+            event::KeyCode::Tab => {
+                self.complete();
+                InputPopupHandleEvent::Nothing
+            }
+            event::KeyCode::Char('o') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.open_browser();
+                InputPopupHandleEvent::Nothing
+            }
             event::KeyCode::Char('s') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                 InputPopupHandleEvent::Save(self.save())
             }
@@ -131,3 +303,26 @@ impl InputPopup {
         }
     }
 }
+
+impl InputPopup {
+    /// 处理目录浏览模式的按键
+    fn handle_browser_key(&mut self, key: event::KeyEvent) {
+        let Some(ref mut browser) = self.browser else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Esc => self.browser = None,
+            event::KeyCode::Up => browser.selected = browser.selected.saturating_sub(1),
+            event::KeyCode::Down => {
+                browser.selected = (browser.selected + 1).min(browser.entries.len().saturating_sub(1))
+            }
+            event::KeyCode::Enter => self.enter_browser_selected(),
+            event::KeyCode::Backspace => {
+                // 返回上级目录
+                browser.selected = 0;
+                self.enter_browser_selected();
+            }
+            _ => {}
+        }
+    }
+}
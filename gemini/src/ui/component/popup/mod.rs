@@ -1,2 +1,3 @@
 pub mod delete_popup;
 pub mod input_popup;
+pub mod popup_manager;
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::utils::char_utils::s_length;
+
+/// 可以由 [`PopupManager`] 管理的弹窗
+pub trait Popup {
+    /// 弹窗期望的宽度与高度，超出可用区域的部分会被裁剪
+    fn size(&self, area: Rect) -> (u16, u16);
+
+    /// 在管理器计算出的区域中绘制弹窗，区域内原有的内容已被清空
+    fn draw(&mut self, frame: &mut Frame, area: Rect);
+}
+
+/// 弹窗栈，后打开的弹窗显示在上层并优先处理按键，另外管理右上角短暂显示的提示信息
+pub struct PopupManager<P> {
+    /// 打开的弹窗，最后一个位于最上层
+    stack: Vec<P>,
+    /// 短暂显示的提示信息，(内容, 显示时间)
+    toast: Option<(String, Instant)>,
+}
+
+impl<P> Default for PopupManager<P> {
+    fn default() -> Self {
+        Self {
+            stack: Vec::new(),
+            toast: None,
+        }
+    }
+}
+
+impl<P: Popup> PopupManager<P> {
+    /// 提示信息显示时长
+    const TOAST_DURATION: Duration = Duration::from_secs(2);
+
+    /// 在最上层打开弹窗
+    pub fn open(&mut self, popup: P) {
+        self.stack.push(popup);
+    }
+
+    /// 关闭最上层的弹窗
+    pub fn close(&mut self) -> Option<P> {
+        self.stack.pop()
+    }
+
+    /// 最上层的弹窗，按键事件应交给它处理
+    pub fn top(&self) -> Option<&P> {
+        self.stack.last()
+    }
+
+    /// 最上层的弹窗
+    pub fn top_mut(&mut self) -> Option<&mut P> {
+        self.stack.last_mut()
+    }
+
+    /// 是否有打开的弹窗
+    pub fn is_open(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    /// 所有打开的弹窗，由下至上
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut P> {
+        self.stack.iter_mut()
+    }
+
+    /// 由下至上绘制所有弹窗，弹窗在区域内居中
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        for popup in self.stack.iter_mut() {
+            let (width, height) = popup.size(area);
+            let rect = centered_rect(area, width, height);
+            frame.render_widget(Clear, rect);
+            popup.draw(frame, rect);
+        }
+    }
+
+    /// 显示提示信息，覆盖之前的提示
+    pub fn toast(&mut self, message: impl Into<String>) {
+        self.toast = Some((message.into(), Instant::now()));
+    }
+
+    /// 正在显示的提示信息，超时后返回 None
+    pub fn toast_message(&self) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() <= Self::TOAST_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// 在区域右上角绘制提示信息，位于所有弹窗之上
+    pub fn draw_toast(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(message) = self.toast_message() else {
+            self.toast = None;
+            return;
+        };
+        let width = (s_length(message.to_string()) as u16 + 4).min(area.width);
+        let rect = Rect::new(area.x + area.width - width, area.y, width, 3.min(area.height));
+        let toast = Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .block(Block::bordered().border_style(Color::Green))
+            .style(Style::default().fg(Color::Green));
+        frame.render_widget(Clear, rect);
+        frame.render_widget(toast, rect);
+    }
+}
+
+/// 在区域中居中放置指定大小的矩形，超出区域的部分被裁剪
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
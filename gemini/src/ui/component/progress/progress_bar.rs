@@ -0,0 +1,34 @@
+use ratatui::style::Color;
+
+/// 单行进度条，可以嵌入状态栏或弹窗中，宽度不足时只显示名称与计数
+#[derive(Clone)]
+pub struct ProgressBar {
+    // 任务名称
+    pub label: String,
+    // 已完成的数量
+    pub done: usize,
+    // 总数量
+    pub total: usize,
+    // 已完成部分的颜色
+    pub color: Color,
+}
+
+impl ProgressBar {
+    pub fn new(label: impl Into<String>, done: usize, total: usize) -> Self {
+        Self {
+            label: label.into(),
+            done,
+            total,
+            color: Color::Cyan,
+        }
+    }
+
+    /// 完成比例，总数量为 0 时视为已完成
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
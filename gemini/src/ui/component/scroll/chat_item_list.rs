@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use ratatui::{
     buffer::Buffer,
     layout::{
@@ -5,14 +7,17 @@ use ratatui::{
         Layout, Rect,
     },
     style::{Color, Style},
-    widgets::{Block, Borders, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
     Frame,
 };
 
 use crate::{
     model::db::Conversation,
-    ui::component::popup::delete_popup::DeletePopup,
-    utils::db_utils::{delete_one, query_all, query_detail_by_id},
+    ui::component::input::{input_trait::InputTextComponent, text_field::TextField},
+    utils::{
+        db_utils::{delete_one, lock_conversation, pin_conversation, query_all, query_detail_by_id, reorder_pins},
+        time_utils::relative_time,
+    },
 };
 
 /// 滚动条相关属性
@@ -26,10 +31,12 @@ pub struct ChatItemListScrollProps {
     pub show_chat_item_area_height: u16,
     /// 选中的会话
     pub selected_conversation: usize,
-    /// 是否展示确认删除弹窗
-    pub popup_delete_confirm_dialog: Option<DeletePopup>,
     /// 是否展示
     pub show: bool,
+    /// 有未读回复的会话 id
+    pub unread: HashSet<String>,
+    /// 重命名选中会话的输入框
+    pub title_editor: Option<TextField>,
 }
 /// 可一被选中的会话
 #[derive(Clone, Debug)]
@@ -40,10 +47,16 @@ pub struct SelectableConversation {
     pub selected: bool,
     /// 是否聚焦
     pub focused: bool,
+    /// 是否有未读回复
+    pub unread: bool,
+    /// 正在编辑的标题，不为空时代替标题显示
+    pub editing_title: Option<String>,
+    /// 快速打开的序号，前 9 个会话可以使用 Alt+序号 打开
+    pub index_hint: Option<usize>,
 }
 
 /// 聊天记录每一项高度
-static ITEM_HEIGHT: u16 = 3;
+static ITEM_HEIGHT: u16 = 4;
 
 impl ChatItemListScrollProps {
     pub fn draw(&mut self, frame: &mut Frame, area: Rect, is_focused: bool) {
@@ -85,6 +98,14 @@ impl ChatItemListScrollProps {
         let item_list_full_area = Rect::new(item_list_x + 1, item_list_y + 1, item_list_width, height);
         let mut item_list_full_area_buf = Buffer::empty(item_list_full_area);
 
+        // 正在重命名时，选中的会话显示输入框中的内容
+        if let Some(title_editor) = self.title_editor.as_mut() {
+            title_editor.set_width_height(item_list_width.saturating_sub(3) as usize, 1);
+            if let Some(selected) = self.chat_history.get_mut(self.selected_conversation) {
+                selected.editing_title = Some(title_editor.should_show_text());
+            }
+        }
+
         let areas = Layout::vertical(layouts).split(item_list_full_area);
         for (area, chat_message) in areas.iter().zip(self.chat_history.iter()) {
             chat_message.clone().render(*area, &mut item_list_full_area_buf);
@@ -109,6 +130,42 @@ impl ChatItemListScrollProps {
         Scrollbar::new(ScrollbarOrientation::VerticalRight).render(show_chat_item_area, buf, &mut state);
 
         chat_list_block.render(area, buf);
+
+        // 光标位于选中会话的标题行，标题左侧有边框与一个空格，标题行在滚动后不可见时不显示光标
+        if let Some(title_editor) = self.title_editor.as_ref() {
+            let (x, _) = title_editor.get_cursor_position();
+            let title_y = self.selected_conversation as u16 * ITEM_HEIGHT + 1;
+            if title_y >= self.scroll_offset && title_y - self.scroll_offset < item_list_height - 1 {
+                frame.set_cursor_position((
+                    item_list_full_area.x + 1 + x as u16,
+                    item_list_full_area.y + title_y - self.scroll_offset,
+                ));
+            }
+        }
+    }
+
+    /// 以纯文本列表渲染会话，不使用边框与装饰字符，供无障碍模式使用
+    pub fn draw_plain(&mut self, frame: &mut Frame, area: Rect, is_focused: bool) {
+        self.chat_history = self.query_all(is_focused);
+        let mut lines = vec![format!("Chats, {} in total:", self.chat_history.len())];
+        lines.extend(self.chat_history.iter().map(|item| {
+            format!(
+                "{}{}{}{}{}, {}",
+                if item.selected { "> " } else { "  " },
+                item.conversation.conversation_title,
+                if item.unread { ", unread" } else { "" },
+                if item.conversation.pin_order.is_some() {
+                    ", pinned"
+                } else {
+                    ""
+                },
+                if item.conversation.locked { ", locked" } else { "" },
+                relative_time(item.conversation.conversation_modify_time),
+            )
+        }));
+        // 保持选中的会话在可见范围内
+        let scroll = (self.selected_conversation as u16 + 2).saturating_sub(area.height);
+        frame.render_widget(Paragraph::new(lines.join("\n")).scroll((scroll, 0)), area);
     }
 
     /// 重建聊天记录
@@ -146,6 +203,86 @@ impl ChatItemListScrollProps {
         }
     }
 
+    /// 新会话出现在置顶会话之后，选中项在其后时需要加一以保持选中状态
+    pub fn conversation_added(&mut self) {
+        let pinned = self.pinned_ids().len();
+        if self.selected_conversation >= pinned {
+            self.selected_conversation += 1;
+        }
+    }
+
+    /// 置顶会话的 id，按置顶顺序排列
+    fn pinned_ids(&self) -> Vec<String> {
+        self.chat_history
+            .iter()
+            .filter(|item| item.conversation.pin_order.is_some())
+            .map(|item| item.conversation.conversation_id.clone())
+            .collect()
+    }
+
+    /// 重新查询会话并选中指定的会话，滚动使其可见
+    fn select_conversation(&mut self, conversation_id: &str) {
+        self.chat_history = self.query_all(true);
+        let Some(index) = self
+            .chat_history
+            .iter()
+            .position(|item| item.conversation.conversation_id == conversation_id)
+        else {
+            return;
+        };
+        self.select_index(index);
+    }
+
+    /// 选中指定位置的会话，滚动使其可见
+    pub fn select_index(&mut self, index: usize) {
+        if index >= self.chat_history.len() {
+            return;
+        }
+        self.selected_conversation = index;
+        let top = index as u16 * ITEM_HEIGHT;
+        if top < self.scroll_offset {
+            self.scroll_offset = top;
+        } else if top + ITEM_HEIGHT > self.scroll_offset + self.show_chat_item_area_height {
+            self.scroll_offset = (top + ITEM_HEIGHT).saturating_sub(self.show_chat_item_area_height);
+        }
+    }
+
+    /// 置顶或取消置顶选中的会话，返回切换后的状态
+    pub fn toggle_pin(&mut self) -> Option<bool> {
+        let selected = self.chat_history.get(self.selected_conversation)?;
+        let conversation_id = selected.conversation.conversation_id.clone();
+        let pinned = selected.conversation.pin_order.is_none();
+        pin_conversation(conversation_id.clone(), pinned).ok()?;
+        self.select_conversation(&conversation_id);
+        Some(pinned)
+    }
+
+    /// 在置顶会话中上移或下移选中的会话，并保存顺序
+    pub fn move_pinned(&mut self, up: bool) {
+        let mut pinned = self.pinned_ids();
+        let index = self.selected_conversation;
+        if index >= pinned.len() {
+            return;
+        }
+        let target = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|target| *target < pinned.len())
+        };
+        let Some(target) = target else {
+            return;
+        };
+        pinned.swap(index, target);
+        if reorder_pins(&pinned).is_ok() {
+            self.select_conversation(&pinned[target]);
+        }
+    }
+
+    /// 将会话标记为已读
+    pub fn mark_read(&mut self, conversation_id: &str) {
+        self.unread.remove(conversation_id);
+    }
+
     /// 删除选中的会话
     pub fn delete_item(&mut self) -> String {
         let id = if let Some(selected_conversation) = self.chat_history.get(self.selected_conversation) {
@@ -160,22 +297,60 @@ impl ChatItemListScrollProps {
         id
     }
 
+    /// 开始重命名选中的会话
+    pub fn start_rename(&mut self) {
+        if let Some(selected) = self.chat_history.get(self.selected_conversation) {
+            let mut title_editor = TextField::new(selected.conversation.conversation_title.clone());
+            title_editor.end_of_cursor();
+            self.title_editor = Some(title_editor);
+        }
+    }
+
+    /// 结束重命名，返回选中会话的 id 与新标题
+    pub fn finish_rename(&mut self) -> Option<(String, String)> {
+        let title = self.title_editor.take()?.get_content();
+        let selected = self.chat_history.get(self.selected_conversation)?;
+        Some((selected.conversation.conversation_id.clone(), title.trim().to_string()))
+    }
+
+    /// 选中的会话是否已锁定
+    pub fn selected_locked(&self) -> bool {
+        self.chat_history
+            .get(self.selected_conversation)
+            .is_some_and(|selected| selected.conversation.locked)
+    }
+
+    /// 锁定或解锁选中的会话，返回切换后的状态
+    pub fn toggle_lock(&mut self) -> Option<bool> {
+        let selected = self.chat_history.get(self.selected_conversation)?;
+        let locked = !selected.conversation.locked;
+        lock_conversation(selected.conversation.conversation_id.clone(), locked).ok()?;
+        Some(locked)
+    }
+
     /// 查询所有会话
     fn query_all(&self, focused: bool) -> Vec<SelectableConversation> {
         let mut conversations = Vec::new();
         for (index, conversation) in query_all().unwrap_or_default().iter().enumerate() {
             let conversation = conversation.clone();
+            let unread = self.unread.contains(&conversation.conversation_id);
             if index == self.selected_conversation {
                 conversations.push(SelectableConversation {
                     conversation,
                     selected: true,
                     focused,
+                    unread,
+                    editing_title: None,
+                    index_hint: (index < 9).then_some(index + 1),
                 });
             } else {
                 conversations.push(SelectableConversation {
                     conversation,
                     selected: false,
                     focused,
+                    unread,
+                    editing_title: None,
+                    index_hint: (index < 9).then_some(index + 1),
                 });
             }
         }
@@ -1,92 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
+    text::Line,
+    widgets::{
+        Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget, Wrap,
+    },
     Frame,
 };
 
-use crate::{model::view::ChatMessage, utils::char_utils::c_len};
+use crate::{
+    model::view::{ChatMessage, Rating},
+    ui::widget::{LayoutCache, LayoutOptions, WrappedChatMessage},
+    utils::{char_utils::s_length, markdown_utils::parse_code_blocks},
+};
 
 use ratatui::layout::{Constraint::Length, Layout};
 
-use crate::model::view::Sender::Never;
+use crate::model::view::Sender::{Bot, Never, Note, User};
 
 /// 滚动条相关属性
 #[derive(Default)]
 pub struct ChatShowScrollProps {
     /// 聊天历史记录
     pub chat_history: Vec<ChatMessage>,
+    /// 边框标题
+    pub title: Option<String>,
+    /// 估算的上下文 token 数，显示在下边框右侧
+    pub context_tokens: Option<i64>,
     /// 滚动条偏移量
     pub scroll_offset: u16,
     /// 聊天历史记录区域高度
     pub chat_history_area_height: u16,
+    /// 代码行水平滚动偏移量
+    pub horizontal_offset: u16,
+    /// 最大水平滚动偏移量
+    pub max_horizontal_offset: u16,
+    /// 代码块是否显示行号
+    pub line_numbers: bool,
+    /// 选中的消息下标
+    pub selected_index: Option<usize>,
+    /// 以原始文本展示的消息下标
+    pub raw_messages: HashSet<usize>,
+    /// 超过该行数的消息默认折叠，为 0 时不折叠
+    pub collapse_lines: u16,
+    /// 已展开的折叠消息下标
+    pub expanded_messages: HashSet<usize>,
+    /// 消息下标对应的评价
+    pub ratings: HashMap<usize, Rating>,
+    /// 消息下标对应的译文
+    pub translations: HashMap<usize, String>,
+    /// 回复下标对应的思考摘要，展开消息时显示，不保存到数据库
+    pub reasonings: HashMap<usize, String>,
+    /// 联网搜索回复下标对应的带引用脚注与来源列表的文本，代替回复显示，不保存到数据库
+    pub citations: HashMap<usize, String>,
+    /// 使用备用模型回答的回复下标与模型名称，不保存到数据库
+    pub fallback_models: HashMap<usize, String>,
+    /// 消息排版缓存，排版选项包含可用宽度，终端尺寸变化后自动重新排版
+    pub layout_cache: LayoutCache,
+    /// 每条消息在聊天记录区域中的起始位置
+    pub item_offsets: Vec<u16>,
+    /// 每个代码块在聊天记录区域中的起始位置
+    pub code_block_offsets: Vec<u16>,
+    /// 使用其他模型重新提问得到的备选回复
+    pub alternative: Option<AlternativeAnswer>,
+    /// 是否使用 Nerd Font 图标
+    pub icons: bool,
+}
+
+/// 备选回复，显示在原回复下方，可选择保留哪一个
+#[derive(Clone)]
+pub struct AlternativeAnswer {
+    /// 原回复的下标
+    pub index: usize,
+    /// 生成备选回复的模型
+    pub model: String,
+    /// 备选回复内容
+    pub message: String,
 }
 // 顶部底部边框以及时间区域高度
 static TOP_BOTTOM_BORDER_TIME_HEIGHT: u16 = 3;
 
 impl ChatShowScrollProps {
+    /// 选中上一条消息，未选中时选中最后一条
+    pub fn prev_message(&mut self) {
+        let prev = self
+            .selectable_indexes()
+            .rfind(|i| self.selected_index.is_none_or(|selected| *i < selected));
+        if let Some(index) = prev {
+            self.select(index);
+        }
+    }
+
+    /// 选中下一条消息，未选中时选中第一条
+    pub fn next_message(&mut self) {
+        let next = self
+            .selectable_indexes()
+            .find(|i| self.selected_index.is_none_or(|selected| *i > selected));
+        if let Some(index) = next {
+            self.select(index);
+        }
+    }
+
+    /// 选中上一条用户消息
+    pub fn prev_user_message(&mut self) {
+        let prev = self
+            .user_message_indexes()
+            .rfind(|i| self.selected_index.is_none_or(|selected| *i < selected));
+        if let Some(index) = prev {
+            self.select(index);
+        }
+    }
+
+    /// 选中下一条用户消息
+    pub fn next_user_message(&mut self) {
+        let next = self
+            .user_message_indexes()
+            .find(|i| self.selected_index.is_none_or(|selected| *i > selected));
+        if let Some(index) = next {
+            self.select(index);
+        }
+    }
+
+    /// 滚动到当前位置之前的代码块
+    pub fn prev_code_block(&mut self) {
+        if let Some(offset) = self
+            .code_block_offsets
+            .iter()
+            .rfind(|offset| **offset < self.scroll_offset)
+        {
+            self.scroll_offset = *offset;
+        }
+    }
+
+    /// 滚动到当前位置之后的代码块
+    pub fn next_code_block(&mut self) {
+        if let Some(offset) = self
+            .code_block_offsets
+            .iter()
+            .find(|offset| **offset > self.scroll_offset)
+        {
+            self.scroll_offset = *offset;
+        }
+    }
+
+    /// 切换选中消息的原始文本展示
+    pub fn toggle_raw_view(&mut self) {
+        if let Some(index) = self.selected_index {
+            if !self.raw_messages.remove(&index) {
+                self.raw_messages.insert(index);
+            }
+        }
+    }
+
+    /// 展开或折叠选中的长消息
+    pub fn toggle_collapse(&mut self) {
+        if let Some(index) = self.selected_index {
+            if !self.expanded_messages.remove(&index) {
+                self.expanded_messages.insert(index);
+            }
+        }
+    }
+
+    /// 可被选中的消息下标
+    fn selectable_indexes(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.chat_history
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| !matches!(m.sender, Never))
+            .map(|(i, _)| i)
+    }
+
+    /// 用户消息下标
+    fn user_message_indexes(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.chat_history
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| matches!(m.sender, User(_)))
+            .map(|(i, _)| i)
+    }
+
+    /// 选中消息，并滚动到该消息所在位置
+    fn select(&mut self, index: usize) {
+        self.selected_index = Some(index);
+        if let Some(offset) = self.item_offsets.get(index) {
+            self.scroll_offset = *offset;
+        }
+    }
+
     pub fn draw<F>(&mut self, frame: &mut Frame, area: Rect, chat_area_width: F, is_focused: bool)
     where
         F: Fn() -> usize,
     {
-        let chat_block = Block::default()
+        let mut chat_block = Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(if is_focused { Color::Green } else { Color::White }));
-        let items: Vec<ChatMessage> = self
+        if let Some(title) = self.title.as_deref() {
+            chat_block = chat_block.title(title);
+        }
+        if let Some(tokens) = self.context_tokens.filter(|tokens| *tokens > 0) {
+            chat_block = chat_block.title_bottom(Line::from(format!(" ~{} tokens ", tokens)).right_aligned());
+        }
+        // 对每条消息进行样式处理与换行
+        let area_width = chat_area_width();
+        self.layout_cache.truncate(self.chat_history.len());
+        let items: Vec<WrappedChatMessage> = self
             .chat_history
             .iter()
-            .map(|m| {
-                let area_width = chat_area_width();
-                let mut message = String::new();
-                // 对长文本进行插入换行符号
-                let mut line_width = 0;
-                for (_, c) in m.message.clone().char_indices() {
-                    // 如果当前行宽度正好为组件宽度，则插入换行符
-                    if line_width == area_width {
-                        message.push('\n');
-                        line_width = 0;
-                    }
-                    // 如果当前字符宽度大于组件宽度，则在最后一个字符之前插入换行符插入换行符
-                    if line_width > area_width {
-                        let c = message.pop().unwrap();
-                        message.push('\n');
-                        message.push(c);
-                        line_width = c_len(c);
-                    }
-                    message.push(c);
-                    line_width += c_len(c);
-                    if c == '\n' {
-                        line_width = 0;
+            .enumerate()
+            .map(|(i, m)| {
+                let options = LayoutOptions {
+                    width: area_width,
+                    horizontal_offset: self.horizontal_offset,
+                    line_numbers: self.line_numbers,
+                    raw: self.raw_messages.contains(&i),
+                };
+                let segments = match self.citations.get(&i) {
+                    Some(citations) => {
+                        let cited = ChatMessage {
+                            message: citations.clone(),
+                            ..m.clone()
+                        };
+                        self.layout_cache.layout(i, &cited, options)
                     }
+                    None => self.layout_cache.layout(i, m, options),
+                };
+                let mut item = WrappedChatMessage::with_segments(m.clone(), segments);
+                item.selected = self.selected_index == Some(i);
+                item.icons = self.icons;
+                item.rating = self.ratings.get(&i).cloned();
+                item.fallback_model = self.fallback_models.get(&i).cloned();
+                let expanded = self.expanded_messages.contains(&i);
+                if self.collapse_lines > 0 && !expanded {
+                    item.collapse(self.collapse_lines);
                 }
-                ChatMessage { message, ..m.clone() }
+                if let Some(reasoning) = self.reasonings.get(&i) {
+                    item.append_reasoning(reasoning, expanded, area_width);
+                }
+                if let Some(translation) = self.translations.get(&i) {
+                    item.append_translation(translation, area_width);
+                }
+                if let Some(alternative) = self.alternative.as_ref().filter(|alternative| alternative.index == i) {
+                    item.append_alternative(&alternative.model, &alternative.message, area_width);
+                }
+                for attachment in &m.attachments {
+                    item.append_attachment(attachment, area_width);
+                }
+                item
             })
             .collect();
-        // 计算当前聊天记录区域高度
-        self.chat_history_area_height = items
-            .clone()
+        // 计算最大水平滚动偏移量
+        self.max_horizontal_offset = items
+            .iter()
+            .map(WrappedChatMessage::code_overflow)
+            .max()
+            .unwrap_or_default();
+        let heights: Vec<u16> = items
             .iter()
             .map(|item| {
-                if matches!(item.sender, Never) {
+                if matches!(item.message.sender, Never) {
                     0
                 } else {
-                    item.message.lines().count() as u16 + TOP_BOTTOM_BORDER_TIME_HEIGHT
+                    item.height() + TOP_BOTTOM_BORDER_TIME_HEIGHT
                 }
             })
-            .sum();
-
-        let layouts: Vec<Constraint> = items
-            .clone()
+            .collect();
+        // 计算当前聊天记录区域高度，尺寸变化后高度可能变小，滚动位置不超过新的高度
+        self.chat_history_area_height = heights.iter().sum();
+        self.scroll_offset = self.scroll_offset.min(self.chat_history_area_height);
+        self.item_offsets = heights
             .iter()
-            .map(|item| {
-                if let Never = item.sender {
-                    Length(0)
-                } else {
-                    Length(item.message.lines().count() as u16 + TOP_BOTTOM_BORDER_TIME_HEIGHT)
-                }
+            .scan(0, |offset, height| {
+                let start = *offset;
+                *offset += height;
+                Some(start)
             })
             .collect();
+        self.code_block_offsets = items
+            .iter()
+            .zip(&self.item_offsets)
+            .filter(|(item, _)| !matches!(item.message.sender, Never))
+            .flat_map(|(item, start)| item.code_block_offsets().into_iter().map(move |offset| start + offset))
+            .collect();
+
+        let layouts: Vec<Constraint> = heights.iter().map(|height| Length(*height)).collect();
 
         let chat_area_x = area.x;
         let chat_area_y = area.y;
@@ -105,8 +304,8 @@ impl ChatShowScrollProps {
         let mut chat_list_full_area_buf = Buffer::empty(chat_list_full_area);
         // 计算每一条聊天消息的位置
         let areas = Layout::vertical(layouts).split(chat_list_full_area);
-        for (area, chat_message) in areas.iter().zip(items.iter()) {
-            chat_message.clone().render(*area, &mut chat_list_full_area_buf);
+        for (area, chat_message) in areas.iter().zip(items) {
+            chat_message.render(*area, &mut chat_list_full_area_buf);
         }
 
         // 将要被展示的聊天记录区域
@@ -114,7 +313,7 @@ impl ChatShowScrollProps {
             .content
             .into_iter()
             .skip((chat_area_width * self.scroll_offset) as usize) // 跳过滚动条滚动位置头部的区域
-            .take((chat_area_width * (chat_area_height - 2)) as usize); // 取出可见区域的内容，此处 -2 为去掉上边框和下边框
+            .take((chat_area_width * chat_area_height.saturating_sub(2)) as usize); // 取出可见区域的内容，此处 -2 为去掉上边框和下边框
 
         let buf = frame.buffer_mut();
         for (i, cell) in visible_content.enumerate() {
@@ -130,4 +329,97 @@ impl ChatShowScrollProps {
         // 给聊天记录区域渲染边框
         chat_block.render(area, buf);
     }
+
+    /// 消息显示的文本，联网搜索的回复显示带引用的文本
+    pub fn display_text(&self, index: usize) -> &str {
+        self.citations
+            .get(&index)
+            .or_else(|| self.chat_history.get(index).map(|message| &message.message))
+            .map_or("", String::as_str)
+    }
+
+    /// 以纯文本线性渲染聊天记录，不使用边框与装饰字符，供无障碍模式使用
+    pub fn draw_plain(&mut self, frame: &mut Frame, area: Rect) {
+        let width = (area.width as usize).max(1);
+        let mut lines: Vec<String> = Vec::new();
+        let mut offsets = Vec::new();
+        let mut code_block_offsets = Vec::new();
+        for (i, message) in self.chat_history.iter().enumerate() {
+            offsets.push(Self::plain_height(&lines, width));
+            let name = match message.sender {
+                User(_) => "You",
+                Bot => "Gemini",
+                Note => "Note",
+                Never => continue,
+            };
+            let selected = if self.selected_index == Some(i) {
+                "Selected. "
+            } else {
+                ""
+            };
+            let failed = if message.success { "" } else { ", not sent" };
+            let fallback = self
+                .fallback_models
+                .get(&i)
+                .map(|model| format!(", answered by {}", model))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{}{} at {}{}{}:",
+                selected,
+                name,
+                message.date_time.format("%H:%M"),
+                failed,
+                fallback
+            ));
+            let text = self.display_text(i);
+            let blocks = parse_code_blocks(text);
+            for (index, line) in text.lines().enumerate() {
+                if blocks.iter().any(|block| block.start_line == index) {
+                    code_block_offsets.push(Self::plain_height(&lines, width));
+                }
+                lines.push(line.into());
+            }
+            if let Some(reasoning) = self.reasonings.get(&i) {
+                if self.expanded_messages.contains(&i) {
+                    lines.push("Reasoning:".into());
+                    lines.extend(reasoning.lines().map(String::from));
+                } else {
+                    lines.push("Reasoning hidden, press z to show.".into());
+                }
+            }
+            if let Some(translation) = self.translations.get(&i) {
+                lines.push("Translation:".into());
+                lines.extend(translation.lines().map(String::from));
+            }
+            if let Some(alternative) = self.alternative.as_ref().filter(|alternative| alternative.index == i) {
+                lines.push(format!("Answer from {}:", alternative.model));
+                lines.extend(alternative.message.lines().map(String::from));
+            }
+            for attachment in &message.attachments {
+                lines.push(format!(
+                    "Attached {} ({}): {}",
+                    attachment.kind.label(),
+                    attachment.mime_type,
+                    attachment.path.display()
+                ));
+            }
+            lines.push(String::new());
+        }
+        self.chat_history_area_height = Self::plain_height(&lines, width);
+        self.scroll_offset = self.scroll_offset.min(self.chat_history_area_height);
+        self.item_offsets = offsets;
+        self.code_block_offsets = code_block_offsets;
+        let paragraph = Paragraph::new(lines.join("\n"))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll_offset, 0));
+        frame.render_widget(paragraph, area);
+    }
+
+    /// 按显示宽度估算纯文本换行后的总行数
+    fn plain_height(lines: &[String], width: usize) -> u16 {
+        lines
+            .iter()
+            .map(|line| s_length(line.clone()).div_ceil(width).max(1) as u16)
+            .sum()
+    }
 }
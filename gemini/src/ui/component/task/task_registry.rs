@@ -0,0 +1,134 @@
+use std::{collections::HashSet, time::Instant};
+
+use ratatui::{
+    layout::{
+        Constraint::{Fill, Length},
+        Layout, Rect,
+    },
+    style::Color,
+    text::Span,
+    Frame,
+};
+
+use crate::{ui::component::progress::progress_bar::ProgressBar, utils::db_utils::generate_unique_id};
+
+/// 等待动画的帧
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// 正在后台执行的任务
+#[derive(Clone)]
+pub struct Task {
+    /// 任务 id
+    pub id: String,
+    /// 任务类型，显示在状态栏与请求队列中
+    pub label: &'static str,
+    /// 所属会话 id，不属于任何会话时为空
+    pub conversation_id: String,
+    /// 所属会话标题或定时任务名称
+    pub title: String,
+    /// 开始时间
+    pub started_at: Instant,
+    /// 任务进度，(已完成数量, 总数量)，不汇报进度的任务为 None
+    pub progress: Option<(usize, usize)>,
+    /// 任务当前的状态，如等待速率限制，存在时代替任务类型显示在状态栏
+    pub status: Option<String>,
+    /// 是否允许取消，后台线程无法中断，取消只会丢弃任务的结果
+    pub cancellable: bool,
+}
+
+/// 后台任务登记表，任务开始时登记、完成时移除，统一记录进度与取消状态，并在状态栏中显示
+#[derive(Default)]
+pub struct TaskRegistry {
+    /// 正在执行的任务，按开始顺序排列
+    tasks: Vec<Task>,
+    /// 已取消的任务 id，这些任务的结果到达后会被丢弃
+    cancelled: HashSet<String>,
+}
+
+impl TaskRegistry {
+    /// 登记新任务，返回任务 id
+    pub fn start(&mut self, label: &'static str, conversation_id: String, title: String, cancellable: bool) -> String {
+        let id = generate_unique_id();
+        self.tasks.push(Task {
+            id: id.clone(),
+            label,
+            conversation_id,
+            title,
+            started_at: Instant::now(),
+            progress: None,
+            status: None,
+            cancellable,
+        });
+        id
+    }
+
+    /// 更新任务进度，任务已完成或被取消时忽略
+    pub fn set_progress(&mut self, id: &str, done: usize, total: usize) {
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+            task.progress = Some((done, total));
+        }
+    }
+
+    /// 更新任务状态，为 None 时恢复显示任务类型，任务已完成或被取消时忽略
+    pub fn set_status(&mut self, id: &str, status: Option<String>) {
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) {
+            task.status = status;
+        }
+    }
+
+    /// 任务完成，移出登记表，返回任务的结果是否应该被处理（未被取消）
+    pub fn finish(&mut self, id: &str) -> bool {
+        self.tasks.retain(|task| task.id != id);
+        !self.cancelled.remove(id)
+    }
+
+    /// 取消指定下标的任务，不允许取消的任务保持不变，返回被取消的任务
+    pub fn cancel(&mut self, index: usize) -> Option<Task> {
+        if !self.tasks.get(index)?.cancellable {
+            return None;
+        }
+        let task = self.tasks.remove(index);
+        self.cancelled.insert(task.id.clone());
+        Some(task)
+    }
+
+    /// 所有正在执行的任务
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    /// 指定会话是否有指定类型的任务正在执行
+    pub fn is_running(&self, label: &str, conversation_id: &str) -> bool {
+        self.tasks
+            .iter()
+            .any(|task| task.label == label && task.conversation_id == conversation_id)
+    }
+
+    /// 最近开始的任务
+    pub fn latest(&self) -> Option<&Task> {
+        self.tasks.last()
+    }
+
+    /// 在状态栏中绘制最近开始的任务，有状态时显示状态，有进度时显示进度条，同时执行多个任务时显示其余任务的数量
+    pub fn draw_status(&self, frame: &mut Frame, area: Rect) {
+        let Some(task) = self.latest() else {
+            return;
+        };
+        let frame_index = (task.started_at.elapsed().as_millis() / 100) as usize % SPINNER.len();
+        let others = self.tasks.len() - 1;
+        let more = if others > 0 {
+            format!(" +{}", others)
+        } else {
+            String::new()
+        };
+        let [spinner_area, task_area, more_area] =
+            Layout::horizontal([Length(2), Fill(1), Length(more.len() as u16)]).areas(area);
+        frame.render_widget(Span::styled(SPINNER[frame_index], Color::Cyan), spinner_area);
+        match (&task.status, task.progress) {
+            (Some(status), _) => frame.render_widget(Span::styled(status.as_str(), Color::LightYellow), task_area),
+            (None, Some((done, total))) => frame.render_widget(ProgressBar::new(task.label, done, total), task_area),
+            (None, None) => frame.render_widget(Span::styled(task.label, Color::Cyan), task_area),
+        }
+        frame.render_widget(Span::styled(more, Color::LightYellow), more_area);
+    }
+}
@@ -0,0 +1,41 @@
+/// 界面中使用的图标，开启 `icons` 配置时使用 Nerd Font 图标，否则使用 ASCII 字符
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Icon {
+    /// 新建聊天按钮
+    NewChat,
+    /// 设置按钮
+    Setting,
+    /// 用户头像
+    User,
+    /// Gemini 头像
+    Bot,
+    /// 图片附件
+    Image,
+    /// 命令输出附件
+    Command,
+}
+
+impl Icon {
+    /// 获取图标字符，`nerd` 为 false 时回退为 ASCII 字符
+    pub fn glyph(&self, nerd: bool) -> &'static str {
+        match (self, nerd) {
+            (Self::NewChat, true) => "\u{f067}",
+            (Self::NewChat, false) => "+",
+            (Self::Setting, true) => "\u{f013}",
+            (Self::Setting, false) => "*",
+            (Self::User, true) => "\u{f007}",
+            (Self::User, false) => "U",
+            (Self::Bot, true) => "\u{f06a9}",
+            (Self::Bot, false) => "AI",
+            (Self::Image, true) => "\u{f03e}",
+            (Self::Image, false) => "Image",
+            (Self::Command, true) => "\u{f120}",
+            (Self::Command, false) => "$",
+        }
+    }
+
+    /// 在文本前添加图标
+    pub fn label(&self, nerd: bool, text: &str) -> String {
+        format!("{} {}", self.glyph(nerd), text)
+    }
+}
@@ -1,3 +1,5 @@
 pub(crate) mod component;
+pub(crate) mod icon;
 pub(crate) mod page;
+pub(crate) mod theme;
 pub(crate) mod widget;
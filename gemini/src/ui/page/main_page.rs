@@ -1,28 +1,44 @@
-use std::sync::mpsc;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::{mpsc, Arc};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use self::component::popup::input_popup::InputPopup;
+use self::component::popup::input_popup::{InputPopup, InputPopupHandleEvent};
 
+use super::schedule_page::ScheduleUI;
 use super::setting_page::SettingUI;
+use super::usage_page::UsageUI;
 use anyhow::Result;
 use chrono::Local;
 use component::input::{input_trait::InputTextComponent, text_field::TextField};
 use component::popup::delete_popup::DeletePopup;
+use component::popup::popup_manager::{Popup, PopupManager};
+use component::progress::progress_bar::ProgressBar;
 use component::scroll::chat_item_list::ChatItemListScrollProps;
-use component::scroll::chat_show::ChatShowScrollProps;
+use component::scroll::chat_show::{AlternativeAnswer, ChatShowScrollProps};
+use component::task::task_registry::{Task, TaskRegistry};
 use gemini_api::body::request::GenerationConfig;
 use gemini_api::body::{Content, Part, Role};
 use gemini_api::model::blocking::Gemini;
 use gemini_api::param::LanguageModel;
 use gemini_api::utils::image::blocking::get_image_type_and_base64_string;
 use ratatui::layout::Position as CursorPosition;
-use ratatui::layout::{Alignment, Rect};
-use ratatui::style::{Color, Style};
+use ratatui::layout::{Alignment, Margin, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::block::{Position as TitlePosition, Title};
-use ratatui::widgets::Paragraph;
+use ratatui::widgets::{Paragraph, Row, Table, Tabs, Wrap};
 use ratatui::Frame;
 use ratatui::{
-    crossterm::event::{self, Event, KeyEventKind},
+    crossterm::{
+        event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyEventKind},
+        execute,
+        terminal::{enable_raw_mode, EnterAlternateScreen},
+    },
     layout::{
         Constraint::{Fill, Length},
         Layout,
@@ -30,63 +46,428 @@ use ratatui::{
     widgets::{Block, Borders},
     DefaultTerminal,
 };
-use strum::{EnumCount, FromRepr};
+use strum::{EnumIter, EnumString, IntoEnumIterator};
 
-use crate::model::view::ChatMessage;
-use crate::model::view::Sender::{Bot, Never, User};
+use crate::model::db::{Conversation, MessageRecord};
+use crate::model::view::Sender::{Bot, Never, Note, User};
+use crate::model::view::{Attachment, ChatMessage, Rating};
 use crate::ui::component;
+use crate::ui::icon::Icon;
+use crate::ui::theme::Theme;
+use crate::utils::char_utils::{c_len, fuzzy_score, s_length, truncate};
+use crate::utils::client_utils::{history_contents, GeminiBuilder};
+use crate::utils::clipboard_utils::{read_clipboard, write_clipboard};
 use crate::utils::db_utils::{
-    current_db_version, generate_unique_id, modify_title, save_conversation, update_db_structure,
+    backup_db, check_integrity, current_db_version, generate_unique_id, has_backup, is_conversation_locked,
+    lock_conversation, modify_reply, modify_title, query_all, query_detail_by_id, rate_reply, restore_backup,
+    save_conversation, save_conversation_with_image, update_db_structure,
+};
+use crate::utils::editor_utils::{edit_text, view_text};
+use crate::utils::error_utils::{friendly_error, is_capacity_error};
+use crate::utils::export_utils::{
+    export_ai_studio, export_file_name, export_html, export_markdown, salvage_conversations,
 };
-use crate::utils::image_utils::{cache_image, read_image_cache};
+use crate::utils::git_utils::{git_apply, git_diff, review_message, DEFAULT_REVIEW_PROMPT};
+use crate::utils::image_gen_utils::{generate_image, DEFAULT_IMAGE_MODEL};
+use crate::utils::image_utils::{
+    cache_image, cache_image_by_hash, inspect_image, read_image_cache, shrink_inline_image,
+};
+use crate::utils::key_utils::{paste_key_events, KeyBinding};
+use crate::utils::language_utils::{detect_language, reply_language_hint};
+use crate::utils::link_utils::{extract_links, open_link};
+use crate::utils::markdown_utils::{parse_code_blocks, CodeBlock};
+use crate::utils::path_utils::{expand_tilde, pasted_file_path};
+use crate::utils::quota_utils::check_key_limits;
+use crate::utils::rate_limit_utils::{acquire, RateLimit};
+use crate::utils::retention_utils::apply_retention;
+use crate::utils::schedule_utils::{run_schedule, schedule_message, take_due_schedules};
+use crate::utils::screenshot_utils::{capture_screenshot, default_screenshot_command};
+use crate::utils::session_utils::{ChatSession, OfflineSession, SendOptions};
+use crate::utils::share_utils::share_markdown;
 use crate::utils::store_utils::{read_config, save_config, update_db_version_into_profile, StoreData};
+use crate::utils::template_utils::{read_templates, save_template, ConversationTemplate};
+use crate::utils::tool_utils::{find_tool, run_command, run_tool, tool_output_message, LocalTool, ToolCall};
+use crate::utils::translate_utils::{translate, AUTO_LANGUAGE, DEFAULT_TARGET_LANGUAGE};
+use crate::utils::tts_utils::speak;
+use crate::utils::usage_utils::{estimate_tokens, track_usage};
+use crate::utils::voice_utils::{start_recording, transcribe, Recording};
 
 const ENV_NAME: &str = "GEMINI_KEY";
+/// 未配置时，超过该行数的消息默认折叠
+pub(crate) const DEFAULT_COLLAPSE_LINES: u16 = 40;
+/// 未配置时，生成的会话标题的最大显示宽度，与侧边栏中没有标记的标题的可用宽度一致
+pub(crate) const DEFAULT_TITLE_MAX_WIDTH: usize = 27;
 
 /// 窗口UI
 #[derive(Default)]
 pub struct UI {
-    /// 是否正在接收消息
-    receiving_message: bool,
+    /// 正在等待回复的会话 id
+    pending_conversations: HashSet<String>,
+    /// 正在后台执行的请求与任务
+    tasks: TaskRegistry,
+    /// 打开的弹窗与提示信息
+    popups: PopupManager<MainPopup>,
+    /// 正在后台读取图片的会话及读取进度
+    image_progress: HashMap<String, (usize, usize)>,
+    /// 分屏显示的只读会话
+    split_view: Option<ChatShowScrollProps>,
+    /// 打开的标签页，当前标签页的状态保存在 UI 中
+    tabs: Vec<ConversationTab>,
+    /// 当前标签页下标
+    active_tab: usize,
     /// 消息响应失败
     response_status: ResponseStatus,
     /// 是否应该退出程序
     should_exit: bool,
     /// Gemini API
     gemini: Option<Gemini>,
+    /// 离线模式，聊天回复由模拟会话生成，不访问网络
+    offline: bool,
     /// 当前聚焦的组件
     focus_component: MainFocusComponent,
     /// 输入区域组件
     input_field_component: TextField,
     /// 当前窗口
     current_windows: CurrentWindows,
+    /// 是否已开启终端的粘贴事件，仅主界面处理粘贴事件
+    bracketed_paste: bool,
+    /// 是否需要清空终端后重新绘制，如使用外部编辑器之后
+    needs_clear: bool,
     /// 图片路径
     image_path: Option<String>,
     /// 对话标题内容
     title: String,
     /// 对话 id
     conversation_id: String,
-    /// 是否正在生成标题
-    gen_title_ing: bool,
     /// 数据库版本
     db_version: Option<String>,
     /// 是否正在编辑标题
     title_editor_input_field: Option<TextField>,
-    /// 是否显示图片输入弹窗
-    image_url_input_popup: Option<InputPopup>,
+    /// 本地工具列表，发送消息时与请求线程共享
+    tools: Arc<[LocalTool]>,
+    /// 等待保存的文件列表
+    pending_files: Vec<CodeBlock>,
+    /// 附加到下一条消息的命令输出，(命令, 输出)
+    command_output: Option<(String, String)>,
+    /// 已附加图片的路径及其附件描述
+    image_chip: Option<(String, String)>,
+    /// 复制最后一条回复的快捷键
+    copy_reply_key: KeyBinding,
+    /// 上一次检查定时任务的时间
+    last_schedule_check: Option<Instant>,
+    /// 正在朗读的进程
+    speaking: Option<Child>,
+    /// 正在进行的录音
+    recording: Option<Recording>,
+    /// 启动阶段
+    startup: Startup,
+    /// 无障碍模式，线性布局且不使用边框与装饰字符
+    accessible: bool,
+    /// 界面配色主题
+    theme: Theme,
+    /// 是否使用 Nerd Font 图标
+    icons: bool,
+    /// 超过该行数的消息默认折叠
+    collapse_lines: u16,
+    /// 生成的会话标题的最大显示宽度
+    title_max_width: usize,
+    /// 主模型容量不足时自动改用的备用模型
+    fallback_model: Option<LanguageModel>,
+    /// 聊天回复的输出类型，不为空时通过接口直接发送消息，并保存回复中的图片、音频等附件
+    response_modalities: Vec<String>,
+    /// 思考模型的思考预算，设置时通过接口直接发送消息，并显示思考摘要
+    thinking_budget: Option<i64>,
+    /// 各模型的速率限制，发送消息时与请求线程共享
+    rate_limits: Arc<HashMap<String, RateLimit>>,
+    /// Tab 切换焦点的顺序
+    focus_order: Vec<MainFocusComponent>,
     chat_item_list: ChatItemListScrollProps,
     chat_show: ChatShowScrollProps,
+    /// 待处理的主界面事件
+    events: EventQueue,
+}
+/// 会话标签页，保存非当前标签页的会话状态
+#[derive(Default)]
+struct ConversationTab {
+    /// 对话 id
+    conversation_id: String,
+    /// 对话标题
+    title: String,
+    /// Gemini API
+    gemini: Option<Gemini>,
+    /// 图片路径
+    image_path: Option<String>,
+    /// 聊天内容显示区域
+    chat_show: ChatShowScrollProps,
+}
+
+/// 最近会话快速切换弹窗
+struct ConversationSwitcher {
+    /// 搜索关键字
+    query: TextField,
+    /// 最近的会话，按修改时间倒序
+    conversations: Vec<Conversation>,
+    /// 选中的匹配项下标
+    selected: usize,
+}
+
+impl ConversationSwitcher {
+    /// 弹窗中显示的最近会话数量
+    const RECENT_COUNT: usize = 20;
+
+    /// 与搜索关键字匹配的会话，按匹配得分排序，得分相同时保持修改时间顺序
+    fn matches(&self) -> Vec<&Conversation> {
+        let query = self.query.get_content();
+        let mut matches: Vec<(i64, &Conversation)> = self
+            .conversations
+            .iter()
+            .filter_map(|conversation| Some((fuzzy_score(&query, &conversation.conversation_title)?, conversation)))
+            .collect();
+        matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+        matches.into_iter().map(|(_, conversation)| conversation).collect()
+    }
+}
+
+/// 主界面的弹窗，由 [`PopupManager`] 管理层级、大小与按键分发
+enum MainPopup {
+    /// 请求队列，值为选中的请求下标与绘制时同步的后台请求
+    Queue { selected: usize, requests: Vec<Task> },
+    /// 模板选择，值为所有模板与选中的模板下标
+    Templates(Vec<ConversationTemplate>, usize),
+    /// 链接选择，值为消息中的链接与选中的链接下标
+    Links(Vec<String>, usize),
+    /// 最近会话快速切换
+    Switcher(ConversationSwitcher),
+    /// 操作确认，值为弹窗与确认后执行的操作
+    Confirm(DeletePopup, ConfirmAction),
+    /// 确认删除聊天列表中选中的会话
+    DeleteChat(DeletePopup),
+    /// 保存文件路径输入
+    SaveFile(InputPopup),
+    /// 图片路径输入
+    ImagePath(InputPopup),
+    /// 剪贴板内容的翻译，值为原文、译文与滚动位置
+    Translation {
+        text: String,
+        translation: String,
+        scroll: u16,
+    },
+}
+
+impl MainPopup {
+    /// 弹窗名称，用于提示按键输入的去向
+    fn label(&self) -> &'static str {
+        match self {
+            MainPopup::Queue { .. } => "requests",
+            MainPopup::Templates(..) => "templates",
+            MainPopup::Links(..) => "links",
+            MainPopup::Switcher(_) => "quick switch",
+            MainPopup::Confirm(..) => "confirm",
+            MainPopup::DeleteChat(_) => "chat list > delete",
+            MainPopup::SaveFile(_) => "save file",
+            MainPopup::ImagePath(_) => "input box > image path",
+            MainPopup::Translation { .. } => "translation",
+        }
+    }
+
+    /// 绘制请求队列
+    fn draw_queue(frame: &mut Frame, area: Rect, requests: &[Task], selected: usize) {
+        let rows: Vec<Row> = requests
+            .iter()
+            .enumerate()
+            .map(|(i, request)| {
+                let title = if request.title.is_empty() {
+                    "New Chat"
+                } else {
+                    request.title.as_str()
+                };
+                let elapsed = request.started_at.elapsed().as_secs();
+                let row = Row::new(vec![
+                    request.label.to_string(),
+                    truncate(title, 36),
+                    format!("{}:{:02}", elapsed / 60, elapsed % 60),
+                ]);
+                // 不允许取消的任务显示为灰色
+                let row = if request.cancellable { row } else { row.dark_gray() };
+                if i == selected {
+                    row.reversed()
+                } else {
+                    row
+                }
+            })
+            .collect();
+        let table = Table::new(rows, [Length(13), Fill(1), Length(6)])
+            .header(Row::new(vec!["request", "conversation", "age"]).fg(Color::Yellow))
+            .block(
+                Block::bordered()
+                    .title("requests (Delete: cancel, Esc: close)")
+                    .border_style(Color::Blue),
+            );
+        frame.render_widget(table, area);
+    }
+
+    /// 绘制模板选择列表
+    fn draw_templates(frame: &mut Frame, area: Rect, templates: &[ConversationTemplate], selected: usize) {
+        let rows: Vec<Row> = templates
+            .iter()
+            .enumerate()
+            .map(|(i, template)| {
+                let row = Row::new(vec![truncate(&template.name, 40), template.messages.len().to_string()]);
+                if i == selected {
+                    row.reversed()
+                } else {
+                    row
+                }
+            })
+            .collect();
+        let table = Table::new(rows, [Fill(1), Length(8)])
+            .header(Row::new(vec!["template", "messages"]).fg(Color::Yellow))
+            .block(
+                Block::bordered()
+                    .title("new chat from template (Enter: create, Esc: close)")
+                    .border_style(Color::Blue),
+            );
+        frame.render_widget(table, area);
+    }
+
+    /// 绘制链接选择列表，链接按在消息中出现的顺序编号
+    fn draw_links(frame: &mut Frame, area: Rect, links: &[String], selected: usize) {
+        let rows: Vec<Row> = links
+            .iter()
+            .enumerate()
+            .map(|(i, link)| {
+                let row = Row::new(vec![
+                    format!("{}", i + 1),
+                    truncate(link, (area.width as usize).saturating_sub(8)),
+                ]);
+                if i == selected {
+                    row.reversed()
+                } else {
+                    row
+                }
+            })
+            .collect();
+        let table = Table::new(rows, [Length(3), Fill(1)]).block(
+            Block::bordered()
+                .title("links (Enter/1-9: open, c: copy, Esc: close)")
+                .border_style(Color::Blue),
+        );
+        frame.render_widget(table, area);
+    }
+
+    /// 绘制剪贴板内容的翻译，原文以灰色显示在译文上方
+    fn draw_translation(frame: &mut Frame, area: Rect, text: &str, translation: &str, scroll: u16) {
+        let mut lines: Vec<Line> = text.lines().map(|line| Line::from(line).dark_gray()).collect();
+        lines.push(Line::default());
+        lines.extend(translation.lines().map(Line::from));
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(
+                Block::bordered()
+                    .title("translation (c: copy, Esc: close)")
+                    .border_style(Color::Blue),
+            );
+        frame.render_widget(paragraph, area);
+    }
+
+    /// 按显示宽度估算文本换行后的行数
+    fn wrapped_height(text: &str, width: usize) -> u16 {
+        text.lines()
+            .map(|line| s_length(line.to_string()).div_ceil(width.max(1)).max(1) as u16)
+            .sum()
+    }
+
+    /// 绘制最近会话快速切换列表，第一行为搜索框
+    fn draw_switcher(frame: &mut Frame, area: Rect, switcher: &mut ConversationSwitcher) {
+        let block = Block::bordered()
+            .title("recent conversations (Enter: open, Esc: close)")
+            .border_style(Color::Blue);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let [query_area, list_area] = Layout::vertical([Length(1), Fill(1)]).areas(inner);
+        switcher
+            .query
+            .set_width_height(query_area.width.saturating_sub(2) as usize, 1);
+        let query = Paragraph::new(format!("> {}", switcher.query.should_show_text())).fg(Color::Yellow);
+        frame.render_widget(query, query_area);
+        let (x, _) = switcher.query.get_cursor_position();
+        frame.set_cursor_position(CursorPosition::new(query_area.x + 2 + x as u16, query_area.y));
+        let rows: Vec<Row> = switcher
+            .matches()
+            .into_iter()
+            .enumerate()
+            .map(|(i, conversation)| {
+                let row = Row::new(vec![
+                    truncate(&conversation.conversation_title, 48),
+                    conversation.conversation_modify_time.format("%m-%d %H:%M").to_string(),
+                ]);
+                if i == switcher.selected {
+                    row.reversed()
+                } else {
+                    row
+                }
+            })
+            .collect();
+        frame.render_widget(Table::new(rows, [Fill(1), Length(11)]), list_area);
+    }
+}
+
+impl Popup for MainPopup {
+    fn size(&self, area: Rect) -> (u16, u16) {
+        match self {
+            MainPopup::Queue { requests, .. } => (70, (requests.len() as u16 + 3).max(4)),
+            MainPopup::Templates(templates, _) => (60, (templates.len() as u16 + 3).max(4)),
+            MainPopup::Links(links, _) => (80, (links.len() as u16 + 2).max(3)),
+            MainPopup::Switcher(_) => (70, ConversationSwitcher::RECENT_COUNT as u16 + 4),
+            MainPopup::Confirm(popup, _) | MainPopup::DeleteChat(popup) => (popup.width as u16, popup.height as u16),
+            MainPopup::SaveFile(popup) | MainPopup::ImagePath(popup) => {
+                (area.width.saturating_sub(50).max(50), popup.total_height() as u16)
+            }
+            MainPopup::Translation { text, translation, .. } => {
+                let width = area.width.saturating_sub(10).min(80);
+                let inner_width = width.saturating_sub(2) as usize;
+                let height =
+                    Self::wrapped_height(text, inner_width) + Self::wrapped_height(translation, inner_width) + 3;
+                (width, height.min(area.height.saturating_sub(4)))
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        match self {
+            MainPopup::Queue { selected, requests } => Self::draw_queue(frame, area, requests, *selected),
+            MainPopup::Templates(templates, selected) => Self::draw_templates(frame, area, templates, *selected),
+            MainPopup::Links(links, selected) => Self::draw_links(frame, area, links, *selected),
+            MainPopup::Switcher(switcher) => Self::draw_switcher(frame, area, switcher),
+            MainPopup::Confirm(popup, _) | MainPopup::DeleteChat(popup) => popup.clone().draw(frame, area),
+            MainPopup::SaveFile(popup) | MainPopup::ImagePath(popup) => {
+                popup.set_size(area.width as usize, 3);
+                popup.draw(frame, area);
+            }
+            MainPopup::Translation {
+                text,
+                translation,
+                scroll,
+            } => Self::draw_translation(frame, area, text, translation, *scroll),
+        }
+    }
 }
+
 /// 窗口枚举
 #[derive(Default)]
+#[allow(clippy::enum_variant_names, clippy::large_enum_variant)]
 pub enum CurrentWindows {
     #[default]
     MainWindow,
     SettingWindow(SettingUI),
+    ScheduleWindow(Box<ScheduleUI>),
+    UsageWindow(Box<UsageUI>),
 }
 
-/// 当前聚焦组件
-#[derive(Default, Clone, EnumCount, FromRepr)]
+/// 当前聚焦组件，配置焦点顺序时使用下划线分隔的名称，如 `chat_item_list`
+#[derive(Default, Clone, PartialEq, Eq, EnumIter, EnumString)]
+#[strum(serialize_all = "snake_case")]
 pub enum MainFocusComponent {
     /// 输入框
     #[default]
@@ -99,6 +480,30 @@ pub enum MainFocusComponent {
     SettingButton,
     /// 聊天内容显示区域
     ChatShow,
+    /// 分屏显示的只读聊天内容区域
+    SplitView,
+}
+
+impl MainFocusComponent {
+    /// 组件名称，用于提示按键输入的去向
+    fn label(&self) -> &'static str {
+        match self {
+            MainFocusComponent::InputField => "input box",
+            MainFocusComponent::NewChatButton => "new chat button",
+            MainFocusComponent::ChatItemList => "chat list",
+            MainFocusComponent::SettingButton => "setting button",
+            MainFocusComponent::ChatShow => "chat messages",
+            MainFocusComponent::SplitView => "split view",
+        }
+    }
+
+    /// 解析焦点顺序配置，包含未知名称时返回 None，配置为空时使用默认顺序
+    pub fn parse_order(names: &[String]) -> Option<Vec<Self>> {
+        if names.is_empty() {
+            return Some(Self::iter().collect());
+        }
+        names.iter().map(|name| name.trim().parse().ok()).collect()
+    }
 }
 
 /// 响应状态
@@ -111,32 +516,339 @@ pub enum ResponseStatus {
 }
 
 enum ChatType {
-    Simple { message: String },
-    Image { message: String, image_path: String },
+    Simple {
+        message: String,
+    },
+    Image {
+        message: String,
+        image_path: String,
+    },
+    /// 使用 Google 搜索回答问题
+    Search {
+        message: String,
+    },
+    /// 翻译指定下标的消息，下标为 None 时翻译剪贴板内容
+    Translate {
+        index: Option<usize>,
+        text: String,
+    },
+    /// 使用其他模型重新回答指定下标的回复
+    Alternative {
+        index: usize,
+        prompt: String,
+    },
+    /// 将导出的 Markdown 上传到分享服务
+    Share {
+        file_name: String,
+        markdown: String,
+    },
+    /// 识别录音内容
+    Transcribe {
+        path: PathBuf,
+    },
+    /// 根据提示词生成图片
+    GenerateImage {
+        prompt: String,
+    },
+    /// 执行截图命令
+    Screenshot {
+        command: String,
+    },
+    /// 执行模型请求的本地工具
+    Tool {
+        tool: LocalTool,
+        call: ToolCall,
+    },
+    /// 将本地工具的执行结果发送给模型
+    ToolResponse {
+        call: ToolCall,
+        output: String,
+    },
+    /// 执行用户输入的 shell 命令
+    Command {
+        command: String,
+    },
+    /// 读取会话中的图片数据，元素为上下文下标、图片记录 id 与原图片路径
+    LoadImages {
+        conversation_id: String,
+        images: Vec<(usize, String, String)>,
+    },
+}
+
+impl ChatType {
+    /// 请求类型名称，显示在请求队列中
+    fn kind(&self) -> &'static str {
+        match self {
+            ChatType::Simple { .. } => "chat",
+            ChatType::Image { .. } => "chat (image)",
+            ChatType::Search { .. } => "chat (search)",
+            ChatType::Translate { .. } => "translate",
+            ChatType::Alternative { .. } => "other model",
+            ChatType::Share { .. } => "share",
+            ChatType::Transcribe { .. } => "transcribe",
+            ChatType::GenerateImage { .. } => "image",
+            ChatType::Screenshot { .. } => "screenshot",
+            ChatType::Tool { .. } => "tool",
+            ChatType::ToolResponse { .. } => "chat (tool)",
+            ChatType::Command { .. } => "command",
+            ChatType::LoadImages { .. } => "load images",
+        }
+    }
+}
+
+/// 主界面事件，终端输入与后台线程的结果都作为事件发送到同一个通道，由 [`UI::handle_event`] 统一处理
+enum AppEvent {
+    /// 按键
+    Key(event::KeyEvent),
+    /// 粘贴文本
+    Paste(String),
+    /// 终端尺寸变化
+    Resize,
+    /// 每一轮事件循环触发一次，用于执行定时检查
+    Tick,
+    /// 需要在后台执行的请求
+    Request(ChatType),
+    /// 后台请求的中间结果，不会将请求移出请求队列
+    ResponseChunk(ChatResponse),
+    /// 后台请求完成，附带请求 id
+    ResponseDone(String, ChatResponse),
+    /// 后台检查发现的问题，显示在状态栏
+    Warning(String),
+    /// 启动时在后台检查数据库的结果
+    DbReady(DbStartup),
+}
+
+/// 启动阶段，首帧绘制之后才读取配置与检查数据库，避免数据库较大或磁盘较慢时界面迟迟不出现
+#[derive(Default, PartialEq)]
+enum Startup {
+    /// 尚未绘制首帧
+    #[default]
+    FirstFrame,
+    /// 正在后台检查并更新数据库
+    LoadingDb,
+    /// 启动完成
+    Ready,
+}
+
+/// 启动时在后台检查数据库的结果
+struct DbStartup {
+    /// 数据库损坏或更新数据库结构失败的原因
+    problem: Option<String>,
+    /// 是否已更新数据库结构
+    migrated: bool,
+    /// 按保留策略删除的会话数量或错误信息
+    retention: Result<usize, String>,
+}
+
+/// 主界面事件的发送端
+type EventSender = mpsc::Sender<AppEvent>;
+
+/// 主界面事件队列，后台线程持有发送端的副本
+struct EventQueue {
+    /// 发送端
+    tx: EventSender,
+    /// 接收端
+    rx: mpsc::Receiver<AppEvent>,
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self { tx, rx }
+    }
+}
+
+/// 后台请求的响应
+enum ChatResponse {
+    /// 聊天消息的回复
+    Chat {
+        /// 会话 id
+        conversation_id: String,
+        /// 发送消息时的会话标题
+        title: String,
+        /// 是否为新建的会话
+        new_conversation: bool,
+        /// 用户发送的消息
+        user_message: Box<ChatMessage>,
+        /// 发送消息后的客户端，包含最新的上下文
+        gemini: Box<Gemini>,
+        /// 在后台缓存的图片记录 id
+        cached_image: Option<String>,
+        /// 主模型容量不足时实际回答的备用模型
+        fallback_model: Option<String>,
+        /// 回复中的图片、音频等附件
+        attachments: Vec<Attachment>,
+        /// 思考模型的思考摘要
+        reasoning: Option<String>,
+        /// 模型请求调用的本地工具
+        tool_call: Option<ToolCall>,
+        /// 带引用脚注与来源列表的回复，只用于显示
+        citations: Option<String>,
+        /// 回复内容或错误信息
+        result: Result<String, String>,
+    },
+    /// 读取会话图片的进度
+    ImageProgress {
+        /// 会话 id
+        conversation_id: String,
+        /// 已读取的图片数量
+        done: usize,
+        /// 图片总数
+        total: usize,
+    },
+    /// 后台任务的进度，显示在状态栏
+    Progress {
+        /// 请求 id
+        request_id: String,
+        /// 已完成的数量
+        done: usize,
+        /// 总数量
+        total: usize,
+    },
+    /// 后台任务当前的状态，如等待速率限制或正在发送图片，显示在状态栏
+    Status {
+        /// 请求 id
+        request_id: String,
+        /// 状态文本，为 None 时恢复显示任务类型
+        status: Option<String>,
+    },
+    /// 后台生成的会话标题
+    Title {
+        /// 会话 id
+        conversation_id: String,
+        /// 生成的标题
+        title: String,
+    },
+    /// 导出的会话数量与目录，或错误信息
+    Salvage(Result<(usize, PathBuf), String>),
+    /// 读取完成的会话图片，元素为上下文下标与图片数据
+    ImagesLoaded {
+        /// 会话 id
+        conversation_id: String,
+        /// 上下文下标与图片数据
+        images: Vec<(usize, Part)>,
+    },
+    /// 翻译结果
+    Translate {
+        /// 会话 id
+        conversation_id: String,
+        /// 被翻译的消息下标
+        index: usize,
+        /// 译文或错误信息
+        result: Result<String, String>,
+    },
+    /// 剪贴板内容的翻译结果
+    ClipboardTranslation {
+        /// 剪贴板内容
+        text: String,
+        /// 译文或错误信息
+        result: Result<String, String>,
+    },
+    /// 其他模型的备选回复
+    Alternative {
+        /// 会话 id
+        conversation_id: String,
+        /// 原回复的下标
+        index: usize,
+        /// 生成备选回复的模型
+        model: String,
+        /// 备选回复或错误信息
+        result: Result<String, String>,
+    },
+    /// 分享链接或错误信息
+    Share(Result<String, String>),
+    /// 语音识别结果或错误信息
+    Transcription(Result<String, String>),
+    /// 截图文件路径或错误信息
+    Screenshot(Result<PathBuf, String>),
+    /// 本地工具的输出
+    ToolOutput {
+        /// 会话 id
+        conversation_id: String,
+        /// 模型请求的工具调用
+        call: ToolCall,
+        /// 工具输出或错误信息
+        output: String,
+    },
+    /// shell 命令与输出
+    CommandOutput {
+        /// 执行的命令
+        command: String,
+        /// 命令输出或错误信息
+        output: String,
+    },
+    /// 生成的图片
+    Image {
+        /// 会话 id
+        conversation_id: String,
+        /// 保存的图片附件或错误信息
+        result: Result<Vec<Attachment>, String>,
+    },
+    /// 定时任务的执行结果，保存为新的会话
+    Schedule {
+        /// 定时任务名称
+        schedule_name: String,
+        /// 发送的提示词
+        user_message: ChatMessage,
+        /// 回复内容或错误信息
+        result: Result<String, String>,
+    },
+}
+
+/// 需要用户确认的操作
+enum ConfirmAction {
+    /// 执行模型请求的本地工具
+    RunTool(ToolCall),
+    /// 执行 shell 命令并将输出附加到消息中
+    RunCommand(String),
+    /// 覆盖写入文件
+    WriteFile { path: String, content: String },
+    /// 使用 git apply 应用 diff
+    ApplyDiff(String),
+    /// 将粘贴的图片路径作为图片附加，取消时输入粘贴的文本
+    AttachImage { path: String, text: String },
+    /// 解锁会话
+    Unlock(String),
+    /// 使用最近一次的备份恢复数据库，取消时导出仍能读取的会话
+    RestoreDb,
+    /// 导出损坏的数据库中仍能读取的会话
+    SalvageDb,
 }
 
 impl UI {
+    /// 创建离线模式的界面，聊天回复由模拟会话生成
+    pub fn offline() -> Self {
+        Self {
+            offline: true,
+            ..Default::default()
+        }
+    }
+
     /// 启动UI
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        let (chat_tx, chat_rx) = mpsc::channel();
-        let (title_tx, title_rx) = mpsc::channel();
-        self.restore_or_new_gemini(None);
-        // 如果数据库版本不一致，则更新数据库结构，补全更新数据库版本
-        if self.db_version.clone().unwrap_or_default() != current_db_version() {
-            // 更新数据库结构
-            update_db_structure()?;
-            self.db_version = Some(current_db_version());
-        }
         while !self.should_exit {
-            // 异步生成标题
-            if let Ok(title) = title_rx.try_recv() {
-                self.gen_title_ing = false;
-                self.title = title;
+            // 处理上一轮循环中产生的所有事件
+            self.update();
+            if self.needs_clear {
+                self.needs_clear = false;
+                terminal.clear()?;
             }
+            self.set_bracketed_paste(matches!(self.current_windows, CurrentWindows::MainWindow));
             match self.current_windows {
                 CurrentWindows::MainWindow => {
-                    terminal.draw(|frame| self.draw(frame))?;
-                    self.handle_key(chat_tx.clone(), title_tx.clone(), &chat_rx);
+                    terminal.draw(|frame| {
+                        self.draw(frame);
+                        self.theme.apply(frame.buffer_mut());
+                    })?;
+                    if self.startup == Startup::FirstFrame {
+                        self.start_loading();
+                    }
+                    // 等待终端事件，超时后继续循环以便处理后台请求的响应
+                    if event::poll(Duration::from_millis(100)).unwrap_or_default() {
+                        if let Ok(event) = event::read() {
+                            self.push_terminal_event(event);
+                        }
+                    }
                 }
                 CurrentWindows::SettingWindow(ref mut setting_ui) => {
                     if setting_ui.should_exit {
@@ -146,17 +858,86 @@ impl UI {
                         }
                         self.current_windows = CurrentWindows::MainWindow;
                     } else {
-                        terminal.draw(|frame| setting_ui.draw(frame))?;
+                        terminal.draw(|frame| {
+                            setting_ui.draw(frame);
+                            self.theme.apply(frame.buffer_mut());
+                        })?;
                         setting_ui.handle_key();
                     }
                 }
+                CurrentWindows::ScheduleWindow(ref mut schedule_ui) => {
+                    if schedule_ui.should_exit {
+                        self.current_windows = CurrentWindows::MainWindow;
+                    } else {
+                        terminal.draw(|frame| {
+                            schedule_ui.draw(frame);
+                            self.theme.apply(frame.buffer_mut());
+                        })?;
+                        schedule_ui.handle_key();
+                    }
+                }
+                CurrentWindows::UsageWindow(ref mut usage_ui) => {
+                    if usage_ui.should_exit {
+                        self.current_windows = CurrentWindows::MainWindow;
+                    } else {
+                        terminal.draw(|frame| {
+                            usage_ui.draw(frame);
+                            self.theme.apply(frame.buffer_mut());
+                        })?;
+                        usage_ui.handle_key();
+                    }
+                }
             }
+            self.push_event(AppEvent::Tick);
+        }
+        self.set_bracketed_paste(false);
+        // 程序退出时，保存数据版本变更，数据库检查完成前退出时不保存
+        if self.startup == Startup::Ready {
+            let _ = update_db_version_into_profile();
         }
-        // 程序退出时，保存数据版本变更
-        let _ = update_db_version_into_profile();
         Ok(())
     }
 
+    /// 首帧绘制之后读取配置，并在后台检查数据库，完成后发送 [`AppEvent::DbReady`]
+    fn start_loading(&mut self) {
+        self.startup = Startup::LoadingDb;
+        self.restore_or_new_gemini(None);
+        let outdated = self.db_version.clone().unwrap_or_default() != current_db_version();
+        let tx = self.events.tx.clone();
+        thread::spawn(move || {
+            let _ = tx.send(AppEvent::DbReady(load_db(outdated)));
+        });
+    }
+
+    /// 应用后台检查数据库的结果，数据库损坏时弹窗提示恢复备份或导出仍能读取的会话
+    fn finish_loading(&mut self, result: DbStartup) {
+        self.startup = Startup::Ready;
+        if result.migrated {
+            self.db_version = Some(current_db_version());
+        }
+        if let Some(problem) = result.problem {
+            self.offer_db_repair(&problem);
+        }
+        match result.retention {
+            Ok(0) => {}
+            Ok(count) => self.popups.toast(format!("Deleted {} old conversations", count)),
+            Err(msg) => self.response_status = ResponseStatus::Failed(msg),
+        }
+    }
+
+    /// 开启或关闭终端的粘贴事件，其他界面仍以按键的形式接收粘贴的文本
+    fn set_bracketed_paste(&mut self, enabled: bool) {
+        if self.bracketed_paste == enabled {
+            return;
+        }
+        let result = if enabled {
+            execute!(io::stdout(), EnableBracketedPaste)
+        } else {
+            execute!(io::stdout(), DisableBracketedPaste)
+        };
+        self.bracketed_paste = enabled && result.is_ok();
+    }
+
     fn max_scroll_offset(&self) -> u16 {
         self.chat_show.chat_history_area_height
     }
@@ -166,59 +947,140 @@ impl UI {
         // 尝试读取配置文件
         match read_config() {
             Ok(store_data) => {
-                if let Some(gemini_origin) = self.gemini.clone() {
-                    // gemini 已经存在，则此方法是在settings页面切换到main页面，更新配置信息
-                    let mut gemini_new = Gemini::rebuild(
-                        store_data.key,
-                        store_data.model,
-                        gemini_origin.contents,
-                        store_data.options,
-                    );
-                    gemini_new.set_system_instruction(store_data.system_instruction.unwrap_or("".into()));
-                    self.gemini = Some(gemini_new)
-                } else {
-                    // gemini 不存在，读取到配置文件则直接使用配置文件中的 Gemini API
-                    let mut gemini_new =
-                        Gemini::rebuild(store_data.key, store_data.model, Vec::new(), store_data.options);
-                    gemini_new.set_system_instruction(store_data.system_instruction.unwrap_or("".into()));
-                    // 读取数据库版本
-                    self.db_version = store_data.db_version;
-                    self.gemini = Some(gemini_new)
+                self.tools = store_data.tools.clone().into();
+                self.rate_limits = Arc::new(store_data.rate_limits.clone());
+                self.accessible = store_data.accessible;
+                self.theme = Theme::from_config(store_data.theme.as_deref());
+                self.icons = store_data.icons;
+                self.collapse_lines = store_data.collapse_lines.unwrap_or(DEFAULT_COLLAPSE_LINES);
+                self.title_max_width = store_data.title_max_width.unwrap_or(DEFAULT_TITLE_MAX_WIDTH);
+                self.fallback_model = store_data.fallback_model.clone().map(LanguageModel::from);
+                self.response_modalities = store_data.response_modalities.clone();
+                self.thinking_budget = store_data.thinking_budget;
+                self.focus_order = MainFocusComponent::parse_order(&store_data.focus_order)
+                    .unwrap_or_else(|| MainFocusComponent::iter().collect());
+                self.copy_reply_key = store_data
+                    .copy_reply_key
+                    .as_deref()
+                    .and_then(KeyBinding::parse)
+                    .unwrap_or_default();
+                // 本地工具在发送消息时以函数声明发送，不写入系统指令
+                let system_instruction = store_data.system_instruction.clone().unwrap_or_default();
+                match self.gemini.as_mut() {
+                    // gemini 已经存在，则此方法是在settings页面切换到main页面，更新配置信息，
+                    // 密钥与模型不变时保留客户端以复用连接池
+                    Some(gemini)
+                        if gemini.key == store_data.key && gemini.model.to_string() == store_data.model.to_string() =>
+                    {
+                        gemini.set_options(store_data.options);
+                        gemini.set_system_instruction(system_instruction);
+                    }
+                    Some(gemini) => {
+                        let contents = std::mem::take(&mut gemini.contents);
+                        *gemini = GeminiBuilder::from_config(&store_data)
+                            .contents(contents)
+                            .system_instruction(system_instruction)
+                            .build();
+                    }
+                    // gemini 不存在，读取到配置文件则直接使用配置文件中的 Gemini API，并读取数据库版本
+                    None => {
+                        self.gemini = Some(
+                            GeminiBuilder::from_config(&store_data)
+                                .system_instruction(system_instruction)
+                                .build(),
+                        );
+                        self.db_version = store_data.db_version;
+                    }
                 }
             }
             Err(_) => {
+                self.collapse_lines = DEFAULT_COLLAPSE_LINES;
+                self.title_max_width = DEFAULT_TITLE_MAX_WIDTH;
+                self.focus_order = MainFocusComponent::iter().collect();
                 if let Some(key) = key {
                     // 尝试从 key 构造 Gemini API
                     self.init_gemini(key);
                 } else if let Ok(key) = std::env::var(ENV_NAME) {
                     // 尝试从环境变量中读取密钥
                     self.init_gemini(key);
+                } else if self.offline {
+                    // 离线模式不需要密钥，也不保存配置
+                    self.gemini = Some(GeminiBuilder::default().build());
                 }
             }
         }
+        self.check_key_limits();
+    }
+
+    /// 在后台检查密钥能否使用当前的模型与生成参数，发现问题时显示在状态栏，网络错误时不提醒
+    fn check_key_limits(&self) {
+        if self.offline {
+            return;
+        }
+        let Some(gemini) = self.gemini.as_ref().filter(|gemini| !gemini.key.is_empty()) else {
+            return;
+        };
+        let key = gemini.key.clone();
+        let model = gemini.model.to_string();
+        let options = gemini.options.clone();
+        let tx = self.events.tx.clone();
+        thread::spawn(move || {
+            if let Ok(warnings) = check_key_limits(&key, &model, &options) {
+                if !warnings.is_empty() {
+                    let _ = tx.send(AppEvent::Warning(warnings.join("; ")));
+                }
+            }
+        });
     }
 
     /// 设置图片或清除图片路径
     fn show_image_input(&mut self) {
-        if self.image_url_input_popup.is_none() {
-            self.image_url_input_popup = Some(InputPopup::new(self.image_path.clone().unwrap_or_default(), 50, 3));
+        let popup = InputPopup::new(self.image_path.clone().unwrap_or_default(), 50, 3);
+        self.popups.open(MainPopup::ImagePath(popup));
+        self.validate_image_input();
+    }
+
+    /// 校验图片输入弹窗中的路径，在弹窗标题中显示图片信息或错误原因，返回路径是否可用
+    fn validate_image_input(&mut self) -> bool {
+        let Some(MainPopup::ImagePath(popup)) = self.popups.top_mut() else {
+            return false;
+        };
+        let path = expand_tilde(popup.input_text.get_content().trim());
+        let path = path.as_str();
+        // 网络图片在发送时才会下载，空路径表示清除图片
+        let result = if path.is_empty() || path.starts_with("https://") || path.starts_with("http://") {
+            Ok(None)
+        } else {
+            inspect_image(path).map(Some)
+        };
+        match result {
+            Ok(info) => {
+                popup.title = match info {
+                    Some(info) => format!("Image Path Or URL ({})", info.summary()),
+                    None => "Image Path Or URL".into(),
+                };
+                popup.border_color = Color::Blue;
+                true
+            }
+            Err(e) => {
+                popup.title = format!("Image Path Or URL ({})", e);
+                popup.border_color = Color::Red;
+                false
+            }
         }
     }
 
     /// 初始化 Gemini API
     fn init_gemini(&mut self, key: String) {
-        let system_instruction = String::new();
-        let mut gemini = Gemini::new(key, LanguageModel::Gemini1_5Flash);
-        gemini.set_options(GenerationConfig::default());
-        gemini.set_system_instruction(system_instruction.clone());
         let data = StoreData {
-            key: gemini.key.clone(),
-            model: gemini.model.clone(),
-            system_instruction: Some(system_instruction),
-            options: gemini.options.clone(),
+            key,
+            model: LanguageModel::Gemini1_5Flash,
+            system_instruction: Some(String::new()),
+            options: GenerationConfig::default(),
             db_version: None,
+            ..Default::default()
         };
-        gemini.start_chat(Vec::new());
+        let gemini = GeminiBuilder::from_config(&data).build();
         let _ = save_config(data);
         self.gemini = Some(gemini)
     }
@@ -237,32 +1099,135 @@ impl UI {
 impl UI {
     /// 侧边栏宽度
     const SIDEBAR_WIDTH: u16 = 30;
+    /// 检查定时任务的间隔
+    const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+    /// 主界面的最小宽度
+    const MIN_WIDTH: u16 = 40;
+    /// 主界面的最小高度
+    const MIN_HEIGHT: u16 = 10;
 
-    /// 绘制UI
-    fn draw(&mut self, frame: &mut Frame) {
+    /// 绘制UI，不依赖真实终端，可以绘制到任意后端
+    pub(crate) fn draw(&mut self, frame: &mut Frame) {
         let area = frame.area();
+        // 终端过小时无法布局，只显示提示
+        if area.width < Self::MIN_WIDTH || area.height < Self::MIN_HEIGHT {
+            self.render_too_small(frame, area);
+            return;
+        }
+        // 数据库检查完成前只显示加载提示，不查询会话列表
+        if self.startup != Startup::Ready {
+            let loading = Paragraph::new("Loading chats...").centered().fg(Color::Cyan);
+            frame.render_widget(loading, Rect::new(area.x, area.y + area.height / 2, area.width, 1));
+            return;
+        }
         // 左侧宽度
-        if self.chat_item_list.show {
+        if self.accessible {
+            self.draw_accessible(frame, area);
+        } else if self.chat_item_list.show {
             let [left_area, right_area] = Layout::horizontal([Length(Self::SIDEBAR_WIDTH), Fill(1)]).areas(area);
             self.render_left_area(frame, left_area);
             self.render_right_area(frame, right_area);
         } else {
             self.render_right_area(frame, area);
         }
-        // 是否显示删除弹窗
-        if let Some(popup) = self.chat_item_list.popup_delete_confirm_dialog.clone() {
-            let x = (area.width - popup.width as u16) / 2;
-            let y = (area.height - popup.height as u16) / 2;
-            let rect = Rect::new(x, y, popup.width as u16, popup.height as u16);
-            popup.draw(frame, rect);
+        // 同步请求队列弹窗中显示的请求
+        for popup in self.popups.iter_mut() {
+            if let MainPopup::Queue { requests, .. } = popup {
+                *requests = self.tasks.tasks().to_vec();
+            }
+        }
+        self.popups.draw(frame, area);
+        // 无障碍模式下提示信息在状态行中播报
+        if !self.accessible {
+            self.popups.draw_toast(frame, area);
+        }
+    }
+
+    /// 渲染终端过小的提示
+    fn render_too_small(&self, frame: &mut Frame, area: Rect) {
+        let text = format!(
+            "Terminal too small\n{}x{}, need at least {}x{}",
+            area.width,
+            area.height,
+            Self::MIN_WIDTH,
+            Self::MIN_HEIGHT
+        );
+        let paragraph = Paragraph::new(text)
+            .style(Style::default().fg(Color::LightYellow))
+            .centered()
+            .wrap(Wrap { trim: true });
+        let y = area.height.saturating_sub(2) / 2;
+        frame.render_widget(paragraph, Rect::new(area.x, area.y + y, area.width, area.height - y));
+    }
+
+    /// 无障碍模式下按从上到下的顺序线性渲染：状态、会话列表、标题、聊天记录、输入框
+    fn draw_accessible(&mut self, frame: &mut Frame, area: Rect) {
+        let list_height = if self.chat_item_list.show { area.height / 3 } else { 0 };
+        let [status_area, list_area, title_area, chat_area, input_area] =
+            Layout::vertical([Length(1), Length(list_height), Length(1), Fill(1), Length(1)]).areas(area);
+        // 状态变化固定在第一行播报
+        frame.render_widget(Paragraph::new(format!("Status: {}", self.announcement())), status_area);
+        if self.chat_item_list.show {
+            let is_focused = matches!(self.focus_component, MainFocusComponent::ChatItemList);
+            self.chat_item_list.draw_plain(frame, list_area, is_focused);
+        }
+        let mut title = format!("Chat: {}", if self.title.is_empty() { "New chat" } else { &self.title });
+        if self.tabs.len() > 1 {
+            title.push_str(&format!(", tab {} of {}", self.active_tab + 1, self.tabs.len()));
+        }
+        frame.render_widget(Paragraph::new(title), title_area);
+        self.chat_show.draw_plain(frame, chat_area);
+        let label = if self.gemini.is_none() { "Key: " } else { "Input: " };
+        self.input_field_component
+            .set_width_height((input_area.width as usize).saturating_sub(label.len()), 1);
+        let input = format!("{}{}", label, self.input_field_component.should_show_text());
+        frame.render_widget(Paragraph::new(input), input_area);
+        if matches!(self.focus_component, MainFocusComponent::InputField) {
+            let (x, _) = self.input_field_component.get_cursor_position();
+            frame.set_cursor_position(CursorPosition::new(
+                input_area.x + (label.len() + x) as u16,
+                input_area.y,
+            ));
+        }
+    }
+
+    /// 无障碍模式下播报的状态：提示信息、错误、等待回复或当前焦点
+    fn announcement(&self) -> String {
+        if let Some(message) = self.popups.toast_message() {
+            return message.to_string();
+        }
+        if let ResponseStatus::Failed(msg) = &self.response_status {
+            return format!("Error, {}", msg);
+        }
+        if self.recording.is_some() {
+            return "Recording, press F10 to stop".into();
         }
-        // 是否显示图片输入弹窗
-        if let Some(ref mut popup) = self.image_url_input_popup {
-            popup.set_size(area.width.saturating_sub(50).max(50) as usize, 3);
-            let x = (area.width - popup.width as u16) / 2;
-            let y = (area.height - popup.height as u16) / 2;
-            let rect = Rect::new(x, y, popup.width as u16, popup.height as u16);
-            popup.draw(frame, rect);
+        if self.receiving_message() {
+            return "Waiting for reply".into();
+        }
+        if let Some(task) = self.tasks.latest() {
+            return format!("Running {}", task.label);
+        }
+        let (mode, target) = self.key_target();
+        format!("Ready, {} mode, focus on {}", mode.to_lowercase(), target)
+    }
+
+    /// 按键输入的去向，返回输入模式（INSERT 输入文本，NORMAL 导航，POPUP 弹窗）与聚焦位置，
+    /// 判断顺序与按键事件的处理顺序一致
+    fn key_target(&self) -> (&'static str, String) {
+        if let Some(popup) = self.popups.top() {
+            return ("POPUP", popup.label().into());
+        }
+        if self.title_editor_input_field.is_some() {
+            return ("INSERT", "title".into());
+        }
+        if self.chat_item_list.title_editor.is_some() {
+            return ("INSERT", "chat list > rename".into());
+        }
+        let focus = self.focus_component.label();
+        match self.focus_component {
+            MainFocusComponent::InputField => ("INSERT", focus.into()),
+            _ => ("NORMAL", focus.into()),
         }
     }
 
@@ -283,7 +1248,7 @@ impl UI {
                 Color::White
             },
         ));
-        let new_chat_button_text = Paragraph::new("New Chat")
+        let new_chat_button_text = Paragraph::new(Icon::NewChat.label(self.icons, "New Chat"))
             .style(Style::default().fg(Color::LightBlue))
             .block(new_chat_button_block)
             .centered();
@@ -299,7 +1264,7 @@ impl UI {
                 Color::White
             },
         ));
-        let setting_button_text = Paragraph::new("Setting")
+        let setting_button_text = Paragraph::new(Icon::Setting.label(self.icons, "Setting"))
             .style(Style::default().fg(Color::LightBlue))
             .block(setting_button_block)
             .centered();
@@ -308,14 +1273,22 @@ impl UI {
 
     /// 渲染右侧区域
     fn render_right_area(&mut self, frame: &mut Frame, right_area: Rect) {
-        // 计算显示区域宽度
-        // - 10 留出左右空白区域
-        // -2 文本段落中的左右边框
-        // -3 输入框左右两侧头像部分
-        // -1 对齐中文文本
-        // 如果没有减去这4个宽度，文本可能有显示问题，可以再减去任意宽度，以使得在输出的列表文本右侧留出对应宽度空白
-        let chat_area_width = || right_area.width as usize - 10 - 2 - 3 - 1;
-        let [header_area, chat_area, input_area] = Layout::vertical([Length(1), Fill(1), Length(3)]).areas(right_area);
+        let chat_area_width = || Self::chat_area_width(right_area);
+        // 打开多个标签页时显示标签栏
+        let tab_bar_height = if self.tabs.len() > 1 { 1 } else { 0 };
+        // 存在附件时在输入框上方显示附件
+        let chips = self.attachment_chips();
+        let chips_height = if chips.is_empty() { 0 } else { 1 };
+        let [header_area, tab_bar_area, chat_area, chips_area, input_area] = Layout::vertical([
+            Length(1),
+            Length(tab_bar_height),
+            Fill(1),
+            Length(chips_height),
+            Length(3),
+        ])
+        .areas(right_area);
+        self.render_tab_bar(frame, tab_bar_area);
+        self.render_attachment_chips(frame, chips_area, chips);
         // 输入区域（底部）
         self.render_input_area(frame, input_area);
         // 聊天记录区域（中间）
@@ -324,14 +1297,41 @@ impl UI {
         self.render_header_area(frame, header_area);
     }
 
+    /// 计算聊天区域中消息内容的显示宽度
+    pub(crate) fn chat_area_width(area: Rect) -> usize {
+        // - 10 留出左右空白区域
+        // -2 文本段落中的左右边框
+        // -3 输入框左右两侧头像部分
+        // 字符宽度按 Unicode 显示宽度计算，换行时宽字符不会超出该宽度
+        (area.width as usize).saturating_sub(10 + 2 + 3)
+    }
+
+    /// 渲染标签栏
+    fn render_tab_bar(&mut self, frame: &mut Frame, tab_bar_area: Rect) {
+        if self.tabs.len() < 2 {
+            return;
+        }
+        let tabs = Tabs::new(self.tab_titles())
+            .select(self.active_tab)
+            .style(Style::default().fg(Color::DarkGray))
+            .highlight_style(Style::default().fg(Color::LightBlue));
+        frame.render_widget(tabs, tab_bar_area);
+    }
+
     /// 渲染头部区域
     fn render_header_area(&mut self, frame: &mut Frame, header_area: Rect) {
         let [tip_area, title_area, edit_tip_area] =
             Layout::horizontal([Length(10), Fill(1), Length(10)]).areas(header_area);
         let tip_text = if self.chat_item_list.show { "< F3" } else { "> F3" };
-        let tip_paragraph = Paragraph::new(tip_text)
-            .style(Style::default().fg(Color::Red))
-            .left_aligned();
+        let mut tip_line = Line::from(Span::styled(tip_text, Color::Red));
+        // 有后台请求时显示请求数量，按 F2 查看请求队列
+        if !self.tasks.tasks().is_empty() {
+            tip_line.push_span(Span::styled(
+                format!(" [{}]", self.tasks.tasks().len()),
+                Color::LightYellow,
+            ));
+        }
+        let tip_paragraph = Paragraph::new(tip_line).left_aligned();
         frame.render_widget(tip_paragraph, tip_area);
 
         if self.title_editor_input_field.is_none() {
@@ -367,6 +1367,86 @@ impl UI {
         frame.render_widget(edit_tip_paragraph, edit_tip_area);
     }
 
+    /// 当前附件的描述，依次为图片与命令输出
+    fn attachment_chips(&mut self) -> Vec<String> {
+        let mut chips = Vec::new();
+        if !self.blank_image() {
+            let path = self.image_path.clone().unwrap_or_default();
+            // 图片信息需要读取文件，仅在图片变化时重新读取
+            if self
+                .image_chip
+                .as_ref()
+                .is_none_or(|(cached_path, _)| *cached_path != path)
+            {
+                let name = Path::new(&path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or(path.clone());
+                let label = match inspect_image(&path) {
+                    Ok(info) => format!("{} · {}", name, info.summary()),
+                    Err(_) => name,
+                };
+                self.image_chip = Some((path, label));
+            }
+            if let Some((_, label)) = &self.image_chip {
+                chips.push(Icon::Image.label(self.icons, label));
+            }
+        }
+        if let Some((command, output)) = &self.command_output {
+            let label = format!("{} · text/plain · {:.1} KB", command, output.len() as f64 / 1024.0);
+            chips.push(Icon::Command.label(self.icons, &label));
+        }
+        chips
+    }
+
+    /// 渲染附件，每个附件前显示移除的快捷键序号
+    fn render_attachment_chips(&self, frame: &mut Frame, area: Rect, chips: Vec<String>) {
+        if chips.is_empty() {
+            return;
+        }
+        let mut spans = Vec::new();
+        for (index, chip) in chips.iter().enumerate() {
+            spans.push(Span::styled(
+                format!(" {} ✕ Alt+{} ", chip, index + 1),
+                Style::default().fg(Color::White).bg(Color::DarkGray),
+            ));
+            spans.push(Span::raw(" "));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+
+    /// 移除指定序号的附件，序号与附件的显示顺序一致
+    fn remove_attachment(&mut self, index: usize) {
+        let mut attachments = Vec::new();
+        if !self.blank_image() {
+            attachments.push(0);
+        }
+        if self.command_output.is_some() {
+            attachments.push(1);
+        }
+        match attachments.get(index) {
+            Some(0) => self.image_path = None,
+            Some(_) => self.command_output = None,
+            None => {}
+        }
+    }
+
+    /// 输入框下边框左侧的模式提示，显示输入模式与按键输入的去向
+    fn mode_indicator(&self) -> Title<'static> {
+        let (mode, target) = self.key_target();
+        let color = match mode {
+            "INSERT" => Color::Green,
+            "POPUP" => Color::Yellow,
+            _ => Color::LightBlue,
+        };
+        Title::from(Line::from(vec![
+            Span::styled(format!(" {} ", mode), Style::default().fg(Color::Black).bg(color)),
+            Span::styled(format!(" {} ", target), Color::DarkGray),
+        ]))
+        .position(TitlePosition::Bottom)
+        .alignment(Alignment::Left)
+    }
+
     /// 渲染输入区域
     fn render_input_area(&mut self, frame: &mut Frame, input_area: Rect) {
         // 调整输入框宽度
@@ -375,22 +1455,19 @@ impl UI {
         // 输入区域（底部）
         let input_block_title = if self.gemini.is_none() {
             "Input Key"
+        } else if self.recording.is_some() {
+            "Input Text [● Recording, press F10 to stop]"
         } else {
             "Input Text"
         };
-        // 根据图片是否为空设置文本
-        let title = if self.blank_image() {
-            Title::from("Press F4 Set Image Path")
-                .position(TitlePosition::Top)
-                .alignment(Alignment::Right)
+        // 根据图片是否为空设置文本，已附加的图片显示在输入框上方
+        let title = Title::from(if self.blank_image() {
+            "Press F4 Set Image Path"
         } else {
-            Title::from(format!(
-                "[{}] Press F4 Modify Image Path",
-                self.image_path.clone().unwrap_or_default()
-            ))
-            .position(TitlePosition::Top)
-            .alignment(Alignment::Right)
-        };
+            "Press F4 Modify Image Path"
+        })
+        .position(TitlePosition::Top)
+        .alignment(Alignment::Right);
 
         // 根据是否选中组件变色
         let input_block = Block::bordered()
@@ -400,6 +1477,7 @@ impl UI {
                     .alignment(Alignment::Left),
             )
             .title(title)
+            .title(self.mode_indicator())
             .borders(Borders::ALL)
             .border_style(
                 Style::default().fg(if matches!(self.focus_component, MainFocusComponent::InputField) {
@@ -411,7 +1489,11 @@ impl UI {
         // 输入框内容
         let text = self.input_field_component.should_show_text();
 
-        let input_paragraph = if self.receiving_message {
+        let image_progress = self.image_progress.get(&self.conversation_id).copied();
+        let input_paragraph = if image_progress.is_some() {
+            // 如果正在后台读取会话图片，则在输入框中显示读取进度
+            Paragraph::default().block(input_block)
+        } else if self.receiving_message() {
             // 如果处于等待消息接收状态，则显示等待提示
             Paragraph::new("Receiving message...")
                 .block(input_block)
@@ -429,6 +1511,16 @@ impl UI {
         };
 
         frame.render_widget(input_paragraph, input_area);
+        if let Some((done, total)) = image_progress {
+            frame.render_widget(
+                ProgressBar::new("Loading images", done, total),
+                input_area.inner(Margin::new(1, 1)),
+            );
+        }
+        // 后台任务显示在输入框下边框的右侧
+        let width = (input_area.width / 2).min(40);
+        let status_area = Rect::new(input_area.right() - width - 1, input_area.bottom() - 1, width, 1);
+        self.tasks.draw_status(frame, status_area);
         if matches!(self.focus_component, MainFocusComponent::InputField) {
             let (x, y) = self.input_field_component.get_cursor_position();
             frame.set_cursor_position(CursorPosition::new(
@@ -444,179 +1536,1148 @@ impl UI {
         F: Fn() -> usize,
     {
         let is_focused = matches!(self.focus_component, MainFocusComponent::ChatShow);
-        self.chat_show.draw(frame, chat_area, chat_area_width, is_focused);
+        self.chat_show.icons = self.icons;
+        self.chat_show.collapse_lines = self.collapse_lines;
+        self.chat_show.context_tokens = self.gemini.as_ref().map(ChatSession::token_count);
+        if let Some(split_view) = self.split_view.as_mut() {
+            split_view.icons = self.icons;
+            split_view.collapse_lines = self.collapse_lines;
+            // 分屏时左右两侧各占一半宽度
+            let [left_area, right_area] = Layout::horizontal([Fill(1), Fill(1)]).areas(chat_area);
+            let split_focused = matches!(self.focus_component, MainFocusComponent::SplitView);
+            self.chat_show
+                .draw(frame, left_area, || Self::chat_area_width(left_area), is_focused);
+            split_view.draw(frame, right_area, || Self::chat_area_width(right_area), split_focused);
+        } else {
+            self.chat_show.draw(frame, chat_area, chat_area_width, is_focused);
+        }
     }
 }
 
-/// 处理输入事件
+/// 处理后台请求
 impl UI {
-    /// 处理按键事件
-    fn handle_key(
-        &mut self,
-        chat_tx: mpsc::Sender<ChatType>,
-        title_rx: mpsc::Sender<String>,
-        chat_rx: &mpsc::Receiver<ChatType>,
-    ) {
-        // 如果接收消息位为真
-        if self.receiving_message {
-            // 阻塞接收消息
-            if let Ok(request) = chat_rx.recv() {
-                match request {
-                    ChatType::Simple { message } => {
-                        match self.gemini.as_mut().unwrap().send_simple_message(message) {
-                            // 成功接收响应消息后，将响应消息封装后加入到消息列表以供展示
-                            Ok((response, _)) => {
-                                // 如果 id 为空，则生成唯一 id
-                                if self.conversation_id.is_empty() {
-                                    self.conversation_id = generate_unique_id();
-                                    // 由于是新建会话，若想保持聊天列表选中状态，则需要将选中项加一
-                                    self.chat_item_list.selected_conversation += 1;
-                                }
-                                // 如果标题为空，则总结标题
-                                if self.title.is_empty() && !self.gen_title_ing {
-                                    self.gen_title_ing = true;
-                                    let key = self.gemini.clone().unwrap().key.clone();
-                                    let response = response.clone();
-                                    // 总结标题
-                                    thread::spawn(move || {
-                                        let title = summary_by_gemini(key, response);
-                                        let _ = title_rx.send(title);
-                                    });
-                                }
-                                // 推送用户发送的消息保存到数据库
-                                let chat_message = self.chat_show.chat_history.pop().unwrap();
-                                let _ = save_conversation(
-                                    self.conversation_id.clone(),
-                                    self.title.clone(),
-                                    chat_message.clone(),
-                                );
-                                self.chat_show.chat_history.push(chat_message);
-                                let response = response.replace("\n\n", "\n");
-                                let response = if response.ends_with("\n") {
-                                    response[..response.len() - 1].to_owned()
-                                } else {
-                                    response
-                                };
-                                let chat_message = ChatMessage {
-                                    success: true,
-                                    sender: Bot,
-                                    message: response,
-                                    date_time: Local::now(),
-                                };
-                                // 推送接收到的消息保存到数据库
-                                let _ = save_conversation(
-                                    self.conversation_id.clone(),
-                                    self.title.clone(),
-                                    chat_message.clone(),
-                                );
-                                self.chat_show.chat_history.push(chat_message);
-                            }
-                            // 接收响应消息失败，将响应状态位改为失败，并提供错误信息
-                            Err(e) => {
-                                if let Some(msg) = e.downcast_ref::<String>() {
-                                    self.response_status = ResponseStatus::Failed(msg.clone());
-                                } else {
-                                    self.response_status = ResponseStatus::Failed("Unknown Error".into());
-                                }
-                                // 将最后一条消息状态修改为失败
-                                let mut chat_message = self.chat_show.chat_history.pop().unwrap();
-                                chat_message.success = false;
-                                self.chat_show.chat_history.push(chat_message);
-                            }
-                        }
-                    }
-                    ChatType::Image { message, image_path } => {
-                        match self.gemini.as_mut().unwrap().send_image_message(image_path, message) {
-                            // 成功接收响应消息后，将响应消息封装后加入到消息列表以供展示
-                            Ok((response, _)) => {
-                                // 如果 id 为空，则生成唯一 id
-                                if self.conversation_id.is_empty() {
-                                    self.conversation_id = generate_unique_id();
-                                    // 由于是新建会话，若想保持聊天列表选中状态，则需要将选中项加一
-                                    self.chat_item_list.selected_conversation += 1;
-                                }
-                                // 如果标题为空，则总结标题
-                                if self.title.is_empty() && !self.gen_title_ing {
-                                    self.gen_title_ing = true;
-                                    let key = self.gemini.clone().unwrap().key.clone();
-                                    let response = response.clone();
-                                    // 总结标题
-                                    thread::spawn(move || {
-                                        let title = summary_by_gemini(key, response);
-                                        let _ = title_rx.send(title);
-                                    });
-                                }
-                                // 推送用户发送的消息保存到数据库
-                                let chat_message = self.chat_show.chat_history.pop().unwrap();
-                                let _ = save_conversation(
-                                    self.conversation_id.clone(),
-                                    self.title.clone(),
-                                    chat_message.clone(),
-                                );
-                                self.chat_show.chat_history.push(chat_message);
-                                let response = response.replace("\n\n", "\n");
-                                let response = if response.ends_with("\n") {
-                                    response[..response.len() - 1].to_owned()
-                                } else {
-                                    response
-                                };
-                                let chat_message = ChatMessage {
-                                    success: true,
-                                    sender: Bot,
-                                    message: response,
-                                    date_time: Local::now(),
-                                };
-                                // 推送接收到的消息保存到数据库
-                                let _ = save_conversation(
-                                    self.conversation_id.clone(),
-                                    self.title.clone(),
-                                    chat_message.clone(),
-                                );
-                                self.chat_show.chat_history.push(chat_message);
-                            }
-                            // 接收响应消息失败，将响应状态位改为失败，并提供错误信息
-                            Err(e) => {
-                                if let Some(msg) = e.downcast_ref::<String>() {
-                                    self.response_status = ResponseStatus::Failed(msg.clone());
-                                } else {
-                                    self.response_status = ResponseStatus::Failed("Unknown Error".into());
-                                }
-                                // 将最后一条消息状态修改为失败
-                                let mut chat_message = self.chat_show.chat_history.pop().unwrap();
-                                chat_message.success = false;
-                                self.chat_show.chat_history.push(chat_message);
-                            }
-                        }
-                    }
-                }
-                self.receiving_message = false;
-            }
+    /// 当前会话是否正在等待回复
+    fn receiving_message(&self) -> bool {
+        self.pending_conversations.contains(&self.conversation_id)
+    }
+
+    /// 在后台线程中发送请求，请求完成后通过通道返回响应，期间可以切换到其他会话
+    fn dispatch_request(&mut self, request: ChatType, response_tx: EventSender) {
+        // 只有聊天消息需要完整的客户端上下文，其他请求只使用密钥与模型，避免复制整个会话
+        let Some((key, model)) = self
+            .gemini
+            .as_ref()
+            .map(|gemini| (gemini.key.clone(), gemini.model.clone()))
+        else {
+            return;
+        };
+        let kind = request.kind();
+        // 聊天消息在生成会话 id 之后再加入请求队列
+        let request_id = if matches!(
+            request,
+            ChatType::Simple { .. } | ChatType::Image { .. } | ChatType::Search { .. } | ChatType::ToolResponse { .. }
+        ) {
+            String::new()
+        } else {
+            self.track_request(kind, self.conversation_id.clone(), self.title.clone())
+        };
+        let (message, image_path, search, tool_call) = match request {
+            ChatType::Simple { message } => (message, None, false, None),
+            ChatType::Image { message, image_path } => (message, Some(image_path), false, None),
+            ChatType::Search { message } => (message, None, true, None),
+            ChatType::ToolResponse { call, output } => (output, None, false, Some(call)),
+            ChatType::Translate { index, text } => {
+                self.dispatch_translation(request_id, index, text, key, model, response_tx);
+                return;
+            }
+            ChatType::Alternative { index, prompt } => {
+                if let Some(gemini) = self.gemini.as_ref() {
+                    self.dispatch_alternative(request_id, index, prompt, gemini, response_tx);
+                }
+                return;
+            }
+            ChatType::Transcribe { path } => {
+                thread::spawn(move || {
+                    let result = transcribe(&key, &model, path).map_err(|e| e.to_string());
+                    let _ = response_tx.send(AppEvent::ResponseDone(request_id, ChatResponse::Transcription(result)));
+                });
+                return;
+            }
+            ChatType::Screenshot { command } => {
+                thread::spawn(move || {
+                    let result = capture_screenshot(&command).map_err(|e| e.to_string());
+                    let _ = response_tx.send(AppEvent::ResponseDone(request_id, ChatResponse::Screenshot(result)));
+                });
+                return;
+            }
+            ChatType::Tool { tool, call } => {
+                let conversation_id = self.conversation_id.clone();
+                thread::spawn(move || {
+                    let output = run_tool(&tool, &call).unwrap_or_else(|e| e.to_string());
+                    let response = ChatResponse::ToolOutput {
+                        conversation_id,
+                        call,
+                        output,
+                    };
+                    let _ = response_tx.send(AppEvent::ResponseDone(request_id, response));
+                });
+                return;
+            }
+            ChatType::Command { command } => {
+                thread::spawn(move || {
+                    let output = run_command(&command).unwrap_or_else(|e| e.to_string());
+                    let _ = response_tx.send(AppEvent::ResponseDone(
+                        request_id,
+                        ChatResponse::CommandOutput { command, output },
+                    ));
+                });
+                return;
+            }
+            ChatType::GenerateImage { prompt } => {
+                let model = read_config()
+                    .ok()
+                    .and_then(|config| config.image_model)
+                    .unwrap_or(DEFAULT_IMAGE_MODEL.into());
+                let conversation_id = self.conversation_id.clone();
+                thread::spawn(move || {
+                    let result = generate_image(&key, &model, &prompt).map_err(|e| e.to_string());
+                    let _ = response_tx.send(AppEvent::ResponseDone(
+                        request_id,
+                        ChatResponse::Image {
+                            conversation_id,
+                            result,
+                        },
+                    ));
+                });
+                return;
+            }
+            ChatType::LoadImages {
+                conversation_id,
+                images,
+            } => {
+                thread::spawn(move || {
+                    let total = images.len();
+                    let mut parts = Vec::new();
+                    for (done, (index, image_record_id, image_path)) in images.into_iter().enumerate() {
+                        if let Some(part) = read_image_data(image_record_id, image_path) {
+                            parts.push((index, part));
+                        }
+                        // 进度消息不附带请求 id，不会将请求移出请求队列
+                        let progress = ChatResponse::ImageProgress {
+                            conversation_id: conversation_id.clone(),
+                            done: done + 1,
+                            total,
+                        };
+                        let _ = response_tx.send(AppEvent::ResponseChunk(progress));
+                    }
+                    let response = ChatResponse::ImagesLoaded {
+                        conversation_id,
+                        images: parts,
+                    };
+                    let _ = response_tx.send(AppEvent::ResponseDone(request_id, response));
+                });
+                return;
+            }
+            ChatType::Share { file_name, markdown } => {
+                let config = read_config().unwrap_or_default().share;
+                thread::spawn(move || {
+                    let result = share_markdown(&config, &file_name, markdown).map_err(|e| e.to_string());
+                    let _ = response_tx.send(AppEvent::ResponseDone(request_id, ChatResponse::Share(result)));
+                });
+                return;
+            }
+        };
+        // 新建的会话在发送时生成 id，以便回复能送达正确的会话
+        let new_conversation = self.conversation_id.is_empty();
+        if new_conversation {
+            self.conversation_id = generate_unique_id();
+        }
+        let conversation_id = self.conversation_id.clone();
+        let title = self.title.clone();
+        let (Some(user_message), Some(gemini)) = (self.chat_show.chat_history.last().cloned(), self.gemini.as_mut())
+        else {
+            return;
+        };
+        // 上下文移交给请求线程，不复制图片等内容，回复后随客户端一起送回会话；
+        // 其余配置很小，客户端内部的连接池在复制后共享
+        let contents = std::mem::take(&mut gemini.contents);
+        let mut gemini = gemini.clone();
+        gemini.contents = contents;
+        self.pending_conversations.insert(conversation_id.clone());
+        let request_id = self.track_request(kind, conversation_id.clone(), title.clone());
+        let fallback_model = self.fallback_model.clone();
+        let options = SendOptions {
+            search,
+            modalities: self.response_modalities.clone(),
+            thinking_budget: self.thinking_budget,
+            tools: self.tools.clone(),
+        };
+        let offline = self.offline;
+        let rate_limits = self.rate_limits.clone();
+        thread::spawn(move || {
+            let set_status = |status: Option<String>| {
+                let _ = response_tx.send(AppEvent::ResponseChunk(ChatResponse::Status {
+                    request_id: request_id.clone(),
+                    status,
+                }));
+            };
+            // 过大的图片先在后台缩小再发送，减少编码与发送时占用的内存，缩小失败时发送原图
+            let inline_image = image_path.as_ref().map(|path| {
+                set_status(Some("Resizing image".into()));
+                shrink_inline_image(path).unwrap_or_else(|_| path.clone())
+            });
+            let send = |gemini: &mut Gemini| {
+                // 按模型的速率限制等待，等待期间在状态栏显示剩余时间
+                let limit = rate_limits.get(&gemini.model.to_string()).copied().unwrap_or_default();
+                let tokens = gemini.token_count() + estimate_tokens(&message);
+                let mut throttled = false;
+                acquire(&gemini.model.to_string(), limit, tokens, |seconds| {
+                    throttled = true;
+                    set_status(Some(format!("Throttled, retrying in {}s", seconds)));
+                })
+                .map_err(|e| e.to_string())?;
+                let status = inline_image
+                    .as_deref()
+                    .and_then(|path| inspect_image(path).ok())
+                    .map(|info| format!("Sending {}", info.summary()));
+                if throttled || status.is_some() {
+                    set_status(status);
+                }
+                let result = match (&tool_call, offline) {
+                    (Some(call), true) => {
+                        OfflineSession::new(&mut gemini.contents).send_tool_response(call, message.clone(), &options)
+                    }
+                    (Some(call), false) => gemini.send_tool_response(call, message.clone(), &options),
+                    (None, true) => OfflineSession::new(&mut gemini.contents).send_chat_message(
+                        message.clone(),
+                        inline_image.clone(),
+                        &options,
+                    ),
+                    (None, false) => gemini.send_chat_message(message.clone(), inline_image.clone(), &options),
+                };
+                if let Ok(reply) = &result {
+                    track_usage(gemini, &reply.text);
+                }
+                result.map_err(|e| {
+                    if let Some(msg) = e.downcast_ref::<String>() {
+                        msg.clone()
+                    } else {
+                        e.to_string()
+                    }
+                })
+            };
+            let mut result = send(&mut gemini);
+            // 主模型容量不足时使用备用模型重试一次，实际使用的模型只用于显示，之后的消息仍使用主模型
+            let mut answered_by = None;
+            if let Some(fallback) = fallback_model.filter(|model| model.to_string() != gemini.model.to_string()) {
+                if result.as_ref().is_err_and(|msg| is_capacity_error(msg)) {
+                    let primary = std::mem::replace(&mut gemini.model, fallback.clone());
+                    result = send(&mut gemini);
+                    gemini.model = primary;
+                    answered_by = result.is_ok().then(|| fallback.to_string());
+                }
+            }
+            // 删除缩小后的临时图片
+            if let (Some(inline_image), Some(image_path)) = (&inline_image, &image_path) {
+                if inline_image != image_path {
+                    let _ = fs::remove_file(inline_image);
+                }
+            }
+            // 在后台压缩并缓存图片，避免保存消息时阻塞界面
+            let mut cached_image = None;
+            if let (Ok(_), Some(image_path)) = (&result, image_path) {
+                cached_image = cache_image_by_hash(image_path).ok();
+            }
+            let (result, attachments, reasoning, tool_call, citations) = match result {
+                Ok(reply) => (
+                    Ok(reply.text),
+                    reply.attachments,
+                    reply.reasoning,
+                    reply.tool_call,
+                    reply.citations,
+                ),
+                Err(msg) => (Err(msg), Vec::new(), None, None, None),
+            };
+            let response = ChatResponse::Chat {
+                conversation_id,
+                title,
+                new_conversation,
+                user_message: Box::new(user_message),
+                gemini: Box::new(gemini),
+                cached_image,
+                fallback_model: answered_by,
+                attachments,
+                reasoning,
+                tool_call,
+                citations,
+                result,
+            };
+            let _ = response_tx.send(AppEvent::ResponseDone(request_id, response));
+        });
+    }
+
+    /// 在后台线程中翻译文本，使用独立的客户端，不影响会话上下文
+    fn dispatch_translation(
+        &self,
+        request_id: String,
+        index: Option<usize>,
+        text: String,
+        key: String,
+        model: LanguageModel,
+        response_tx: EventSender,
+    ) {
+        let conversation_id = self.conversation_id.clone();
+        let store_data = read_config().unwrap_or_default();
+        let source = store_data.source_language.unwrap_or(AUTO_LANGUAGE.into());
+        let target = store_data.target_language.unwrap_or(DEFAULT_TARGET_LANGUAGE.into());
+        thread::spawn(move || {
+            let result = translate(key, model, &source, &target, text.clone()).map_err(|e| e.to_string());
+            let response = match index {
+                Some(index) => ChatResponse::Translate {
+                    conversation_id,
+                    index,
+                    result,
+                },
+                None => ChatResponse::ClipboardTranslation { text, result },
+            };
+            let _ = response_tx.send(AppEvent::ResponseDone(request_id, response));
+        });
+    }
+
+    /// 在后台线程中执行到期的定时任务，每隔一段时间检查一次
+    fn run_due_schedules(&mut self, response_tx: EventSender) {
+        if self
+            .last_schedule_check
+            .is_some_and(|checked_at| checked_at.elapsed() < Self::SCHEDULE_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.last_schedule_check = Some(Instant::now());
+        let Some(gemini) = self.gemini.as_ref() else {
+            return;
+        };
+        let (key, model) = (gemini.key.clone(), gemini.model.clone());
+        // 定时任务无法执行本地工具，使用配置中不含工具说明的系统指令
+        let system_instruction = read_config()
+            .ok()
+            .and_then(|config| config.system_instruction)
+            .unwrap_or_default();
+        for schedule in take_due_schedules().unwrap_or_default() {
+            let key = key.clone();
+            let model = model.clone();
+            let system_instruction = system_instruction.clone();
+            let response_tx = response_tx.clone();
+            let request_id = self.track_request("schedule", String::new(), schedule.schedule_name.clone());
+            thread::spawn(move || {
+                let message = schedule_message(&schedule);
+                let user_message = ChatMessage {
+                    success: true,
+                    sender: User("".into()),
+                    message: message.as_ref().unwrap_or(&schedule.schedule_prompt).clone(),
+                    date_time: Local::now(),
+                    record_id: None,
+                    attachments: Vec::new(),
+                    raw: None,
+                };
+                let result = message
+                    .and_then(|message| run_schedule(key, model, system_instruction, message))
+                    .map_err(|e| e.to_string());
+                let response = ChatResponse::Schedule {
+                    schedule_name: schedule.schedule_name,
+                    user_message,
+                    result,
+                };
+                let _ = response_tx.send(AppEvent::ResponseDone(request_id, response));
+            });
+        }
+    }
+
+    /// 在后台线程中使用另一个模型重新回答最后一个问题，上下文与原回复相同
+    fn dispatch_alternative(
+        &self,
+        request_id: String,
+        index: usize,
+        prompt: String,
+        gemini: &Gemini,
+        response_tx: EventSender,
+    ) {
+        let conversation_id = self.conversation_id.clone();
+        let model = if matches!(gemini.model, LanguageModel::Gemini1_5Pro) {
+            LanguageModel::Gemini1_5Flash
+        } else {
+            LanguageModel::Gemini1_5Pro
+        };
+        // 去掉最后一轮问答，重新提问
+        let position = gemini
+            .contents
+            .iter()
+            .rposition(|content| matches!(content.role, Some(Role::User)))
+            .unwrap_or(gemini.contents.len());
+        let contents = gemini.contents[..position].to_vec();
+        let mut alternative = GeminiBuilder::from_client(gemini)
+            .model(model.clone())
+            .contents(contents)
+            .build();
+        let model = model.to_string();
+        thread::spawn(move || {
+            let result = alternative
+                .send_simple_message(prompt)
+                .inspect(|(response, _)| track_usage(&alternative, response))
+                .map(|(response, _)| tidy_response(response))
+                .map_err(|e| e.to_string());
+            let response = ChatResponse::Alternative {
+                conversation_id,
+                index,
+                model,
+                result,
+            };
+            let _ = response_tx.send(AppEvent::ResponseDone(request_id, response));
+        });
+    }
+
+    /// 将可以取消的请求登记到任务列表，返回请求 id
+    fn track_request(&mut self, kind: &'static str, conversation_id: String, title: String) -> String {
+        self.tasks.start(kind, conversation_id, title, true)
+    }
+
+    /// 取消请求队列中选中的请求，后台线程无法中断，其响应到达后会被丢弃
+    fn cancel_request(&mut self, index: usize) {
+        let Some(request) = self.tasks.cancel(index) else {
+            return;
+        };
+        // 取消的聊天消息标记为发送失败，并允许在该会话中继续发送消息
+        if request.label.starts_with("chat") && self.pending_conversations.remove(&request.conversation_id) {
+            if let Some((_, _, chat_show)) = self.conversation_state_mut(&request.conversation_id) {
+                if let Some(message) = chat_show
+                    .chat_history
+                    .iter_mut()
+                    .rfind(|message| matches!(message.sender, User(_)))
+                {
+                    message.success = false;
+                }
+            }
+        }
+        // 取消读取图片后，会话上下文中不包含图片数据
+        if request.label == "load images" && self.image_progress.remove(&request.conversation_id).is_some() {
+            self.pending_conversations.remove(&request.conversation_id);
+        }
+    }
+
+    /// 处理后台请求的响应，回复会保存到对应的会话中，即使该会话当前没有显示
+    fn handle_response(&mut self, response: ChatResponse, tx: EventSender) {
+        match response {
+            ChatResponse::Chat {
+                conversation_id,
+                title,
+                new_conversation,
+                user_message,
+                gemini,
+                cached_image,
+                fallback_model,
+                attachments,
+                reasoning,
+                tool_call,
+                citations,
+                result,
+            } => {
+                self.pending_conversations.remove(&conversation_id);
+                let is_current = conversation_id == self.conversation_id;
+                match result {
+                    // 成功接收响应消息后，将响应消息封装后加入到消息列表以供展示
+                    Ok(response) => {
+                        // 由于是新建会话，若想保持聊天列表选中状态，则需要将选中项加一
+                        if new_conversation {
+                            self.chat_item_list.conversation_added();
+                        }
+                        // 优先使用会话所在标签页中的最新标题
+                        let title = match self.conversation_state_mut(&conversation_id) {
+                            Some((_, current_title, _)) => current_title.clone(),
+                            None => title,
+                        };
+                        // 如果标题为空，则根据对话内容总结标题，对话过短时等到之后的回复再总结，离线模式不总结
+                        let context =
+                            if title.is_empty() && !self.offline && !self.tasks.is_running("title", &conversation_id) {
+                                title_context(gemini.history(), &response)
+                            } else {
+                                None
+                            };
+                        if let Some((context, language)) = context {
+                            let request_id = self.track_request("title", conversation_id.clone(), String::new());
+                            let key = gemini.key.clone();
+                            let conversation_id = conversation_id.clone();
+                            let max_width = self.title_max_width;
+                            // 总结标题
+                            thread::spawn(move || {
+                                let title = summary_by_gemini(key, context, language, max_width);
+                                let response = ChatResponse::Title { conversation_id, title };
+                                let _ = tx.send(AppEvent::ResponseDone(request_id, response));
+                            });
+                        }
+                        // 推送用户发送的消息保存到数据库，使用发送时生成的记录 id
+                        let saved = save_conversation_with_image(
+                            conversation_id.clone(),
+                            title.clone(),
+                            (*user_message).clone(),
+                            cached_image,
+                        );
+                        if let Err(e) = saved {
+                            self.response_status = ResponseStatus::Failed(e.to_string());
+                        }
+                        let mut chat_message = ChatMessage {
+                            success: true,
+                            sender: Bot,
+                            message: tidy_response(response.clone()),
+                            date_time: Local::now(),
+                            record_id: None,
+                            attachments,
+                            raw: Some(response),
+                        };
+                        // 推送接收到的消息保存到数据库
+                        chat_message.record_id =
+                            save_conversation(conversation_id.clone(), title, chat_message.clone()).ok();
+                        // 会话在某个标签页中打开时，将回复送达该标签页
+                        if let Some((gemini_slot, _, chat_show)) = self.conversation_state_mut(&conversation_id) {
+                            *gemini_slot = Some(*gemini);
+                            // 等待回复期间重新加载了该会话时，用户消息尚未保存，需要补充显示
+                            let history = &mut chat_show.chat_history;
+                            if !history.iter().any(|m| m.record_id == user_message.record_id) {
+                                history.push(*user_message);
+                            }
+                            if let Some(model) = fallback_model {
+                                chat_show.fallback_models.insert(history.len(), model);
+                            }
+                            if let Some(reasoning) = reasoning {
+                                chat_show.reasonings.insert(history.len(), reasoning);
+                            }
+                            if let Some(citations) = citations {
+                                chat_show.citations.insert(history.len(), citations);
+                            }
+                            history.push(chat_message);
+                            // 滚动到最新的一条消息
+                            chat_show.scroll_offset = chat_show.chat_history_area_height;
+                        }
+                        // 如果回复中包含工具调用，则弹窗确认是否执行，非当前会话则标记为未读
+                        if is_current {
+                            if let Some(call) = tool_call {
+                                self.prepare_tool_call(call);
+                            }
+                        } else {
+                            self.chat_item_list.unread.insert(conversation_id);
+                        }
+                    }
+                    // 接收响应消息失败，将响应状态位改为失败，并提供错误信息
+                    Err(msg) => {
+                        if is_current {
+                            self.response_status = ResponseStatus::Failed(friendly_error(&msg));
+                        }
+                        if let Some((gemini_slot, _, chat_show)) = self.conversation_state_mut(&conversation_id) {
+                            *gemini_slot = Some(*gemini);
+                            // 将该条消息状态修改为失败
+                            if let Some(chat_message) = chat_show
+                                .chat_history
+                                .iter_mut()
+                                .rev()
+                                .find(|m| m.record_id == user_message.record_id)
+                            {
+                                chat_message.success = false;
+                            }
+                        }
+                    }
+                }
+            }
+            ChatResponse::Translate {
+                conversation_id,
+                index,
+                result,
+            } => match result {
+                Ok(translation) => {
+                    if let Some((_, _, chat_show)) = self.conversation_state_mut(&conversation_id) {
+                        chat_show.translations.insert(index, translation);
+                    }
+                }
+                Err(msg) => self.response_status = ResponseStatus::Failed(friendly_error(&msg)),
+            },
+            ChatResponse::ClipboardTranslation { text, result } => match result {
+                Ok(translation) => self.popups.open(MainPopup::Translation {
+                    text,
+                    translation,
+                    scroll: 0,
+                }),
+                Err(msg) => self.response_status = ResponseStatus::Failed(friendly_error(&msg)),
+            },
+            ChatResponse::Alternative {
+                conversation_id,
+                index,
+                model,
+                result,
+            } => match result {
+                Ok(message) => {
+                    if let Some((_, _, chat_show)) = self.conversation_state_mut(&conversation_id) {
+                        chat_show.alternative = Some(AlternativeAnswer { index, model, message });
+                    }
+                }
+                Err(msg) => self.response_status = ResponseStatus::Failed(friendly_error(&msg)),
+            },
+            // 分享成功后将链接复制到剪贴板
+            ChatResponse::Share(result) => match result.map(|url| (write_clipboard(&url), url)) {
+                Ok((Ok(_), url)) => self.popups.toast(format!("Link copied: {}", url)),
+                Ok((Err(_), url)) => self.popups.toast(format!("Shared: {}", url)),
+                Err(msg) => self.response_status = ResponseStatus::Failed(msg),
+            },
+            // 识别结果插入到输入框光标处
+            ChatResponse::Transcription(result) => match result {
+                Ok(text) => text
+                    .chars()
+                    .map(|c| if c == '\n' { ' ' } else { c })
+                    .for_each(|c| self.input_field_component.enter_char(c)),
+                Err(msg) => self.response_status = ResponseStatus::Failed(friendly_error(&msg)),
+            },
+            // 截图作为图片附加到下一条消息中
+            ChatResponse::Screenshot(result) => match result {
+                Ok(path) => {
+                    self.image_path = Some(path.to_string_lossy().into_owned());
+                    self.popups.toast("Screenshot attached");
+                }
+                Err(msg) => self.response_status = ResponseStatus::Failed(msg),
+            },
+            // 工具输出只发送给发起调用的会话，切换会话后丢弃
+            ChatResponse::ToolOutput {
+                conversation_id,
+                call,
+                output,
+            } => {
+                if conversation_id == self.conversation_id {
+                    self.send_tool_output(call, output, tx);
+                } else {
+                    self.popups.toast(format!("Output of tool {} discarded", call.name));
+                }
+            }
+            ChatResponse::CommandOutput { command, output } => self.command_output = Some((command, output)),
+            // 生成的图片作为附件显示在聊天记录中，不会保存到数据库，也不会加入会话上下文
+            ChatResponse::Image {
+                conversation_id,
+                result,
+            } => match result {
+                Ok(attachments) => {
+                    if let Some((_, _, chat_show)) = self.conversation_state_mut(&conversation_id) {
+                        chat_show.chat_history.push(ChatMessage {
+                            success: true,
+                            sender: Bot,
+                            message: "Generated image".into(),
+                            date_time: Local::now(),
+                            record_id: None,
+                            attachments,
+                            raw: None,
+                        });
+                        chat_show.scroll_offset = chat_show.chat_history_area_height;
+                    }
+                }
+                Err(msg) => self.response_status = ResponseStatus::Failed(friendly_error(&msg)),
+            },
+            ChatResponse::ImageProgress {
+                conversation_id,
+                done,
+                total,
+            } => {
+                // 读取已被取消时忽略进度
+                if let Some(progress) = self.image_progress.get_mut(&conversation_id) {
+                    *progress = (done, total);
+                }
+            }
+            ChatResponse::Progress {
+                request_id,
+                done,
+                total,
+            } => self.tasks.set_progress(&request_id, done, total),
+            ChatResponse::Status { request_id, status } => self.tasks.set_status(&request_id, status),
+            ChatResponse::Title { conversation_id, title } => {
+                let _ = modify_title(conversation_id.clone(), title.clone());
+                if let Some((_, current_title, _)) = self.conversation_state_mut(&conversation_id) {
+                    *current_title = title;
+                }
+            }
+            ChatResponse::Salvage(result) => match result {
+                Ok((count, dir)) => self
+                    .popups
+                    .toast(format!("Exported {} chats to {}", count, dir.display())),
+                Err(msg) => self.response_status = ResponseStatus::Failed(msg),
+            },
+            // 将图片数据补充到会话上下文中，之后才允许继续发送消息
+            ChatResponse::ImagesLoaded {
+                conversation_id,
+                images,
+            } => {
+                self.pending_conversations.remove(&conversation_id);
+                self.image_progress.remove(&conversation_id);
+                if let Some((Some(gemini), _, _)) = self.conversation_state_mut(&conversation_id) {
+                    for (index, part) in images {
+                        if let Some(content) = gemini.contents.get_mut(index) {
+                            content.parts.push(part);
+                        }
+                    }
+                }
+            }
+            ChatResponse::Schedule {
+                schedule_name,
+                user_message,
+                result,
+            } => match result {
+                // 定时任务的结果保存为新的会话，并在聊天列表中标记为未读
+                Ok(response) => {
+                    let conversation_id = generate_unique_id();
+                    let title = format!("{} {}", schedule_name, user_message.date_time.format("%m/%d %H:%M"));
+                    let chat_message = ChatMessage {
+                        success: true,
+                        sender: Bot,
+                        message: tidy_response(response),
+                        date_time: Local::now(),
+                        record_id: None,
+                        attachments: Vec::new(),
+                        raw: None,
+                    };
+                    let _ = save_conversation(conversation_id.clone(), title.clone(), user_message);
+                    let _ = save_conversation(conversation_id.clone(), title, chat_message);
+                    self.chat_item_list.conversation_added();
+                    self.chat_item_list.unread.insert(conversation_id);
+                }
+                Err(msg) => {
+                    self.response_status =
+                        ResponseStatus::Failed(format!("{}: {}", schedule_name, friendly_error(&msg)))
+                }
+            },
+        }
+    }
+
+    /// 获取会话所在标签页的客户端、标题与聊天内容，会话未在任何标签页中打开时返回 None
+    fn conversation_state_mut(
+        &mut self,
+        conversation_id: &str,
+    ) -> Option<(&mut Option<Gemini>, &mut String, &mut ChatShowScrollProps)> {
+        if conversation_id == self.conversation_id {
+            return Some((&mut self.gemini, &mut self.title, &mut self.chat_show));
+        }
+        let active_tab = self.active_tab;
+        self.tabs
+            .iter_mut()
+            .enumerate()
+            .find(|(i, tab)| *i != active_tab && tab.conversation_id == conversation_id)
+            .map(|(_, tab)| (&mut tab.gemini, &mut tab.title, &mut tab.chat_show))
+    }
+}
+
+/// 标签页
+impl UI {
+    /// 保证至少存在一个标签页
+    fn ensure_tabs(&mut self) {
+        if self.tabs.is_empty() {
+            self.tabs.push(ConversationTab::default());
+            self.active_tab = 0;
+        }
+    }
+
+    /// 取出当前标签页的会话状态
+    fn take_tab(&mut self) -> ConversationTab {
+        ConversationTab {
+            conversation_id: std::mem::take(&mut self.conversation_id),
+            title: std::mem::take(&mut self.title),
+            gemini: self.gemini.take(),
+            image_path: self.image_path.take(),
+            chat_show: std::mem::take(&mut self.chat_show),
+        }
+    }
+
+    /// 恢复标签页的会话状态
+    fn restore_tab(&mut self, tab: ConversationTab) {
+        self.conversation_id = tab.conversation_id;
+        self.title = tab.title;
+        self.gemini = tab.gemini;
+        self.image_path = tab.image_path;
+        self.chat_show = tab.chat_show;
+        self.response_status = ResponseStatus::None;
+        self.chat_item_list.mark_read(&self.conversation_id);
+    }
+
+    /// 新建标签页，并在其中开始新的对话
+    fn new_tab(&mut self) {
+        self.ensure_tabs();
+        let current = self.take_tab();
+        // 新标签页沿用当前的客户端，克隆的客户端共享连接池，上下文在新建对话时清空
+        let gemini = current.gemini.clone();
+        self.tabs[self.active_tab] = current;
+        self.tabs.push(ConversationTab::default());
+        self.active_tab = self.tabs.len() - 1;
+        self.gemini = gemini;
+        self.new_conversation();
+    }
+
+    /// 切换到指定标签页
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
             return;
         }
+        self.tabs[self.active_tab] = self.take_tab();
+        let tab = std::mem::take(&mut self.tabs[index]);
+        self.active_tab = index;
+        self.restore_tab(tab);
+    }
+
+    /// 切换到下一个标签页
+    fn next_tab(&mut self) {
+        if !self.tabs.is_empty() {
+            self.switch_tab((self.active_tab + 1) % self.tabs.len());
+        }
+    }
+
+    /// 关闭当前标签页，仅剩一个标签页时不关闭
+    fn close_tab(&mut self) {
+        if self.tabs.len() < 2 {
+            return;
+        }
+        self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        let tab = std::mem::take(&mut self.tabs[self.active_tab]);
+        self.restore_tab(tab);
+    }
+
+    /// 标签页标题列表，等待回复的标签页带有标记
+    fn tab_titles(&self) -> Vec<String> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let (conversation_id, title) = if i == self.active_tab {
+                    (&self.conversation_id, &self.title)
+                } else {
+                    (&tab.conversation_id, &tab.title)
+                };
+                let title = if title.is_empty() { "New Chat" } else { title.as_str() };
+                if self.pending_conversations.contains(conversation_id) {
+                    format!("{} …", title)
+                } else {
+                    title.to_string()
+                }
+            })
+            .collect()
+    }
+}
+
+/// 处理输入事件
+impl UI {
+    /// 将事件加入事件队列
+    fn push_event(&self, event: AppEvent) {
+        let _ = self.events.tx.send(event);
+    }
+
+    /// 将终端事件加入事件队列，只关心按键、粘贴与尺寸变化，
+    /// 不需要真实终端，可以使用合成的终端事件驱动界面状态
+    pub(crate) fn push_terminal_event(&self, event: Event) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.push_event(AppEvent::Key(key)),
+            Event::Paste(text) => self.push_event(AppEvent::Paste(text)),
+            Event::Resize(..) => self.push_event(AppEvent::Resize),
+            _ => {}
+        }
+    }
+
+    /// 处理事件队列中的所有事件，处理事件时产生的新事件也在本次处理，
+    /// 只更新界面状态，不读取终端也不绘制界面
+    pub(crate) fn update(&mut self) {
+        while let Ok(event) = self.events.rx.try_recv() {
+            self.handle_event(event);
+        }
+    }
 
-        // 接收键盘事件
-        if let Ok(Event::Key(key)) = event::read() {
-            if key.kind != KeyEventKind::Press {
+    /// 处理主界面事件
+    fn handle_event(&mut self, event: AppEvent) {
+        // 数据库检查完成前只处理退出按键、后台检查的问题与数据库检查的结果
+        if self.startup != Startup::Ready {
+            match event {
+                AppEvent::Key(key) if key.code == event::KeyCode::Esc => self.should_exit = true,
+                AppEvent::Warning(msg) => self.response_status = ResponseStatus::Failed(msg),
+                AppEvent::DbReady(result) => self.finish_loading(result),
+                _ => {}
+            }
+            return;
+        }
+        let tx = self.events.tx.clone();
+        match event {
+            AppEvent::Key(key) => self.handle_key_event(key, tx.clone()),
+            AppEvent::Paste(text) => self.handle_paste(text, tx.clone()),
+            AppEvent::Resize => self.handle_resize(),
+            // 执行到期的定时任务
+            AppEvent::Tick => self.run_due_schedules(tx.clone()),
+            AppEvent::Request(request) => self.dispatch_request(request, tx.clone()),
+            AppEvent::ResponseChunk(response) => self.handle_response(response, tx.clone()),
+            AppEvent::ResponseDone(request_id, response) => {
+                // 已取消的请求直接丢弃响应
+                if self.tasks.finish(&request_id) {
+                    self.handle_response(response, tx.clone());
+                }
+            }
+            AppEvent::Warning(msg) => self.response_status = ResponseStatus::Failed(msg),
+            AppEvent::DbReady(result) => self.finish_loading(result),
+        }
+    }
+
+    /// 处理终端尺寸变化，消息换行与聊天记录区域高度在下一次绘制时按新尺寸重新计算，
+    /// 原来滚动到底部的聊天记录在重新计算后仍然停留在底部
+    fn handle_resize(&mut self) {
+        let chat_shows = [Some(&mut self.chat_show), self.split_view.as_mut()];
+        for chat_show in chat_shows.into_iter().flatten() {
+            if chat_show.scroll_offset >= chat_show.chat_history_area_height {
+                chat_show.scroll_offset = u16::MAX;
+            }
+            chat_show.layout_cache.clear();
+        }
+        self.needs_clear = true;
+    }
+
+    /// 处理按键事件
+    fn handle_key_event(&mut self, key: event::KeyEvent, chat_tx: EventSender) {
+        // 复制最后一条回复，不受焦点影响
+        if self.copy_reply_key.matches(&key) {
+            self.copy_last_reply();
+            return;
+        }
+        // 有打开的弹窗时，按键只交给最上层的弹窗处理
+        if self.popups.is_open() {
+            self.handle_popup_key_event(key, chat_tx);
+            return;
+        }
+        // 如果正在编辑标题
+        if self.title_editor_input_field.is_some() {
+            self.handle_title_edit_key_event(key);
+            return;
+        }
+        // 如果正在聊天列表中重命名会话
+        if self.chat_item_list.title_editor.is_some() {
+            self.handle_rename_key_event(key);
+            return;
+        }
+        // 标签页与分屏快捷键
+        if self.handle_tab_key_event(key) {
+            return;
+        }
+        // 侧边栏显示时，Alt+1..9 打开聊天列表中对应序号的会话，输入框中有附件时优先删除附件
+        if let event::KeyCode::Char(c @ '1'..='9') = key.code {
+            let removes_attachment = matches!(self.focus_component, MainFocusComponent::InputField)
+                && (!self.blank_image() || self.command_output.is_some());
+            if key.modifiers.contains(event::KeyModifiers::ALT) && self.chat_item_list.show && !removes_attachment {
+                self.quick_open(c as usize - '1' as usize, chat_tx);
                 return;
             }
-            // 如果正在编辑标题
-            if self.title_editor_input_field.is_some() {
-                self.handle_title_edit_key_event(key);
+        }
+
+        match self.focus_component {
+            // 当聚焦于输入框时，处理输入
+            MainFocusComponent::InputField => self.handle_input_key_event(key, chat_tx),
+            // 当聚焦于新建聊天按钮时，处理输入
+            MainFocusComponent::NewChatButton => self.handle_new_chat_key_event(key),
+            // 当聚焦于聊天列表时，处理输入
+            MainFocusComponent::ChatItemList => self.handle_chat_list_key_event(key, chat_tx),
+            // 当聚焦于设置按钮时，处理输入
+            MainFocusComponent::SettingButton => self.handle_setting_button_key_event(key),
+            // 当聚焦于聊天内容显示区域时，处理输入
+            MainFocusComponent::ChatShow => self.handle_chat_show_key_event(key, chat_tx),
+            // 当聚焦于分屏区域时，处理输入
+            MainFocusComponent::SplitView => self.handle_split_view_key_event(key),
+        }
+    }
+
+    /// 将按键事件交给最上层的弹窗处理
+    fn handle_popup_key_event(&mut self, key: event::KeyEvent, chat_tx: EventSender) {
+        match self.popups.top() {
+            Some(MainPopup::Queue { .. }) => self.handle_queue_popup_key_event(key),
+            Some(MainPopup::Templates(..)) => self.handle_template_popup_key_event(key),
+            Some(MainPopup::Links(..)) => self.handle_link_popup_key_event(key),
+            Some(MainPopup::Switcher(_)) => self.handle_switcher_popup_key_event(key, chat_tx),
+            Some(MainPopup::Confirm(..)) => self.handle_confirm_key_event(key, chat_tx),
+            Some(MainPopup::DeleteChat(_)) => self.handle_delete_chat_key_event(key),
+            Some(MainPopup::SaveFile(_)) => self.handle_file_path_popup_key_event(key),
+            Some(MainPopup::ImagePath(_)) => self.handle_image_popup_key_event(key),
+            Some(MainPopup::Translation { .. }) => self.handle_translation_popup_key_event(key),
+            None => {}
+        }
+    }
+
+    /// 处理粘贴的文本，在输入框中粘贴图片文件路径时询问是否作为图片附加，否则逐个字符输入
+    fn handle_paste(&mut self, text: String, chat_tx: EventSender) {
+        let popup_shown = self.popups.is_open()
+            || self.title_editor_input_field.is_some()
+            || self.chat_item_list.title_editor.is_some();
+        if matches!(self.focus_component, MainFocusComponent::InputField) && !popup_shown {
+            if let Some(path) = pasted_file_path(&text).filter(|path| inspect_image(path).is_ok()) {
+                let name = Path::new(&path)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned();
+                self.show_confirm(format!("Attach {}?", name), ConfirmAction::AttachImage { path, text });
                 return;
             }
+        }
+        for key in paste_key_events(&text) {
+            self.handle_key_event(key, chat_tx.clone());
+        }
+    }
+
+    /// 当请求队列弹窗打开时，处理输入
+    fn handle_queue_popup_key_event(&mut self, key: event::KeyEvent) {
+        let Some(MainPopup::Queue { selected, .. }) = self.popups.top_mut() else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Esc | event::KeyCode::F(2) => {
+                self.popups.close();
+            }
+            event::KeyCode::Up => *selected = selected.saturating_sub(1),
+            event::KeyCode::Down if *selected + 1 < self.tasks.tasks().len() => *selected += 1,
+            event::KeyCode::Delete | event::KeyCode::Char('c') => {
+                let index = *selected;
+                self.cancel_request(index);
+                let count = self.tasks.tasks().len();
+                if let Some(MainPopup::Queue { selected, .. }) = self.popups.top_mut() {
+                    *selected = index.min(count.saturating_sub(1));
+                }
+            }
+            _ => {}
+        }
+    }
 
-            match self.focus_component {
-                // 当聚焦于输入框时，处理输入
-                MainFocusComponent::InputField => self.handle_input_key_event(key, chat_tx),
-                // 当聚焦于新建聊天按钮时，处理输入
-                MainFocusComponent::NewChatButton => self.handle_new_chat_key_event(key),
-                // 当聚焦于聊天列表时，处理输入
-                MainFocusComponent::ChatItemList => self.handle_chat_list_key_event(key),
-                // 当聚焦于设置按钮时，处理输入
-                MainFocusComponent::SettingButton => self.handle_setting_button_key_event(key),
-                // 当聚焦于聊天内容显示区域时，处理输入
-                MainFocusComponent::ChatShow => self.handle_chat_show_key_event(key),
+    /// 将最后一条模型回复复制到剪贴板
+    fn copy_last_reply(&mut self) {
+        let Some(reply) = self
+            .chat_show
+            .chat_history
+            .iter()
+            .rfind(|message| matches!(message.sender, Bot))
+        else {
+            return;
+        };
+        match write_clipboard(&reply.message) {
+            Ok(_) => self.popups.toast("Copied last reply"),
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 处理新建会话、标签页、分屏、定时任务与用量统计快捷键，返回是否已处理
+    fn handle_tab_key_event(&mut self, key: event::KeyEvent) -> bool {
+        let control = key.modifiers.contains(event::KeyModifiers::CONTROL);
+        match key.code {
+            event::KeyCode::F(7) => self.toggle_split_view(),
+            event::KeyCode::F(9) => self.open_schedule_page(),
+            event::KeyCode::F(2) => self.popups.open(MainPopup::Queue {
+                selected: 0,
+                requests: self.tasks.tasks().to_vec(),
+            }),
+            event::KeyCode::F(12) => self.open_usage_page(),
+            event::KeyCode::Tab if control => self.next_tab(),
+            event::KeyCode::F(6) => self.next_tab(),
+            // 在任何焦点下新建会话，等同于在新建聊天按钮上按下 Enter，之后聚焦输入框
+            event::KeyCode::Char('n') if control => {
+                self.new_conversation();
+                self.focus_component = MainFocusComponent::InputField;
             }
+            event::KeyCode::F(5) => self.new_tab(),
+            event::KeyCode::Char('w') if control => self.close_tab(),
+            event::KeyCode::F(8) => self.close_tab(),
+            event::KeyCode::Char('o') if control => self.open_switcher(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// 当聚焦于分屏区域时，处理输入
+    fn handle_split_view_key_event(&mut self, key: event::KeyEvent) {
+        let Some(split_view) = self.split_view.as_mut() else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Esc => self.should_exit = true,
+            event::KeyCode::Tab => self.next_component(),
+            event::KeyCode::BackTab => self.prev_component(),
+            event::KeyCode::Up => split_view.scroll_offset = split_view.scroll_offset.saturating_sub(1),
+            event::KeyCode::Down => {
+                split_view.scroll_offset = split_view
+                    .scroll_offset
+                    .saturating_add(1)
+                    .min(split_view.chat_history_area_height)
+            }
+            event::KeyCode::Char('[') => split_view.prev_message(),
+            event::KeyCode::Char(']') => split_view.next_message(),
+            event::KeyCode::Char('r') => split_view.toggle_raw_view(),
+            event::KeyCode::Char('z') => split_view.toggle_collapse(),
+            _ => {}
+        }
+    }
+
+    /// 打开最近会话快速切换弹窗
+    fn open_switcher(&mut self) {
+        let conversations = query_all().unwrap_or_default();
+        self.popups.open(MainPopup::Switcher(ConversationSwitcher {
+            query: TextField::new(String::new()),
+            conversations: conversations
+                .into_iter()
+                .take(ConversationSwitcher::RECENT_COUNT)
+                .collect(),
+            selected: 0,
+        }));
+    }
+
+    /// 当快速切换弹窗打开时，处理输入，输入字符时过滤会话
+    fn handle_switcher_popup_key_event(&mut self, key: event::KeyEvent, chat_tx: EventSender) {
+        let Some(MainPopup::Switcher(switcher)) = self.popups.top_mut() else {
+            return;
+        };
+        let count = switcher.matches().len();
+        match key.code {
+            event::KeyCode::Esc => {
+                self.popups.close();
+            }
+            event::KeyCode::Up => switcher.selected = switcher.selected.saturating_sub(1),
+            event::KeyCode::Down => switcher.selected = (switcher.selected + 1).min(count.saturating_sub(1)),
+            event::KeyCode::Enter => {
+                let selected = switcher
+                    .matches()
+                    .get(switcher.selected)
+                    .map(|conversation| (*conversation).clone());
+                self.popups.close();
+                if let Some(conversation) = selected.and_then(|conversation| query_detail_by_id(conversation).ok()) {
+                    self.open_conversation(conversation, chat_tx);
+                }
+            }
+            event::KeyCode::Backspace => {
+                switcher.query.delete_pre_char();
+                switcher.selected = 0;
+            }
+            event::KeyCode::Left => switcher.query.move_cursor_left(switcher.query.get_current_char()),
+            event::KeyCode::Right => switcher.query.move_cursor_right(switcher.query.get_next_char()),
+            event::KeyCode::Char(x) => {
+                switcher.query.enter_char(x);
+                switcher.selected = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// 开启或关闭分屏，开启时在右侧以只读方式显示聊天列表中选中的会话
+    fn toggle_split_view(&mut self) {
+        if self.split_view.take().is_some() {
+            if matches!(self.focus_component, MainFocusComponent::SplitView) {
+                self.focus_component = MainFocusComponent::ChatShow;
+            }
+            return;
+        }
+        // 无障碍模式为线性布局，不支持分屏
+        if self.accessible {
+            return;
+        }
+        if let Some(conversation) = self.chat_item_list.rebuild() {
+            self.split_view = Some(ChatShowScrollProps {
+                chat_history: chat_history_of(&conversation),
+                title: Some(format!("{} (read-only)", conversation.conversation_title)),
+                ..Default::default()
+            });
         }
     }
 
@@ -638,26 +2699,319 @@ impl UI {
         };
     }
 
-    /// 当聚焦于输入框时，处理输入
-    fn handle_input_key_event(&mut self, key: event::KeyEvent, tx: mpsc::Sender<ChatType>) {
-        // 如果输入图片路径的弹窗处于显示状态，则将按键事件视为弹窗的按键事件
-        if let Some(ref mut popup) = self.image_url_input_popup {
-            // 处理弹窗事件，如果存在返回值，
-            match popup.handle_key(key) {
-                component::popup::input_popup::InputPopupHandleEvent::Save(res) => {
-                    self.image_url_input_popup = None;
-                    self.image_path = Some(res);
-                }
-                component::popup::input_popup::InputPopupHandleEvent::Cancel => self.image_url_input_popup = None,
-                component::popup::input_popup::InputPopupHandleEvent::Nothing => {}
+    /// 处理聊天列表中重命名会话的事件，Enter 保存，Esc 取消
+    fn handle_rename_key_event(&mut self, key: event::KeyEvent) {
+        let title_editor = self.chat_item_list.title_editor.as_mut().unwrap();
+        match key.code {
+            event::KeyCode::Enter => self.save_rename(),
+            event::KeyCode::Esc => self.chat_item_list.title_editor = None,
+            event::KeyCode::Backspace => title_editor.delete_pre_char(),
+            event::KeyCode::Left => title_editor.move_cursor_left(title_editor.get_current_char()),
+            event::KeyCode::Right => title_editor.move_cursor_right(title_editor.get_next_char()),
+            event::KeyCode::Home => title_editor.home_of_cursor(),
+            event::KeyCode::End => title_editor.end_of_cursor(),
+            event::KeyCode::Delete => title_editor.delete_suf_char(),
+            event::KeyCode::Char(x) => title_editor.enter_char(x),
+            _ => {}
+        };
+    }
+
+    /// 保存聊天列表中修改的会话标题，会话已在标签页中打开时同步更新标签页的标题
+    fn save_rename(&mut self) {
+        let Some((conversation_id, title)) = self.chat_item_list.finish_rename() else {
+            return;
+        };
+        if let Err(e) = modify_title(conversation_id.clone(), title.clone()) {
+            self.response_status = ResponseStatus::Failed(e.to_string());
+            return;
+        }
+        if let Some((_, current_title, _)) = self.conversation_state_mut(&conversation_id) {
+            *current_title = title;
+        }
+    }
+
+    /// 处理操作确认弹窗事件
+    fn handle_confirm_key_event(&mut self, key: event::KeyEvent, tx: EventSender) {
+        match key.code {
+            event::KeyCode::Tab | event::KeyCode::BackTab | event::KeyCode::Left | event::KeyCode::Right => {
+                if let Some(MainPopup::Confirm(popup, _)) = self.popups.top_mut() {
+                    popup.next_button();
+                }
             }
+            event::KeyCode::Enter => {
+                let Some(MainPopup::Confirm(popup, action)) = self.popups.close() else {
+                    return;
+                };
+                let confirm = popup.press();
+                match action {
+                    ConfirmAction::RunTool(call) if confirm => self.run_tool_call(call, tx),
+                    ConfirmAction::RunCommand(command) if confirm => self.run_shell_command(command, tx),
+                    ConfirmAction::Unlock(conversation_id) if confirm => {
+                        match lock_conversation(conversation_id, false) {
+                            Ok(_) => self.popups.toast("Conversation unlocked"),
+                            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+                        }
+                    }
+                    ConfirmAction::RestoreDb => {
+                        if confirm {
+                            self.restore_db();
+                        } else {
+                            self.salvage_db();
+                        }
+                    }
+                    ConfirmAction::SalvageDb if confirm => self.salvage_db(),
+                    ConfirmAction::ApplyDiff(diff) if confirm => {
+                        if let Err(e) = git_apply(&diff) {
+                            self.response_status = ResponseStatus::Failed(e.to_string());
+                        }
+                    }
+                    ConfirmAction::AttachImage { path, text } => {
+                        if confirm {
+                            self.image_path = Some(path);
+                        } else {
+                            paste_key_events(&text)
+                                .into_iter()
+                                .for_each(|key| self.handle_input_key_event(key, tx.clone()));
+                        }
+                    }
+                    ConfirmAction::WriteFile { path, content } => {
+                        if confirm {
+                            self.write_file(path, content);
+                        }
+                        self.next_pending_file();
+                    }
+                    _ => {}
+                }
+            }
+            event::KeyCode::Esc => {
+                if let Some(MainPopup::Confirm(_, ConfirmAction::WriteFile { .. })) = self.popups.close() {
+                    self.next_pending_file();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 弹窗确认是否执行操作
+    fn show_confirm(&mut self, title: String, action: ConfirmAction) {
+        let popup = DeletePopup {
+            width: (title.chars().count() + 4).max(30),
+            title,
+            ..Default::default()
+        };
+        self.popups.open(MainPopup::Confirm(popup, action));
+    }
+
+    /// 数据库损坏或更新结构失败时，弹窗提示恢复最近一次的备份，没有备份时提示导出仍能读取的会话
+    fn offer_db_repair(&mut self, problem: &str) {
+        let problem = truncate(problem, 30);
+        if has_backup() {
+            self.show_confirm(
+                format!("Database damaged ({}), restore backup? Cancel exports chats", problem),
+                ConfirmAction::RestoreDb,
+            );
         } else {
-            self.handle_input_key_event_common(key, tx);
+            self.show_confirm(
+                format!("Database damaged ({}), export readable chats?", problem),
+                ConfirmAction::SalvageDb,
+            );
+        }
+    }
+
+    /// 使用最近一次的备份恢复数据库并更新数据库结构
+    fn restore_db(&mut self) {
+        match restore_backup().and_then(|_| update_db_structure()) {
+            Ok(_) => {
+                self.db_version = Some(current_db_version());
+                self.popups.toast("Restored the latest backup");
+            }
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 在后台导出损坏的数据库中仍能读取的会话，导出进度显示在状态栏
+    fn salvage_db(&mut self) {
+        // 导出的文件在后台持续写入，不允许取消
+        let request_id = self
+            .tasks
+            .start("salvage", String::new(), "Export readable chats".into(), false);
+        let tx = self.events.tx.clone();
+        thread::spawn(move || {
+            let result = salvage_conversations(|done, total| {
+                let progress = ChatResponse::Progress {
+                    request_id: request_id.clone(),
+                    done,
+                    total,
+                };
+                let _ = tx.send(AppEvent::ResponseChunk(progress));
+            })
+            .map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::ResponseDone(request_id, ChatResponse::Salvage(result)));
+        });
+    }
+
+    /// 回复请求调用已配置的本地工具时，弹窗确认是否执行
+    fn prepare_tool_call(&mut self, call: ToolCall) {
+        if find_tool(&self.tools, &call.name).is_none() {
+            return;
+        }
+        self.show_confirm(format!("Run tool {}?", call.name), ConfirmAction::RunTool(call));
+    }
+
+    /// 在后台执行工具，完成后将结果发送给 Gemini
+    fn run_tool_call(&mut self, call: ToolCall, tx: EventSender) {
+        let Some(tool) = find_tool(&self.tools, &call.name).cloned() else {
+            return;
+        };
+        let _ = tx.send(AppEvent::Request(ChatType::Tool { tool, call }));
+    }
+
+    /// 将工具的执行结果加入聊天记录，并作为函数响应发送给模型
+    fn send_tool_output(&mut self, call: ToolCall, output: String, tx: EventSender) {
+        if self.receiving_message() || self.confirm_unlock() {
+            return;
+        }
+        self.chat_show.chat_history.push(ChatMessage {
+            success: true,
+            sender: User(String::new()),
+            message: tool_output_message(&call.name, &output),
+            date_time: Local::now(),
+            record_id: Some(generate_unique_id()),
+            attachments: Vec::new(),
+            raw: None,
+        });
+        let _ = tx.send(AppEvent::Request(ChatType::ToolResponse { call, output }));
+        self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
+    }
+
+    /// 保存最近一条回复中带有文件名提示的代码块
+    fn save_generated_files(&mut self) {
+        let Some(chat_message) = self
+            .chat_show
+            .chat_history
+            .iter()
+            .rev()
+            .find(|message| matches!(message.sender, Bot))
+        else {
+            return;
+        };
+        self.pending_files = parse_code_blocks(&chat_message.message)
+            .into_iter()
+            .filter(|block| block.file_name.is_some())
+            .collect();
+        self.next_pending_file();
+    }
+
+    /// 应用最近一条回复中的 diff
+    fn apply_latest_diff(&mut self) {
+        let diff = self
+            .chat_show
+            .chat_history
+            .iter()
+            .rev()
+            .filter(|message| matches!(message.sender, Bot))
+            .flat_map(|message| parse_code_blocks(&message.message).into_iter().rev())
+            .find(|block| block.is_diff());
+        if let Some(block) = diff {
+            self.show_confirm(
+                "Apply diff with git apply?".into(),
+                ConfirmAction::ApplyDiff(block.content),
+            );
+        }
+    }
+
+    /// 弹出下一个待保存文件的路径输入框
+    fn next_pending_file(&mut self) {
+        if self.pending_files.is_empty() {
+            return;
         }
+        let block = &self.pending_files[0];
+        let mut popup = InputPopup::new(block.file_name.clone().unwrap_or_default(), 50, 3);
+        popup.title = "Save File As".into();
+        self.popups.open(MainPopup::SaveFile(popup));
     }
 
-    // 当不处于图片路径输入弹窗状态时，处理输入
-    fn handle_input_key_event_common(&mut self, key: event::KeyEvent, tx: mpsc::Sender<ChatType>) {
+    /// 处理保存文件路径输入弹窗事件
+    fn handle_file_path_popup_key_event(&mut self, key: event::KeyEvent) {
+        let Some(MainPopup::SaveFile(popup)) = self.popups.top_mut() else {
+            return;
+        };
+        match popup.handle_key(key) {
+            InputPopupHandleEvent::Save(path) => {
+                self.popups.close();
+                let block = self.pending_files.remove(0);
+                if Path::new(&path).exists() {
+                    // 文件已存在，需要确认是否覆盖
+                    let action = ConfirmAction::WriteFile {
+                        path: path.clone(),
+                        content: block.content,
+                    };
+                    self.show_confirm(format!("Overwrite {}?", path), action);
+                } else {
+                    self.write_file(path, block.content);
+                    self.next_pending_file();
+                }
+            }
+            InputPopupHandleEvent::Cancel => {
+                self.popups.close();
+                self.pending_files.remove(0);
+                self.next_pending_file();
+            }
+            InputPopupHandleEvent::Nothing => {}
+        }
+    }
+
+    /// 写入文件，失败时显示错误信息
+    fn write_file(&mut self, path: String, content: String) {
+        let path = Path::new(&path);
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(path, content + "\n") {
+            self.response_status = ResponseStatus::Failed(e.to_string());
+        }
+    }
+
+    /// 请求执行 shell 命令，命令为空时移除已附加的命令输出
+    fn request_run_command(&mut self, command: String) {
+        let command = command.trim().to_string();
+        if command.is_empty() {
+            self.command_output = None;
+            return;
+        }
+        self.show_confirm(format!("Run `{}`?", command), ConfirmAction::RunCommand(command));
+    }
+
+    /// 在后台执行 shell 命令，完成后将输出附加到下一条消息中
+    fn run_shell_command(&mut self, command: String, tx: EventSender) {
+        self.input_field_component.clear();
+        let _ = tx.send(AppEvent::Request(ChatType::Command { command }));
+    }
+
+    /// 处理图片路径输入弹窗事件
+    fn handle_image_popup_key_event(&mut self, key: event::KeyEvent) {
+        let Some(MainPopup::ImagePath(popup)) = self.popups.top_mut() else {
+            return;
+        };
+        match popup.handle_key(key) {
+            InputPopupHandleEvent::Save(res) => {
+                // 路径无效时保留弹窗，错误原因显示在标题中
+                if self.validate_image_input() {
+                    self.popups.close();
+                    self.image_path = Some(res.trim().to_string());
+                }
+            }
+            InputPopupHandleEvent::Cancel => {
+                self.popups.close();
+            }
+            InputPopupHandleEvent::Nothing => {
+                self.validate_image_input();
+            }
+        }
+    }
+
+    /// 当聚焦于输入框时，处理输入
+    fn handle_input_key_event(&mut self, key: event::KeyEvent, tx: EventSender) {
         // 如果是除 Tab 键外其他任意按键事件，则清空错误提示消息
         if key.code != event::KeyCode::Tab && !matches!(self.response_status, ResponseStatus::None) {
             self.response_status = ResponseStatus::None;
@@ -675,10 +3029,46 @@ impl UI {
                 self.make_title_editable()
             }
             event::KeyCode::F(1) => self.make_title_editable(),
+            event::KeyCode::Char('r') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.request_run_command(self.input_field_component.get_content())
+            }
+            event::KeyCode::F(10) => self.toggle_recording(tx),
+            event::KeyCode::Char('e') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.compose_in_editor()
+            }
+            event::KeyCode::Char(c @ '1'..='9') if key.modifiers.contains(event::KeyModifiers::ALT) => {
+                self.remove_attachment(c as usize - '1' as usize)
+            }
+            event::KeyCode::Char('g') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.take_screenshot(tx)
+            }
             event::KeyCode::Esc => self.should_exit = true,
             event::KeyCode::Tab => self.next_component(),
+            event::KeyCode::BackTab => self.prev_component(),
             event::KeyCode::Backspace => self.input_field_component.delete_pre_char(),
-            event::KeyCode::Enter => self.submit_message(tx),
+            event::KeyCode::Enter => {
+                let content = self.input_field_component.get_content();
+                if !content.is_empty() && self.confirm_unlock() {
+                    return;
+                }
+                if let Some(command) = content.strip_prefix("/run ") {
+                    self.request_run_command(command.into())
+                } else if content == "/review" || content.starts_with("/review ") {
+                    self.review_changes(&content["/review".len()..], tx)
+                } else if let Some(prompt) = content.strip_prefix("/image ") {
+                    self.request_image(prompt.trim().into(), tx)
+                } else if let Some(note) = content.strip_prefix("/note ") {
+                    self.add_note(note.trim().into())
+                } else if let Some(question) = content.strip_prefix("/search ") {
+                    self.input_field_component.clear();
+                    self.send_search_message(question.trim().into(), tx)
+                } else if content == "/bad" || content.starts_with("/bad ") {
+                    self.rate_selected_reply(Rating::Bad(content["/bad".len()..].trim().into()));
+                    self.input_field_component.clear();
+                } else {
+                    self.submit_message(tx)
+                }
+            }
             event::KeyCode::Left => self
                 .input_field_component
                 .move_cursor_left(self.input_field_component.get_current_char()),
@@ -724,19 +3114,158 @@ impl UI {
             }
             event::KeyCode::F(3) => self.show_and_hide_sidebar(),
             event::KeyCode::Tab => self.next_component(),
+            event::KeyCode::BackTab => self.prev_component(),
             event::KeyCode::Enter => self.new_conversation(),
+            event::KeyCode::Char('t') => {
+                let templates = read_templates();
+                if templates.is_empty() {
+                    self.popups.toast("No templates, press t in the chat list to save one");
+                } else {
+                    self.popups.open(MainPopup::Templates(templates, 0));
+                }
+            }
+            _ => {}
+        };
+    }
+
+    /// 显示选中的消息（未选中时为最后一条回复）中的链接，只有一个链接时直接打开
+    fn show_links(&mut self) {
+        let index = self.chat_show.selected_index.or_else(|| {
+            self.chat_show
+                .chat_history
+                .iter()
+                .rposition(|message| matches!(message.sender, Bot))
+        });
+        let Some(index) = index.filter(|index| *index < self.chat_show.chat_history.len()) else {
+            return;
+        };
+        // 联网搜索的来源链接只在带引用的文本中
+        let links = extract_links(self.chat_show.display_text(index));
+        match links.as_slice() {
+            [] => self.popups.toast("No links in the message"),
+            [link] => self.open_link(link.clone()),
+            _ => self.popups.open(MainPopup::Links(links, 0)),
+        }
+    }
+
+    /// 使用系统浏览器打开链接
+    fn open_link(&mut self, link: String) {
+        match open_link(&link) {
+            Ok(_) => self.popups.toast(format!("Opening {}", link)),
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 当剪贴板翻译弹窗打开时，处理输入
+    fn handle_translation_popup_key_event(&mut self, key: event::KeyEvent) {
+        let Some(MainPopup::Translation {
+            translation, scroll, ..
+        }) = self.popups.top_mut()
+        else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Esc => {
+                self.popups.close();
+            }
+            event::KeyCode::Up => *scroll = scroll.saturating_sub(1),
+            event::KeyCode::Down => *scroll = scroll.saturating_add(1),
+            event::KeyCode::Char('c') => {
+                let translation = translation.clone();
+                self.popups.close();
+                match write_clipboard(&translation) {
+                    Ok(_) => self.popups.toast("Translation copied"),
+                    Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 当链接选择弹窗打开时，处理输入
+    fn handle_link_popup_key_event(&mut self, key: event::KeyEvent) {
+        let Some(MainPopup::Links(links, selected)) = self.popups.top_mut() else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Esc => {
+                self.popups.close();
+            }
+            event::KeyCode::Up => *selected = selected.saturating_sub(1),
+            event::KeyCode::Down => *selected = (*selected + 1).min(links.len() - 1),
+            event::KeyCode::Enter => {
+                let link = links[*selected].clone();
+                self.popups.close();
+                self.open_link(link);
+            }
+            event::KeyCode::Char(c @ '1'..='9') => {
+                if let Some(link) = links.get(c as usize - '1' as usize).cloned() {
+                    self.popups.close();
+                    self.open_link(link);
+                }
+            }
+            event::KeyCode::Char('c') => {
+                let link = links[*selected].clone();
+                self.popups.close();
+                match write_clipboard(&link) {
+                    Ok(_) => self.popups.toast("Link copied"),
+                    Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+                }
+            }
             _ => {}
+        }
+    }
+
+    /// 当模板选择弹窗打开时，处理输入
+    fn handle_template_popup_key_event(&mut self, key: event::KeyEvent) {
+        let Some(MainPopup::Templates(templates, selected)) = self.popups.top_mut() else {
+            return;
         };
+        match key.code {
+            event::KeyCode::Esc => {
+                self.popups.close();
+            }
+            event::KeyCode::Up => *selected = selected.saturating_sub(1),
+            event::KeyCode::Down => *selected = (*selected + 1).min(templates.len() - 1),
+            event::KeyCode::Enter => {
+                if let Some(MainPopup::Templates(templates, selected)) = self.popups.close() {
+                    self.new_conversation_from_template(templates[selected].clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 根据模板创建新会话，开场消息会立即保存，系统提示词仅在本次打开会话期间生效
+    fn new_conversation_from_template(&mut self, template: ConversationTemplate) {
+        self.new_conversation();
+        self.conversation_id = generate_unique_id();
+        self.title = template.name.clone();
+        let mut chat_history = template.chat_messages();
+        for message in &mut chat_history {
+            message.record_id =
+                save_conversation(self.conversation_id.clone(), self.title.clone(), message.clone()).ok();
+        }
+        // 新会话出现在置顶会话之后，保持原有的选中项
+        if !chat_history.is_empty() {
+            self.chat_item_list.conversation_added();
+        }
+        if let Some(gemini) = self.gemini.as_mut() {
+            if let Some(system_instruction) = template.system_instruction {
+                gemini.set_system_instruction(system_instruction);
+            }
+            gemini.start_chat(history_contents(&chat_history));
+        }
+        self.chat_show.chat_history = chat_history;
+        self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
     }
 
     /// 创建一个新的对话
     fn new_conversation(&mut self) {
-        self.receiving_message = false;
         self.response_status = ResponseStatus::None;
-        if let Some(gemini) = self.gemini.clone() {
-            let mut gemini_new = Gemini::rebuild(gemini.key, gemini.model, Vec::new(), gemini.options);
-            gemini_new.set_system_instruction(gemini.system_instruction.unwrap_or("".into()));
-            self.gemini = Some(gemini_new);
+        // 只清空上下文，保留客户端以复用连接池
+        if let Some(gemini) = self.gemini.as_mut() {
+            gemini.start_chat(Vec::new());
         };
         self.focus_component = MainFocusComponent::InputField;
         self.input_field_component.clear();
@@ -747,7 +3276,7 @@ impl UI {
     }
 
     /// 当聚焦于聊天列表时，处理输入
-    fn handle_chat_list_key_event(&mut self, key: event::KeyEvent) {
+    fn handle_chat_list_key_event(&mut self, key: event::KeyEvent, chat_tx: EventSender) {
         match key.code {
             event::KeyCode::Esc => self.should_exit = true,
             event::KeyCode::Char('s') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
@@ -755,103 +3284,210 @@ impl UI {
             }
             event::KeyCode::F(3) => self.show_and_hide_sidebar(),
             event::KeyCode::Enter => {
-                // 如果此时有确认删除的弹窗，则处理弹窗
-                if let Some(popup) = self.chat_item_list.popup_delete_confirm_dialog.clone() {
-                    let confirm = popup.press();
-                    // 如果确认删除，则删除
-                    if confirm {
-                        let deleted_id = self.chat_item_list.delete_item();
-                        // 如果删除的是当前聊天，则重新创建新的聊天
-                        if deleted_id == self.conversation_id {
-                            self.new_conversation();
-                        }
-                    }
-                    self.chat_item_list.popup_delete_confirm_dialog = None;
-                    return;
-                }
-                // 否则加载对应选中项的聊天内容列表
+                // 加载对应选中项的聊天内容列表
                 if let Some(conversation) = self.chat_item_list.rebuild() {
-                    self.conversation_id = conversation.conversation_id;
-                    self.title = conversation.conversation_title;
-                    let contents: Vec<Content> = conversation
-                        .conversation_records
-                        .clone()
-                        .iter()
-                        .map(|record| {
-                            let role = match record.record_sender {
-                                User(_) => Some(Role::User),
-                                Bot => Some(Role::Model),
-                                Never => None,
-                            };
-                            let mut parts = Vec::new();
-                            parts.push(Part::Text(record.record_content.clone()));
-                            // 如果包含了图片数据，则添加到 parts 中
-                            if let Some(image_record) = record.image_record.clone() {
-                                let image_record_id = image_record.image_record_id;
-                                // 读取图片缓存数据
-                                Self::read_image_data(image_record_id, image_record.image_path, &mut parts);
-                            }
-                            Content { parts, role }
-                        })
-                        .collect();
-                    // 重新加载 gemini 客户端
-                    if let Some(gemini) = self.gemini.clone() {
-                        let mut gemini_new = Gemini::rebuild(gemini.key, gemini.model, contents, gemini.options);
-                        gemini_new.set_system_instruction(gemini.system_instruction.unwrap_or("".into()));
-                        self.gemini = Some(gemini_new);
-                    }
-                    // 加载聊天记录
-                    let chat_history: Vec<ChatMessage> = conversation
-                        .conversation_records
-                        .clone()
-                        .iter()
-                        .map(|record| ChatMessage {
-                            success: true,
-                            message: record.record_content.clone(),
-                            sender: record.record_sender.clone(),
-                            date_time: record.record_time,
-                        })
-                        .collect();
-                    self.chat_show.chat_history = chat_history;
-                    self.focus_component = MainFocusComponent::ChatShow;
-                    self.input_field_component.clear();
-                    self.image_path = None;
+                    self.open_conversation(conversation, chat_tx);
                 }
             }
+            event::KeyCode::Up if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.chat_item_list.move_pinned(true)
+            }
+            event::KeyCode::Down if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.chat_item_list.move_pinned(false)
+            }
             event::KeyCode::Up => self.chat_item_list.prev_item(),
             event::KeyCode::Down => self.chat_item_list.next_item(),
             event::KeyCode::Delete => {
-                // 弹窗提示
-                self.chat_item_list.popup_delete_confirm_dialog = Some(DeletePopup::default());
+                // 已锁定的会话需要先解锁，否则弹窗确认删除
+                if self.chat_item_list.selected_locked() {
+                    if let Some(selected) = self
+                        .chat_item_list
+                        .chat_history
+                        .get(self.chat_item_list.selected_conversation)
+                    {
+                        let conversation_id = selected.conversation.conversation_id.clone();
+                        self.show_confirm(
+                            "Conversation is locked, unlock it?".into(),
+                            ConfirmAction::Unlock(conversation_id),
+                        );
+                    }
+                } else {
+                    self.popups.open(MainPopup::DeleteChat(DeletePopup::default()));
+                }
             }
-            event::KeyCode::Tab => {
-                // 如果此时有确认删除的弹窗，则处理弹窗
-                if let Some(ref mut popup) = self.chat_item_list.popup_delete_confirm_dialog {
+            event::KeyCode::Char('r') => self.chat_item_list.start_rename(),
+            event::KeyCode::Char('p') => match self.chat_item_list.toggle_pin() {
+                Some(true) => self.popups.toast("Conversation pinned"),
+                Some(false) => self.popups.toast("Conversation unpinned"),
+                None => {}
+            },
+            event::KeyCode::Char('l') => match self.chat_item_list.toggle_lock() {
+                Some(true) => self.popups.toast("Conversation locked"),
+                Some(false) => self.popups.toast("Conversation unlocked"),
+                None => {}
+            },
+            event::KeyCode::Char('e') => self.export_conversation(),
+            event::KeyCode::Char('E') => self.export_ai_studio_prompt(),
+            event::KeyCode::Char('t') => self.save_conversation_template(),
+            event::KeyCode::Tab => self.next_component(),
+            event::KeyCode::BackTab => self.prev_component(),
+            _ => {}
+        };
+    }
+
+    /// 处理确认删除会话弹窗事件
+    fn handle_delete_chat_key_event(&mut self, key: event::KeyEvent) {
+        match key.code {
+            event::KeyCode::Tab | event::KeyCode::BackTab | event::KeyCode::Left | event::KeyCode::Right => {
+                if let Some(MainPopup::DeleteChat(popup)) = self.popups.top_mut() {
                     popup.next_button();
-                } else {
-                    self.next_component();
                 }
             }
+            event::KeyCode::Enter => {
+                // 如果确认删除，则删除
+                if let Some(MainPopup::DeleteChat(popup)) = self.popups.close() {
+                    if popup.press() {
+                        let deleted_id = self.chat_item_list.delete_item();
+                        // 如果删除的是当前聊天，则重新创建新的聊天
+                        if deleted_id == self.conversation_id {
+                            self.new_conversation();
+                        }
+                    }
+                }
+            }
+            event::KeyCode::Esc => {
+                self.popups.close();
+            }
             _ => {}
+        }
+    }
+
+    /// 打开聊天列表中指定位置的会话
+    fn quick_open(&mut self, index: usize, chat_tx: EventSender) {
+        if index >= self.chat_item_list.chat_history.len() {
+            return;
+        }
+        self.chat_item_list.select_index(index);
+        if let Some(conversation) = self.chat_item_list.rebuild() {
+            self.open_conversation(conversation, chat_tx);
+        }
+    }
+
+    /// 打开会话，会话已在其他标签页中打开时切换到该标签页
+    fn open_conversation(&mut self, conversation: Conversation, chat_tx: EventSender) {
+        // 如果会话已在其他标签页中打开，则切换到该标签页
+        if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.conversation_id == conversation.conversation_id)
+            .filter(|index| *index != self.active_tab)
+        {
+            self.switch_tab(index);
+            self.focus_component = MainFocusComponent::ChatShow;
+            return;
+        }
+        self.conversation_id = conversation.conversation_id.clone();
+        self.chat_item_list.mark_read(&self.conversation_id);
+        self.title = conversation.conversation_title.clone();
+        let chat_history = chat_history_of(&conversation);
+        let contents = history_contents(&chat_history);
+        // 备注不在上下文中，图片在上下文中的下标需要跳过备注
+        let records: Vec<&MessageRecord> = conversation
+            .conversation_records
+            .iter()
+            .filter(|record| !matches!(record.record_sender, Note | Never))
+            .collect();
+        // 图片数据较大，在后台读取后再补充到上下文中，读取完成前不允许发送消息
+        let images: Vec<(usize, String, String)> = records
+            .iter()
+            .enumerate()
+            .filter_map(|(index, record)| {
+                let image_record = record.image_record.clone()?;
+                Some((index, image_record.image_record_id, image_record.image_path))
+            })
+            .collect();
+        if !images.is_empty() {
+            self.pending_conversations.insert(self.conversation_id.clone());
+            self.image_progress
+                .insert(self.conversation_id.clone(), (0, images.len()));
+            let _ = chat_tx.send(AppEvent::Request(ChatType::LoadImages {
+                conversation_id: self.conversation_id.clone(),
+                images,
+            }));
+        }
+        // 替换 gemini 客户端的上下文，保留客户端以复用连接池
+        if let Some(gemini) = self.gemini.as_mut() {
+            gemini.start_chat(contents);
+        }
+        // 加载聊天记录
+        let ratings = conversation
+            .conversation_records
+            .iter()
+            .enumerate()
+            .filter_map(|(index, record)| Some((index, record.rating.clone()?)))
+            .collect();
+        self.chat_show = ChatShowScrollProps {
+            chat_history,
+            ratings,
+            ..Default::default()
         };
+        self.focus_component = MainFocusComponent::ChatShow;
+        self.input_field_component.clear();
+        self.image_path = None;
     }
 
-    /// 读取图片数据
-    fn read_image_data(image_record_id: String, image_path: String, parts: &mut Vec<Part>) {
-        // 读取图片缓存数据
-        if let Ok((image_type, image_data)) = read_image_cache(image_record_id.clone()) {
-            parts.push(Part::InlineData {
-                mime_type: image_type,
-                data: image_data,
+    /// 将选中的会话导出为 HTML 文件，通过保存文件弹窗选择路径
+    fn export_conversation(&mut self) {
+        if let Some(conversation) = self.chat_item_list.rebuild() {
+            self.pending_files.push(CodeBlock {
+                language: "html".into(),
+                file_name: Some(export_file_name(
+                    &conversation.conversation_title,
+                    &conversation.conversation_id,
+                    "html",
+                )),
+                content: export_html(&conversation),
+                ..Default::default()
             });
-            // 如果读不到缓存的数据，则重新加载图片数据
-        } else if let Ok((image_type, image_data)) = get_image_type_and_base64_string(image_path.clone()) {
-            // 将图片数据缓存到本地
-            let _ = cache_image(image_path, image_record_id);
-            parts.push(Part::InlineData {
-                mime_type: image_type,
-                data: image_data,
+            self.next_pending_file();
+        }
+    }
+
+    /// 将选中的会话及当前系统提示词保存为会话模板，模板名称为会话标题
+    fn save_conversation_template(&mut self) {
+        let Some(conversation) = self.chat_item_list.rebuild() else {
+            return;
+        };
+        let name = if conversation.conversation_title.is_empty() {
+            "New Chat".to_string()
+        } else {
+            conversation.conversation_title.clone()
+        };
+        // 使用配置中的系统指令，客户端中的系统指令可能来自其他模板
+        let system_instruction = read_config().ok().and_then(|config| config.system_instruction);
+        let template =
+            ConversationTemplate::from_messages(name.clone(), system_instruction, &chat_history_of(&conversation));
+        match save_template(template) {
+            Ok(_) => self.popups.toast(format!("Saved template {}", name)),
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 将选中的会话导出为 Google AI Studio 提示词
+    fn export_ai_studio_prompt(&mut self) {
+        if let Some(conversation) = self.chat_item_list.rebuild() {
+            let config = read_config().unwrap_or_default();
+            self.pending_files.push(CodeBlock {
+                language: "json".into(),
+                file_name: Some(export_file_name(
+                    &conversation.conversation_title,
+                    &conversation.conversation_id,
+                    "json",
+                )),
+                content: export_ai_studio(&conversation, &config),
+                ..Default::default()
             });
+            self.next_pending_file();
         }
     }
 
@@ -864,13 +3500,14 @@ impl UI {
             }
             event::KeyCode::F(3) => self.show_and_hide_sidebar(),
             event::KeyCode::Tab => self.next_component(),
+            event::KeyCode::BackTab => self.prev_component(),
             event::KeyCode::Enter => self.open_setting_menu(),
             _ => {}
         };
     }
 
     /// 当聚焦于聊天内容显示区域时，处理输入
-    fn handle_chat_show_key_event(&mut self, key: event::KeyEvent) {
+    fn handle_chat_show_key_event(&mut self, key: event::KeyEvent, tx: EventSender) {
         match key.code {
             event::KeyCode::Char('s') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                 self.show_and_hide_sidebar()
@@ -882,12 +3519,359 @@ impl UI {
             event::KeyCode::F(1) => self.make_title_editable(),
             event::KeyCode::Esc => self.should_exit = true,
             event::KeyCode::Tab => self.next_component(),
+            event::KeyCode::BackTab => self.prev_component(),
             event::KeyCode::Up => self.up(),
             event::KeyCode::Down => self.down(),
+            event::KeyCode::Left | event::KeyCode::Char('h') => self.left(),
+            event::KeyCode::Right | event::KeyCode::Char('l') => self.right(),
+            event::KeyCode::Char('n') => self.chat_show.line_numbers = !self.chat_show.line_numbers,
+            event::KeyCode::Char('[') => self.chat_show.prev_message(),
+            event::KeyCode::Char(']') => self.chat_show.next_message(),
+            event::KeyCode::Char('{') => self.chat_show.prev_user_message(),
+            event::KeyCode::Char('}') => self.chat_show.next_user_message(),
+            event::KeyCode::Char('<') => self.chat_show.prev_code_block(),
+            event::KeyCode::Char('>') => self.chat_show.next_code_block(),
+            event::KeyCode::Char('r') => self.chat_show.toggle_raw_view(),
+            event::KeyCode::Char('z') => self.chat_show.toggle_collapse(),
+            event::KeyCode::Char('t') => self.translate_selected_message(tx),
+            event::KeyCode::Char('T') => self.translate_clipboard(tx),
+            event::KeyCode::Char('w') => self.save_generated_files(),
+            event::KeyCode::Char('a') => self.apply_latest_diff(),
+            event::KeyCode::Char('m') => self.ask_other_model(tx),
+            event::KeyCode::Char('s') => self.share_conversation(tx),
+            event::KeyCode::Char('p') => self.speak_message(),
+            event::KeyCode::Char('e') => self.edit_reply(),
+            event::KeyCode::Char('v') => self.view_selected_message("pager"),
+            event::KeyCode::Char('V') => self.view_selected_message("editor"),
+            event::KeyCode::Char('o') => self.show_links(),
+            event::KeyCode::Char('+') => self.rate_selected_reply(Rating::Good),
+            event::KeyCode::Char('-') => self.rate_selected_reply(Rating::Bad(String::new())),
+            event::KeyCode::Char('y') => self.keep_alternative(),
+            event::KeyCode::Char('x') => self.chat_show.alternative = None,
             _ => {}
         }
     }
 
+    /// 在后台执行截图命令，截图完成后自动附加到下一条消息
+    fn take_screenshot(&mut self, tx: EventSender) {
+        let command = read_config()
+            .ok()
+            .and_then(|store_data| store_data.screenshot_command)
+            .or(default_screenshot_command().map(String::from))
+            .unwrap_or_default();
+        self.popups.toast("Taking screenshot...");
+        let _ = tx.send(AppEvent::Request(ChatType::Screenshot { command }));
+    }
+
+    /// 开始录音，正在录音时停止录音并识别录音内容
+    fn toggle_recording(&mut self, tx: EventSender) {
+        if let Some(recording) = self.recording.take() {
+            match recording.stop() {
+                Ok(path) => {
+                    self.popups.toast("Transcribing...");
+                    let _ = tx.send(AppEvent::Request(ChatType::Transcribe { path }));
+                }
+                Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+            }
+            return;
+        }
+        let command = read_config().unwrap_or_default().record_command.unwrap_or_default();
+        match start_recording(&command) {
+            Ok(recording) => self.recording = Some(recording),
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 朗读选中的消息，未选中时朗读最后一条回复，正在朗读时停止朗读
+    fn speak_message(&mut self) {
+        if let Some(mut child) = self.speaking.take() {
+            if matches!(child.try_wait(), Ok(None)) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return;
+            }
+        }
+        let history = &self.chat_show.chat_history;
+        let message = match self.chat_show.selected_index {
+            Some(index) => history.get(index),
+            None => history.iter().rfind(|message| matches!(message.sender, Bot)),
+        };
+        let Some(message) = message else {
+            return;
+        };
+        let command = read_config().unwrap_or_default().tts_command.unwrap_or_default();
+        match speak(&command, &message.message) {
+            Ok(child) => self.speaking = Some(child),
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 将当前会话导出为 Markdown 并上传到分享服务
+    fn share_conversation(&mut self, tx: EventSender) {
+        if self.chat_show.chat_history.is_empty() {
+            return;
+        }
+        let file_name = export_file_name(&self.title, &self.conversation_id, "md");
+        let markdown = export_markdown(&self.title, &self.chat_show.chat_history);
+        self.popups.toast("Sharing...");
+        let _ = tx.send(AppEvent::Request(ChatType::Share { file_name, markdown }));
+    }
+
+    /// 使用另一个模型重新回答最后一个问题
+    fn ask_other_model(&mut self, tx: EventSender) {
+        if self.receiving_message() {
+            return;
+        }
+        let history = &self.chat_show.chat_history;
+        let Some(index) = history.iter().rposition(|message| matches!(message.sender, Bot)) else {
+            return;
+        };
+        let Some(prompt) = history[..index]
+            .iter()
+            .rfind(|message| matches!(message.sender, User(_)))
+            .map(|message| message.message.clone())
+        else {
+            return;
+        };
+        // 原回复被评价为差时，附带原回复与原因，避免得到同样的回答
+        let prompt = match self.chat_show.ratings.get(&index) {
+            Some(Rating::Bad(reason)) if read_config().is_ok_and(|config| config.rating_feedback) => {
+                let reason = if reason.is_empty() {
+                    String::new()
+                } else {
+                    format!(" because: {}", reason)
+                };
+                format!(
+                    "{}\n\nA previous answer to this question was:\n\n{}\n\nThat answer was wrong{}. Please answer again.",
+                    prompt, history[index].message, reason
+                )
+            }
+            _ => prompt,
+        };
+        self.chat_show.alternative = None;
+        self.popups.toast("Asking the other model...");
+        let _ = tx.send(AppEvent::Request(ChatType::Alternative { index, prompt }));
+    }
+
+    /// 在当前会话中添加一条本地备注，备注会保存到数据库，但不会发送给模型
+    fn add_note(&mut self, note: String) {
+        if note.is_empty() || self.confirm_unlock() {
+            return;
+        }
+        // 会话开始后才能添加备注，避免产生只有备注的会话
+        if self.chat_show.chat_history.is_empty() {
+            self.popups.toast("Send a message before adding notes");
+            return;
+        }
+        let mut message = ChatMessage {
+            success: true,
+            sender: Note,
+            message: note,
+            date_time: Local::now(),
+            record_id: None,
+            attachments: Vec::new(),
+            raw: None,
+        };
+        message.record_id = save_conversation(self.conversation_id.clone(), self.title.clone(), message.clone()).ok();
+        self.chat_show.chat_history.push(message);
+        self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
+        self.input_field_component.clear();
+    }
+
+    /// 运行需要使用终端的外部程序，运行期间暂时恢复终端
+    fn suspend<T>(&mut self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        ratatui::restore();
+        let result = f();
+        let _ = enable_raw_mode();
+        let _ = execute!(io::stdout(), EnterAlternateScreen);
+        self.bracketed_paste = false;
+        self.needs_clear = true;
+        result
+    }
+
+    /// 在分页器或编辑器中查看选中的消息，未选中时查看最后一条消息，方便阅读、搜索与复制较长的回复
+    fn view_selected_message(&mut self, viewer: &str) {
+        let Some(message) = self
+            .chat_show
+            .selected_index
+            .or(self.chat_show.chat_history.len().checked_sub(1))
+            .and_then(|index| self.chat_show.chat_history.get(index))
+            .map(|chat_message| chat_message.message.clone())
+        else {
+            return;
+        };
+        if let Err(e) = self.suspend(|| view_text(&message, viewer)) {
+            self.response_status = ResponseStatus::Failed(e.to_string());
+        }
+    }
+
+    /// 使用外部编辑器编辑输入框中的草稿，保存退出后将内容放回输入框，换行符会被保留
+    fn compose_in_editor(&mut self) {
+        let draft = self.input_field_component.get_content();
+        match self.suspend(|| edit_text(&draft)) {
+            Ok(content) => {
+                self.input_field_component = TextField::new(content);
+                self.input_field_component.end_of_cursor();
+            }
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 使用外部编辑器编辑选中的回复（未选中时为最后一条回复），同时修改数据库与上下文，
+    /// 之后的对话会使用编辑后的内容
+    fn edit_reply(&mut self) {
+        let Some(index) = self.selected_reply_index() else {
+            return;
+        };
+        if self.confirm_unlock() {
+            return;
+        }
+        let old_content = self.chat_show.chat_history[index].message.clone();
+        let content = match self.suspend(|| edit_text(&old_content)) {
+            Ok(content) => content,
+            Err(e) => {
+                self.response_status = ResponseStatus::Failed(e.to_string());
+                return;
+            }
+        };
+        if content == old_content {
+            return;
+        }
+        let occurrence = self.reply_occurrence(index);
+        self.chat_show.chat_history[index].message = content.clone();
+        self.chat_show.chat_history[index].raw = None;
+        self.chat_show.citations.remove(&index);
+        self.chat_show.layout_cache.clear();
+        if let Some(record_id) = self.chat_show.chat_history[index].record_id.clone() {
+            if let Err(e) = modify_reply(record_id, content.clone()) {
+                self.response_status = ResponseStatus::Failed(e.to_string());
+            }
+        }
+        self.replace_reply_context(occurrence, &old_content, content);
+    }
+
+    /// 替换上下文中与回复对应的内容，`occurrence` 为回复修改前 [`UI::reply_occurrence`] 的结果，
+    /// 聊天记录中的回复经过 [`tidy_response`] 整理，上下文中保留接口返回的原文，因此按整理后的内容比较
+    fn replace_reply_context(&mut self, occurrence: usize, old_content: &str, content: String) {
+        if let Some(gemini) = self.gemini.as_mut() {
+            if let Some(part) = gemini
+                .contents
+                .iter_mut()
+                .filter(|content| matches!(content.role, Some(Role::Model)))
+                .flat_map(|content| content.parts.iter_mut())
+                .filter(|part| matches!(part, Part::Text(text) if tidy_response(text.clone()) == old_content))
+                .nth(occurrence)
+            {
+                *part = Part::Text(content);
+            }
+        }
+    }
+
+    /// 选中的回复下标，未选中时为最后一条回复，选中的不是回复时返回 None
+    fn selected_reply_index(&self) -> Option<usize> {
+        let index = self.chat_show.selected_index.or_else(|| {
+            self.chat_show
+                .chat_history
+                .iter()
+                .rposition(|message| matches!(message.sender, Bot))
+        });
+        index.filter(|index| {
+            self.chat_show
+                .chat_history
+                .get(*index)
+                .is_some_and(|message| matches!(message.sender, Bot))
+        })
+    }
+
+    /// 相同内容的回复按出现顺序对应到上下文，返回回复是内容相同的回复中的第几条
+    fn reply_occurrence(&self, index: usize) -> usize {
+        let content = &self.chat_show.chat_history[index].message;
+        self.chat_show.chat_history[..index]
+            .iter()
+            .filter(|message| matches!(message.sender, Bot) && message.message == *content)
+            .count()
+    }
+
+    /// 评价选中的回复（未选中时为最后一条回复），再次给出相同评价时清除评价
+    fn rate_selected_reply(&mut self, rating: Rating) {
+        let Some(index) = self.selected_reply_index() else {
+            return;
+        };
+        let rating = match self.chat_show.ratings.remove(&index) {
+            Some(old) if old == rating => None,
+            _ => Some(rating),
+        };
+        if let Some(rating) = rating.clone() {
+            self.chat_show.ratings.insert(index, rating);
+        }
+        let Some(record_id) = self.chat_show.chat_history[index].record_id.clone() else {
+            return;
+        };
+        if let Err(e) = rate_reply(record_id, rating) {
+            self.response_status = ResponseStatus::Failed(e.to_string());
+        }
+    }
+
+    /// 保留备选回复，替换原回复及其上下文
+    fn keep_alternative(&mut self) {
+        if self.chat_show.alternative.is_none() || self.confirm_unlock() {
+            return;
+        }
+        let Some(alternative) = self.chat_show.alternative.take() else {
+            return;
+        };
+        if alternative.index >= self.chat_show.chat_history.len() {
+            return;
+        }
+        let record_id = self.chat_show.chat_history[alternative.index].record_id.clone();
+        let old_content = self.chat_show.chat_history[alternative.index].message.clone();
+        let occurrence = self.reply_occurrence(alternative.index);
+        // 原回复的评价不适用于新回复
+        if self.chat_show.ratings.remove(&alternative.index).is_some() {
+            if let Some(Err(e)) = record_id.clone().map(|record_id| rate_reply(record_id, None)) {
+                self.response_status = ResponseStatus::Failed(e.to_string());
+            }
+        }
+        self.chat_show.chat_history[alternative.index].message = alternative.message.clone();
+        self.chat_show.chat_history[alternative.index].raw = None;
+        self.chat_show.citations.remove(&alternative.index);
+        self.chat_show.layout_cache.clear();
+        if let Some(Err(e)) = record_id.map(|record_id| modify_reply(record_id, alternative.message.clone())) {
+            self.response_status = ResponseStatus::Failed(e.to_string());
+        }
+        self.replace_reply_context(occurrence, &old_content, alternative.message);
+    }
+
+    /// 翻译选中的消息
+    fn translate_selected_message(&mut self, tx: EventSender) {
+        let Some(index) = self.chat_show.selected_index else {
+            return;
+        };
+        if let Some(chat_message) = self.chat_show.chat_history.get(index) {
+            let text = chat_message.message.clone();
+            self.request_translation(Some(index), text, tx);
+        }
+    }
+
+    /// 翻译剪贴板内容，原文与译文显示在弹窗中，不加入聊天记录
+    fn translate_clipboard(&mut self, tx: EventSender) {
+        match read_clipboard() {
+            Ok(text) if !text.trim().is_empty() => {
+                self.popups.toast("Translating clipboard...");
+                self.request_translation(None, text, tx);
+            }
+            Ok(_) => {}
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+
+    /// 发送翻译请求给下一次循环处理
+    fn request_translation(&mut self, index: Option<usize>, text: String, tx: EventSender) {
+        if self.gemini.is_some() {
+            let _ = tx.send(AppEvent::Request(ChatType::Translate { index, text }));
+        }
+    }
+
     /// 展示或隐藏侧边栏
     fn show_and_hide_sidebar(&mut self) {
         // 如果侧边栏已经显示，且当前聚焦组件为侧边栏组件，则聚焦到输入框，否则不变
@@ -904,17 +3888,37 @@ impl UI {
 
     /// 切换到下一个组件
     fn next_component(&mut self) {
-        if self.chat_item_list.show {
-            let current = self.focus_component.clone() as usize;
-            let next = (current + 1) % MainFocusComponent::COUNT;
-            self.focus_component = MainFocusComponent::from_repr(next).unwrap();
-        } else {
-            self.focus_component = match self.focus_component {
-                MainFocusComponent::InputField => MainFocusComponent::ChatShow,
-                MainFocusComponent::ChatShow => MainFocusComponent::InputField,
-                _ => MainFocusComponent::InputField,
-            }
+        self.cycle_component(1);
+    }
+
+    /// 聚焦上一个组件
+    fn prev_component(&mut self) {
+        self.cycle_component(-1);
+    }
+
+    /// 按配置的焦点顺序切换聚焦组件，跳过隐藏的侧边栏组件与未开启的分屏区域
+    fn cycle_component(&mut self, step: isize) {
+        let order: Vec<MainFocusComponent> = self
+            .focus_order
+            .iter()
+            .filter(|component| match component {
+                MainFocusComponent::NewChatButton
+                | MainFocusComponent::ChatItemList
+                | MainFocusComponent::SettingButton => self.chat_item_list.show,
+                MainFocusComponent::SplitView => self.split_view.is_some(),
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        if order.is_empty() {
+            self.focus_component = MainFocusComponent::InputField;
+            return;
         }
+        // 当前组件不在顺序中时从第一个组件开始
+        self.focus_component = match order.iter().position(|component| *component == self.focus_component) {
+            Some(index) => order[(index as isize + step).rem_euclid(order.len() as isize) as usize].clone(),
+            None => order[0].clone(),
+        };
     }
 
     /// 进入设置菜单
@@ -922,6 +3926,16 @@ impl UI {
         self.current_windows = CurrentWindows::SettingWindow(SettingUI::new());
     }
 
+    /// 打开定时任务管理窗口
+    fn open_schedule_page(&mut self) {
+        self.current_windows = CurrentWindows::ScheduleWindow(Box::new(ScheduleUI::new()));
+    }
+
+    /// 打开用量统计窗口
+    fn open_usage_page(&mut self) {
+        self.current_windows = CurrentWindows::UsageWindow(Box::new(UsageUI::new()));
+    }
+
     /// 聊天区域向上滚动
     fn up(&mut self) {
         self.chat_show.scroll_offset = self.chat_show.scroll_offset.saturating_sub(1);
@@ -936,48 +3950,560 @@ impl UI {
             .min(self.max_scroll_offset());
     }
 
+    /// 代码行向左滚动
+    fn left(&mut self) {
+        self.chat_show.horizontal_offset = self.chat_show.horizontal_offset.saturating_sub(1);
+    }
+
+    /// 代码行向右滚动
+    fn right(&mut self) {
+        self.chat_show.horizontal_offset = self
+            .chat_show
+            .horizontal_offset
+            .saturating_add(1)
+            .min(self.chat_show.max_horizontal_offset);
+    }
+
     /// 提交消息
-    fn submit_message(&mut self, tx: mpsc::Sender<ChatType>) {
-        let image_path = self.image_path.clone().unwrap_or_default();
+    fn submit_message(&mut self, tx: EventSender) {
         if !self.input_field_component.get_content().is_empty() {
             if self.gemini.is_none() {
                 // 传入 key 创建客户端
                 self.restore_or_new_gemini(Some(self.input_field_component.get_content()));
+                self.input_field_component.clear();
             } else {
-                self.chat_show.chat_history.push(ChatMessage {
-                    success: true,
-                    sender: User(image_path.clone()),
-                    message: self.input_field_component.get_content(),
-                    date_time: Local::now(),
-                });
-                // 将获取消息标志位置真，发送消息给下一次循环使用
-                self.receiving_message = true;
-                if image_path.is_empty() {
-                    let _ = tx.send(ChatType::Simple {
-                        message: self.input_field_component.get_content(),
-                    });
-                } else {
-                    let _ = tx.send(ChatType::Image {
-                        message: self.input_field_component.get_content(),
-                        image_path,
-                    });
-                    self.image_path = None;
+                let mut message = self.input_field_component.get_content();
+                // 如果附加了命令输出，则以代码块形式追加到消息末尾
+                if let Some((command, output)) = self.command_output.take() {
+                    message = format!("{}\n```shell\n$ {}\n{}\n```", message, command, output.trim_end());
+                }
+                self.input_field_component.clear();
+                self.send_message(message, tx);
+            }
+        }
+    }
+
+    /// 当前会话已锁定时弹窗确认是否解锁，返回会话是否已锁定，
+    /// 所有会修改聊天记录或上下文的操作都应先调用此方法，返回 true 时放弃操作
+    fn confirm_unlock(&mut self) -> bool {
+        if self.conversation_id.is_empty() || !is_conversation_locked(&self.conversation_id).unwrap_or_default() {
+            return false;
+        }
+        self.show_confirm(
+            "Conversation is locked, unlock it?".into(),
+            ConfirmAction::Unlock(self.conversation_id.clone()),
+        );
+        true
+    }
+
+    /// 发送消息，如果设置了图片则一并发送
+    fn send_message(&mut self, message: String, tx: EventSender) {
+        // 当前会话正在等待回复或已锁定时，不允许发送新消息
+        if self.receiving_message() || self.confirm_unlock() {
+            return;
+        }
+        let image_path = self.image_path.take().unwrap_or_default();
+        self.chat_show.chat_history.push(ChatMessage {
+            success: true,
+            sender: User(image_path.clone()),
+            message: message.clone(),
+            date_time: Local::now(),
+            record_id: Some(generate_unique_id()),
+            attachments: Vec::new(),
+            raw: None,
+        });
+        // 发送消息给下一次循环，在后台处理
+        if image_path.is_empty() {
+            let _ = tx.send(AppEvent::Request(ChatType::Simple { message }));
+        } else {
+            let _ = tx.send(AppEvent::Request(ChatType::Image { message, image_path }));
+        }
+        // 滚动到最新的一条消息
+        self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
+    }
+
+    /// 使用 Google 搜索回答问题，回复带有引用来源，附加的图片保留到下一条消息
+    fn send_search_message(&mut self, message: String, tx: EventSender) {
+        if self.receiving_message() || message.is_empty() || self.confirm_unlock() {
+            return;
+        }
+        self.chat_show.chat_history.push(ChatMessage {
+            success: true,
+            sender: User("".into()),
+            message: message.clone(),
+            date_time: Local::now(),
+            record_id: Some(generate_unique_id()),
+            attachments: Vec::new(),
+            raw: None,
+        });
+        let _ = tx.send(AppEvent::Request(ChatType::Search { message }));
+        self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
+    }
+
+    /// 根据提示词生成图片
+    fn request_image(&mut self, prompt: String, tx: EventSender) {
+        if self.gemini.is_none() || prompt.is_empty() || self.confirm_unlock() {
+            return;
+        }
+        self.input_field_component.clear();
+        self.chat_show.chat_history.push(ChatMessage {
+            success: true,
+            sender: User(String::new()),
+            message: format!("/image {}", prompt),
+            date_time: Local::now(),
+            record_id: None,
+            attachments: Vec::new(),
+            raw: None,
+        });
+        self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
+        self.popups.toast("Generating image...");
+        let _ = tx.send(AppEvent::Request(ChatType::GenerateImage { prompt }));
+    }
+
+    /// 审查 git 改动，range 为空时审查暂存区或工作区的改动
+    fn review_changes(&mut self, range: &str, tx: EventSender) {
+        if self.gemini.is_none() {
+            return;
+        }
+        match git_diff(Some(range)) {
+            Ok(diff) => {
+                let mut prompt = read_config()
+                    .ok()
+                    .and_then(|config| config.review_prompt)
+                    .unwrap_or(DEFAULT_REVIEW_PROMPT.into());
+                // 在已有的对话中审查时，使用对话的语言回复
+                let prompts: Vec<&str> = self
+                    .chat_show
+                    .chat_history
+                    .iter()
+                    .filter(|message| matches!(message.sender, User(_)))
+                    .map(|message| message.message.as_str())
+                    .collect();
+                if !prompts.is_empty() {
+                    prompt.push_str(&reply_language_hint(detect_language(&prompts.join("\n"))));
                 }
+                self.input_field_component.clear();
+                self.send_message(review_message(&prompt, &diff), tx);
+            }
+            Err(e) => self.response_status = ResponseStatus::Failed(e.to_string()),
+        }
+    }
+}
+
+/// 将数据库中的会话记录转换为聊天消息列表
+fn chat_history_of(conversation: &Conversation) -> Vec<ChatMessage> {
+    conversation
+        .conversation_records
+        .iter()
+        .map(|record| ChatMessage {
+            success: true,
+            message: record.record_content.clone(),
+            sender: record.record_sender.clone(),
+            date_time: record.record_time,
+            record_id: Some(record.record_id.clone()),
+            attachments: Vec::new(),
+            raw: record.raw_content.clone(),
+        })
+        .collect()
+}
+
+/// 读取图片数据，优先读取缓存，读不到缓存时重新加载原图片并缓存
+fn read_image_data(image_record_id: String, image_path: String) -> Option<Part> {
+    let (image_type, image_data) = match read_image_cache(image_record_id.clone()) {
+        Ok(data) => data,
+        Err(_) => {
+            let data = get_image_type_and_base64_string(image_path.clone()).ok()?;
+            // 将图片数据缓存到本地
+            let _ = cache_image(image_path, image_record_id);
+            data
+        }
+    };
+    Some(Part::InlineData {
+        mime_type: image_type,
+        data: image_data,
+    })
+}
+
+/// 检查数据库完整性，完好时备份数据库、按需更新数据库结构并按保留策略删除旧会话，损坏时不再修改数据库
+fn load_db(outdated: bool) -> DbStartup {
+    let problems = check_integrity().unwrap_or_else(|e| vec![e.to_string()]);
+    if let Some(problem) = problems.into_iter().next() {
+        return DbStartup {
+            problem: Some(problem),
+            migrated: false,
+            retention: Ok(0),
+        };
+    }
+    let _ = backup_db();
+    // 如果数据库版本不一致，则更新数据库结构，更新失败时不再清理过期会话
+    if outdated {
+        if let Err(e) = update_db_structure() {
+            return DbStartup {
+                problem: Some(e.to_string()),
+                migrated: false,
+                retention: Ok(0),
+            };
+        }
+    }
+    DbStartup {
+        problem: None,
+        migrated: outdated,
+        retention: apply_retention().map_err(|e| e.to_string()),
+    }
+}
+
+/// 去掉回复中多余的空行与末尾换行
+fn tidy_response(response: String) -> String {
+    let response = response.replace("\n\n", "\n");
+    if response.ends_with("\n") {
+        response[..response.len() - 1].to_owned()
+    } else {
+        response
+    }
+}
+
+/// 生成标题至少需要的提问字数，更短的对话（如问候）不生成标题，附带图片的提问视为足够
+const MIN_TITLE_PROMPT_CHARS: usize = 10;
+/// 生成标题时每条消息最多使用的字数
+const MAX_TITLE_MESSAGE_CHARS: usize = 1000;
+
+/// 生成标题使用的对话内容与根据提问推测的对话语言，从第一轮问答开始，直到累计的提问足以判断话题为止，
+/// 对话过短时返回 None，上下文中缺少最后一条回复时使用 `answer` 补充
+fn title_context(contents: &[Content], answer: &str) -> Option<(String, &'static str)> {
+    let mut messages: Vec<(bool, String, bool)> = contents
+        .iter()
+        .filter_map(|content| {
+            let is_user = match content.role {
+                Some(Role::User) => true,
+                Some(Role::Model) => false,
+                None => return None,
+            };
+            let text: Vec<&str> = content
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::Text(text) => Some(text.trim()),
+                    _ => None,
+                })
+                .filter(|text| !text.is_empty())
+                .collect();
+            let has_image = content.parts.iter().any(|part| matches!(part, Part::InlineData { .. }));
+            Some((is_user, text.join("\n"), has_image))
+        })
+        .collect();
+    if messages.last().is_some_and(|(is_user, _, _)| *is_user) {
+        messages.push((false, answer.to_string(), false));
+    }
+    let mut context = String::new();
+    let mut prompts = String::new();
+    let mut prompt_chars = 0;
+    for (is_user, text, has_image) in messages {
+        let text: String = text.chars().take(MAX_TITLE_MESSAGE_CHARS).collect();
+        if is_user {
+            prompt_chars += if has_image {
+                MIN_TITLE_PROMPT_CHARS
+            } else {
+                text.chars().count()
+            };
+            context.push_str(&format!("User: {}\n", text));
+            prompts.push_str(&text);
+            prompts.push('\n');
+        } else {
+            context.push_str(&format!("Assistant: {}\n", text));
+            // 每轮问答结束时检查提问是否足够
+            if prompt_chars >= MIN_TITLE_PROMPT_CHARS {
+                return Some((context, detect_language(&prompts)));
             }
-            self.input_field_component.clear();
-            // 滚动到最新的一条消息
-            self.chat_show.scroll_offset = self.chat_show.chat_history_area_height;
         }
     }
+    None
 }
 
-/// 通过纯净的 Gemini API 获取对话摘要
-fn summary_by_gemini(key: String, message: String) -> String {
+/// 使用对话的语言总结对话的主题作为会话标题，标题的显示宽度不超过 `max_width`
+fn summary_by_gemini(key: String, conversation: String, language: &str, max_width: usize) -> String {
     let mut pure_gemini = Gemini::new(key, LanguageModel::Gemini1_5Flash);
-    pure_gemini.set_system_instruction("请给我概括一下这段文字内容，不包含任意标点符号，不大于15字。".into());
-    if let Ok((s, _)) = pure_gemini.send_simple_message(message) {
-        s
+    // 中日韩文字占两列
+    let limit = match language {
+        "Chinese" | "Japanese" | "Korean" => max_width / 2,
+        _ => max_width,
+    };
+    pure_gemini.set_system_instruction(format!(
+        "The following is a conversation between a user and an assistant. Write a title in {} for the topic \
+the user asks about, not for the way the answer is written. Reply with only the title, without punctuation, \
+no longer than {} characters.",
+        language, limit
+    ));
+    if let Ok((s, _)) = pure_gemini.send_simple_message(conversation) {
+        track_usage(&pure_gemini, &s);
+        sanitize_title(&s, max_width)
     } else {
         "".into()
     }
 }
+
+/// 整理模型生成的标题：合并为一行，去掉 Markdown 标记、引号与 `Title:` 前缀，超出宽度的部分直接截断
+fn sanitize_title(title: &str, max_width: usize) -> String {
+    let title: String = title
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '#' | '*' | '_' | '`' | '~' | '>' | '"' | '\'' | '“' | '”' | '‘' | '’' | '「' | '」' | '《' | '》'
+            )
+        })
+        .collect();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title = title
+        .strip_prefix("Title:")
+        .or_else(|| title.strip_prefix("标题："))
+        .unwrap_or(&title)
+        .trim();
+    let mut result = String::new();
+    let mut width = 0;
+    for c in title.chars() {
+        width += c_len(c);
+        if width > max_width {
+            break;
+        }
+        result.push(c);
+    }
+    result.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::Terminal;
+
+    use super::*;
+    use crate::model::view::Sender;
+
+    /// 已完成启动检查、隐藏侧边栏的界面，按键不会读取配置或数据库
+    fn ready_ui() -> UI {
+        let mut ui = UI {
+            startup: Startup::Ready,
+            focus_order: vec![MainFocusComponent::InputField, MainFocusComponent::ChatShow],
+            ..Default::default()
+        };
+        ui.chat_item_list.show = false;
+        ui
+    }
+
+    fn press(ui: &UI, code: KeyCode, modifiers: KeyModifiers) {
+        ui.push_terminal_event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    fn message(sender: Sender, text: &str) -> ChatMessage {
+        ChatMessage {
+            success: true,
+            sender,
+            message: text.into(),
+            date_time: Local::now(),
+            record_id: Some(generate_unique_id()),
+            attachments: Vec::new(),
+            raw: None,
+        }
+    }
+
+    #[test]
+    fn tab_cycles_focus() {
+        let mut ui = ready_ui();
+        press(&ui, KeyCode::Tab, KeyModifiers::NONE);
+        ui.update();
+        assert!(matches!(ui.focus_component, MainFocusComponent::ChatShow));
+        press(&ui, KeyCode::Tab, KeyModifiers::NONE);
+        ui.update();
+        assert!(matches!(ui.focus_component, MainFocusComponent::InputField));
+    }
+
+    #[test]
+    fn popup_takes_keys_until_closed() {
+        let mut ui = ready_ui();
+        press(&ui, KeyCode::F(2), KeyModifiers::NONE);
+        ui.update();
+        assert!(matches!(ui.popups.top(), Some(MainPopup::Queue { .. })));
+        // 弹窗打开时按键不会切换焦点
+        press(&ui, KeyCode::Tab, KeyModifiers::NONE);
+        ui.update();
+        assert!(matches!(ui.focus_component, MainFocusComponent::InputField));
+        press(&ui, KeyCode::Esc, KeyModifiers::NONE);
+        ui.update();
+        assert!(!ui.popups.is_open());
+        assert!(!ui.should_exit);
+    }
+
+    #[test]
+    fn typed_keys_reach_input_field() {
+        let mut ui = ready_ui();
+        for c in "hi 你好".chars() {
+            press(&ui, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        ui.update();
+        assert_eq!(ui.input_field_component.get_content(), "hi 你好");
+    }
+
+    #[test]
+    fn new_chat_clears_history() {
+        let mut ui = ready_ui();
+        ui.focus_component = MainFocusComponent::ChatShow;
+        ui.chat_show.chat_history = vec![message(User(String::new()), "hello"), message(Bot, "hi")];
+        press(&ui, KeyCode::Char('n'), KeyModifiers::CONTROL);
+        ui.update();
+        assert!(ui.chat_show.chat_history.is_empty());
+        assert!(matches!(ui.focus_component, MainFocusComponent::InputField));
+    }
+
+    #[test]
+    fn failed_reply_marks_message() {
+        let mut ui = ready_ui();
+        let user_message = message(User(String::new()), "hello");
+        ui.chat_show.chat_history.push(user_message.clone());
+        ui.conversation_id = generate_unique_id();
+        let request_id = ui.track_request("chat", ui.conversation_id.clone(), String::new());
+        let response = ChatResponse::Chat {
+            conversation_id: ui.conversation_id.clone(),
+            title: String::new(),
+            new_conversation: false,
+            user_message: Box::new(user_message),
+            gemini: Box::new(Gemini::new("key".into(), LanguageModel::Gemini1_5Flash)),
+            cached_image: None,
+            fallback_model: None,
+            attachments: Vec::new(),
+            reasoning: None,
+            tool_call: None,
+            citations: None,
+            result: Err("timed out".into()),
+        };
+        ui.push_event(AppEvent::ResponseDone(request_id, response));
+        ui.update();
+        assert_eq!(ui.chat_show.chat_history.len(), 1);
+        assert!(!ui.chat_show.chat_history[0].success);
+        assert!(matches!(ui.response_status, ResponseStatus::Failed(_)));
+        assert!(ui.tasks.tasks().is_empty());
+    }
+
+    #[test]
+    fn draws_without_terminal() {
+        let mut ui = ready_ui();
+        ui.chat_show.chat_history = vec![message(User(String::new()), "hello"), message(Bot, "你好 😀")];
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| ui.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("hello"));
+        assert!(screen.contains("😀"));
+    }
+
+    /// 上下文中的回复保留接口返回的原文，聊天记录中为整理后的内容
+    fn ui_with_reply(raw_reply: &str) -> UI {
+        let mut ui = ready_ui();
+        let mut gemini = Gemini::new("key".into(), LanguageModel::Gemini1_5Flash);
+        gemini.contents = vec![
+            Content {
+                parts: vec![Part::Text("question".into())],
+                role: Some(Role::User),
+            },
+            Content {
+                parts: vec![Part::Text(raw_reply.into())],
+                role: Some(Role::Model),
+            },
+        ];
+        ui.gemini = Some(gemini);
+        let mut reply = message(Bot, &tidy_response(raw_reply.into()));
+        // 没有记录 id 时不会修改数据库
+        reply.record_id = None;
+        ui.chat_show.chat_history = vec![message(User(String::new()), "question"), reply];
+        ui
+    }
+
+    fn context_reply(ui: &UI) -> String {
+        match &ui.gemini.as_ref().unwrap().contents[1].parts[0] {
+            Part::Text(text) => text.clone(),
+            _ => String::new(),
+        }
+    }
+
+    #[test]
+    fn edited_reply_with_blank_line_replaces_context() {
+        let mut ui = ui_with_reply("First paragraph.\n\nSecond paragraph.\n");
+        let old_content = ui.chat_show.chat_history[1].message.clone();
+        let occurrence = ui.reply_occurrence(1);
+        ui.replace_reply_context(occurrence, &old_content, "Edited reply.".into());
+        assert_eq!(context_reply(&ui), "Edited reply.");
+    }
+
+    #[test]
+    fn kept_alternative_replaces_context() {
+        let mut ui = ui_with_reply("Original answer.\n\nWith a second paragraph.\n");
+        ui.chat_show.alternative = Some(AlternativeAnswer {
+            index: 1,
+            model: "gemini-1.5-pro".into(),
+            message: "Alternative answer.".into(),
+        });
+        ui.keep_alternative();
+        assert_eq!(ui.chat_show.chat_history[1].message, "Alternative answer.");
+        assert_eq!(context_reply(&ui), "Alternative answer.");
+    }
+
+    #[test]
+    fn locked_conversation_rejects_changes() {
+        let mut ui = ui_with_reply("Answer.");
+        ui.conversation_id = generate_unique_id();
+        lock_conversation(ui.conversation_id.clone(), true).unwrap();
+        ui.chat_show.alternative = Some(AlternativeAnswer {
+            index: 1,
+            model: "gemini-1.5-pro".into(),
+            message: "Alternative answer.".into(),
+        });
+        let (tx, rx) = mpsc::channel();
+        let changes: [fn(&mut UI, EventSender); 5] = [
+            |ui, _| ui.add_note("note".into()),
+            |ui, tx| ui.send_search_message("question".into(), tx),
+            |ui, tx| ui.request_image("a cat".into(), tx),
+            |ui, _| ui.edit_reply(),
+            |ui, _| ui.keep_alternative(),
+        ];
+        for change in changes {
+            change(&mut ui, tx.clone());
+            assert!(matches!(
+                ui.popups.close(),
+                Some(MainPopup::Confirm(_, ConfirmAction::Unlock(_)))
+            ));
+        }
+        assert!(rx.try_recv().is_err());
+        assert_eq!(ui.chat_show.chat_history.len(), 2);
+        assert_eq!(ui.chat_show.chat_history[1].message, "Answer.");
+        assert_eq!(context_reply(&ui), "Answer.");
+        assert!(ui.chat_show.alternative.is_some());
+    }
+
+    #[test]
+    fn chat_request_returns_moved_context() {
+        let mut ui = ui_with_reply("Answer.");
+        ui.offline = true;
+        ui.chat_show.chat_history.push(message(User(String::new()), "again"));
+        let (tx, rx) = mpsc::channel();
+        ui.dispatch_request(
+            ChatType::Simple {
+                message: "again".into(),
+            },
+            tx.clone(),
+        );
+        // 等待回复期间上下文在请求线程中，没有被复制
+        assert!(ui.gemini.as_ref().unwrap().contents.is_empty());
+        let response = loop {
+            if let Ok(AppEvent::ResponseDone(_, response)) = rx.recv() {
+                break response;
+            }
+        };
+        ui.handle_response(response, tx);
+        assert_eq!(ui.gemini.as_ref().unwrap().contents.len(), 4);
+        assert_eq!(context_reply(&ui), "Answer.");
+    }
+}
@@ -1,2 +1,5 @@
 pub(crate) mod main_page;
+pub(crate) mod schedule_page;
 pub(crate) mod setting_page;
+pub(crate) mod usage_page;
+pub(crate) mod view_page;
@@ -0,0 +1,288 @@
+use ratatui::{
+    crossterm::event::{self, Event, KeyEventKind},
+    layout::{
+        Constraint::{Fill, Length},
+        Layout, Position, Rect,
+    },
+    style::{Color, Style, Stylize},
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+use strum::{EnumCount, FromRepr};
+
+use crate::model::db::Schedule;
+use crate::ui::component::input::{input_trait::InputTextComponent, text_field::TextField};
+use crate::utils::db_utils::{delete_schedule, generate_unique_id, query_schedules, save_schedule};
+use crate::utils::schedule_utils::{format_repeat, parse_repeat, parse_start_time};
+
+/// 定时任务管理窗口UI
+pub struct ScheduleUI {
+    /// 当前聚焦的区域
+    focus: ScheduleFocus,
+    /// 所有定时任务
+    schedules: Vec<Schedule>,
+    /// 列表中选中的定时任务
+    selected: usize,
+    /// 新增定时任务的输入框
+    fields: Vec<(ScheduleFocus, String, TextField)>,
+    /// 新增定时任务失败的提示信息
+    error: Option<String>,
+    /// 是否应该退出窗口
+    pub should_exit: bool,
+}
+
+/// 定时任务窗口中可聚焦的区域
+#[derive(Clone, Copy, EnumCount, FromRepr, PartialEq, Eq)]
+enum ScheduleFocus {
+    /// 定时任务列表
+    List,
+    /// 任务名称
+    Name,
+    /// 提示词
+    Prompt,
+    /// 监视的文件
+    WatchFile,
+    /// 首次执行时间
+    StartTime,
+    /// 重复间隔
+    Repeat,
+}
+
+impl ScheduleUI {
+    /// 启动此窗口UI
+    pub fn new() -> Self {
+        let fields = vec![
+            (ScheduleFocus::Name, "name".into()),
+            (ScheduleFocus::Prompt, "prompt".into()),
+            (ScheduleFocus::WatchFile, "watch file (optional)".into()),
+            (ScheduleFocus::StartTime, "first run (HH:MM)".into()),
+            (ScheduleFocus::Repeat, "repeat (30m/2h/1d)".into()),
+        ];
+        Self {
+            focus: ScheduleFocus::List,
+            schedules: query_schedules().unwrap_or_default(),
+            selected: 0,
+            fields: fields
+                .into_iter()
+                .map(|(focus, label)| (focus, label, TextField::new("".into())))
+                .collect(),
+            error: None,
+            should_exit: false,
+        }
+    }
+
+    /// 处理用户输入
+    pub fn handle_key(&mut self) {
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.kind != KeyEventKind::Press {
+                return;
+            }
+            match key.code {
+                event::KeyCode::Esc => self.should_exit = true,
+                event::KeyCode::Tab => self.next_focus(),
+                event::KeyCode::Char('s') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                    self.add_schedule()
+                }
+                event::KeyCode::F(2) => self.add_schedule(),
+                _ if self.focus == ScheduleFocus::List => self.handle_list_key_event(key),
+                _ => self.handle_field_key_event(key),
+            }
+        }
+    }
+
+    /// 当聚焦于定时任务列表时，处理输入
+    fn handle_list_key_event(&mut self, key: event::KeyEvent) {
+        match key.code {
+            event::KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            event::KeyCode::Down if self.selected + 1 < self.schedules.len() => self.selected += 1,
+            event::KeyCode::Char(' ') | event::KeyCode::Enter => {
+                if let Some(schedule) = self.schedules.get_mut(self.selected) {
+                    schedule.enabled = !schedule.enabled;
+                    let _ = save_schedule(schedule);
+                }
+            }
+            event::KeyCode::Delete => {
+                if let Some(schedule) = self.schedules.get(self.selected) {
+                    let _ = delete_schedule(schedule.schedule_id.clone());
+                    self.reload();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 当聚焦于输入框时，处理输入
+    fn handle_field_key_event(&mut self, key: event::KeyEvent) {
+        let Some((_, _, field)) = self.fields.iter_mut().find(|(focus, _, _)| *focus == self.focus) else {
+            return;
+        };
+        match key.code {
+            event::KeyCode::Backspace => field.delete_pre_char(),
+            event::KeyCode::Delete => field.delete_suf_char(),
+            event::KeyCode::Left => field.move_cursor_left(field.get_current_char()),
+            event::KeyCode::Right => field.move_cursor_right(field.get_next_char()),
+            event::KeyCode::Home => field.home_of_cursor(),
+            event::KeyCode::End => field.end_of_cursor(),
+            event::KeyCode::Char(x) => field.enter_char(x),
+            _ => {}
+        }
+    }
+
+    /// 切换到下一个可聚焦区域
+    fn next_focus(&mut self) {
+        let next = (self.focus as usize + 1) % ScheduleFocus::COUNT;
+        self.focus = ScheduleFocus::from_repr(next).unwrap();
+    }
+
+    /// 获取输入框内容
+    fn field_content(&self, focus: ScheduleFocus) -> String {
+        self.fields
+            .iter()
+            .find(|(f, _, _)| *f == focus)
+            .map(|(_, _, field)| field.get_content())
+            .unwrap_or_default()
+    }
+
+    /// 根据输入框内容新增定时任务
+    fn add_schedule(&mut self) {
+        let name = self.field_content(ScheduleFocus::Name);
+        let prompt = self.field_content(ScheduleFocus::Prompt);
+        if name.trim().is_empty() || prompt.trim().is_empty() {
+            self.error = Some("name and prompt are required".into());
+            return;
+        }
+        let next_run_time = match parse_start_time(&self.field_content(ScheduleFocus::StartTime)) {
+            Ok(time) => time,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+        let repeat_minutes = match parse_repeat(&self.field_content(ScheduleFocus::Repeat)) {
+            Ok(minutes) => minutes,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+        let schedule = Schedule {
+            schedule_id: generate_unique_id(),
+            schedule_name: name.trim().into(),
+            schedule_prompt: prompt,
+            watch_file: self.field_content(ScheduleFocus::WatchFile).trim().into(),
+            repeat_minutes,
+            next_run_time,
+            enabled: true,
+        };
+        match save_schedule(&schedule) {
+            Ok(_) => {
+                self.error = None;
+                self.fields.iter_mut().for_each(|(_, _, field)| field.clear());
+                self.reload();
+                self.focus = ScheduleFocus::List;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// 重新查询定时任务列表
+    fn reload(&mut self) {
+        self.schedules = query_schedules().unwrap_or_default();
+        self.selected = self.selected.min(self.schedules.len().saturating_sub(1));
+    }
+
+    /// 绘制定时任务窗口UI
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let [header_area, list_area, form_area] = Layout::vertical([Length(1), Fill(1), Length(6)]).areas(area);
+        self.render_header_area(frame, header_area);
+        self.render_list_area(frame, list_area);
+        self.render_form_area(frame, form_area);
+    }
+
+    /// 绘制头部区域
+    fn render_header_area(&mut self, frame: &mut Frame, header_area: Rect) {
+        let [left, center, right] = Layout::horizontal([Length(9), Fill(1), Length(9)]).areas(header_area);
+
+        let left_paragraph = Paragraph::new("EXIT(ESC)").style(Color::Red).left_aligned();
+        frame.render_widget(left_paragraph, left);
+        let right_paragraph = Paragraph::new("ADD(F2)").style(Color::Green).right_aligned();
+        frame.render_widget(right_paragraph, right);
+
+        let center_paragraph = Paragraph::new("Scheduled Prompts")
+            .style(Style::default().fg(Color::LightBlue))
+            .centered();
+        frame.render_widget(center_paragraph, center);
+    }
+
+    /// 绘制定时任务列表
+    fn render_list_area(&mut self, frame: &mut Frame, list_area: Rect) {
+        let block = Block::default()
+            .title("schedules (Space: enable/disable, Delete: remove)")
+            .borders(Borders::ALL)
+            .border_style(if self.focus == ScheduleFocus::List {
+                Color::Green
+            } else {
+                Color::White
+            });
+        let rows: Vec<Row> = self
+            .schedules
+            .iter()
+            .enumerate()
+            .map(|(i, schedule)| {
+                let row = Row::new(vec![
+                    if schedule.enabled { "on" } else { "off" }.to_string(),
+                    schedule.schedule_name.clone(),
+                    schedule.next_run_time.format("%Y/%m/%d %H:%M").to_string(),
+                    format_repeat(schedule.repeat_minutes),
+                    schedule.watch_file.clone(),
+                    schedule.schedule_prompt.lines().next().unwrap_or_default().to_string(),
+                ]);
+                if i == self.selected && self.focus == ScheduleFocus::List {
+                    row.reversed()
+                } else if schedule.enabled {
+                    row
+                } else {
+                    row.fg(Color::DarkGray)
+                }
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [Length(4), Length(20), Length(17), Length(6), Length(24), Fill(1)],
+        )
+        .header(Row::new(vec!["", "name", "next run", "repeat", "watch file", "prompt"]).fg(Color::Yellow))
+        .block(block);
+        frame.render_widget(table, list_area);
+    }
+
+    /// 绘制新增定时任务的输入区域
+    fn render_form_area(&mut self, frame: &mut Frame, form_area: Rect) {
+        let [first_row, second_row] = Layout::vertical([Length(3), Length(3)]).areas(form_area);
+        let [name_area, prompt_area] = Layout::horizontal([Length(30), Fill(1)]).areas(first_row);
+        let [watch_file_area, start_time_area, repeat_area] =
+            Layout::horizontal([Fill(1), Length(24), Length(24)]).areas(second_row);
+        let areas = [name_area, prompt_area, watch_file_area, start_time_area, repeat_area];
+        for ((focus, label, field), area) in self.fields.iter_mut().zip(areas) {
+            field.set_width_height((area.width as usize).saturating_sub(2), 1);
+            let focused = self.focus == *focus;
+            // 新增失败时在提示词输入框上显示错误信息
+            let title = match &self.error {
+                Some(error) if *focus == ScheduleFocus::Prompt => format!("{} [{}]", label, error),
+                _ => label.clone(),
+            };
+            let block = Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(if focused { Color::Green } else { Color::White });
+            let paragraph = Paragraph::new(field.should_show_text())
+                .block(block)
+                .style(Style::default().fg(Color::Yellow));
+            frame.render_widget(paragraph, area);
+            if focused {
+                let (x, y) = field.get_cursor_position();
+                frame.set_cursor_position(Position::new(area.x + x as u16 + 1, area.y + y as u16 + 1));
+            }
+        }
+    }
+}
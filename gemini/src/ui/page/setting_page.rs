@@ -1,54 +1,153 @@
+use std::env;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
 use ratatui::{
     crossterm::event::{self, Event, KeyEventKind, KeyModifiers},
     layout::{
-        Constraint::{self, Fill, Length, Min},
-        Layout, Position, Rect,
+        Constraint::{Fill, Length, Min},
+        Layout, Rect,
     },
     style::{Color, Style},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Paragraph, Tabs},
     Frame,
 };
 use strum::{EnumCount, FromRepr};
 
-use crate::utils::store_utils::{read_config, save_config, StoreData};
+use crate::utils::key_utils::{KeyBinding, DEFAULT_COPY_REPLY_KEY};
+use crate::utils::model_utils::{list_models, list_tuned_models};
+use crate::utils::quota_utils::check_key_limits;
+use crate::utils::store_utils::{export_config, import_config, read_config, save_config, StoreData, EXPORT_FILE_NAME};
+use crate::utils::translate_utils::{AUTO_LANGUAGE, DEFAULT_TARGET_LANGUAGE};
+
+use crate::ui::component::form::{
+    form_field::{FieldIdentifier, FormField},
+    form_widget::Form,
+};
+use crate::ui::theme::Theme;
 
-use crate::ui::component::input::{input_trait::InputTextComponent, text_area::TextArea, text_field::TextField};
+use super::main_page::{MainFocusComponent, DEFAULT_COLLAPSE_LINES, DEFAULT_TITLE_MAX_WIDTH};
 
 /// 窗口UI
 pub struct SettingUI {
-    /// 选中的输入框
-    select_input_field: InputIdentifier,
-    /// 组件列表，先纵向再横向排列
-    components: Vec<(Constraint, Vec<SettingComponent>)>,
+    /// 选中的分组
+    select_tab: SettingTab,
+    /// 每个分组的配置表单
+    sections: Vec<(SettingTab, Form<InputIdentifier>)>,
     /// 修改后的配置数据
     data: StoreData,
+    /// 提示信息，如校验失败或存在未保存的修改
+    message: Option<String>,
+    /// 存在未保存的修改时，是否已经提示过再次按下 Esc 放弃修改
+    confirm_discard: bool,
     /// 是否需要更新配置标志位
     pub update: bool,
+    /// 密钥可用的对话模型，用于模型的下拉列表
+    models: Vec<String>,
+    /// 后台查询模型列表的结果
+    models_rx: Option<Receiver<Vec<String>>>,
     /// 是否应该退出程序
     pub should_exit: bool,
 }
 
-pub struct SettingComponent {
-    /// 标识符
-    identifier: InputIdentifier,
-    /// 提示文本
-    label: String,
-    /// 布局属性
-    layout: Constraint,
-    // 输入框组件
-    input_component: Box<dyn InputTextComponent>,
+/// 配置分组
+#[derive(Clone, Copy, EnumCount, FromRepr, PartialEq, Eq)]
+pub enum SettingTab {
+    Api,
+    Generation,
+    Translation,
+    Appearance,
+    Keybindings,
 }
+
 /// 组件标识符枚举
-#[derive(Clone, EnumCount, FromRepr, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum InputIdentifier {
     Model,
+    FallbackModel,
     Key,
+    ImageModel,
+    DailyRequestLimit,
     SystemInstruction,
     ResponseMineType,
     MaxOutputTokens,
     Temperature,
     TopP,
     TopK,
+    SourceLanguage,
+    TargetLanguage,
+    Accessible,
+    Theme,
+    Icons,
+    CollapseLines,
+    TitleMaxWidth,
+    CopyReplyKey,
+    FocusOrder,
+}
+
+impl SettingTab {
+    /// 分组标题
+    fn title(&self) -> &'static str {
+        match self {
+            SettingTab::Api => "API",
+            SettingTab::Generation => "Generation",
+            SettingTab::Translation => "Translation",
+            SettingTab::Appearance => "Appearance",
+            SettingTab::Keybindings => "Keybindings",
+        }
+    }
+}
+
+impl FieldIdentifier for InputIdentifier {
+    fn validate(&self, content: &str) -> Option<&'static str> {
+        let content = content.trim();
+        let valid = match self {
+            InputIdentifier::Model => !content.is_empty(),
+            InputIdentifier::Key => !content.is_empty(),
+            InputIdentifier::DailyRequestLimit => content.is_empty() || content.parse::<u64>().is_ok(),
+            InputIdentifier::MaxOutputTokens | InputIdentifier::TopK => content.parse::<u32>().is_ok(),
+            InputIdentifier::Temperature => content.parse::<f32>().is_ok_and(|value| (0.0..=2.0).contains(&value)),
+            InputIdentifier::TopP => content.parse::<f32>().is_ok_and(|value| (0.0..=1.0).contains(&value)),
+            InputIdentifier::CollapseLines => content.parse::<u16>().is_ok(),
+            InputIdentifier::TitleMaxWidth => content.parse::<usize>().is_ok_and(|width| width > 0),
+            InputIdentifier::CopyReplyKey => content.is_empty() || KeyBinding::parse(content).is_some(),
+            InputIdentifier::FocusOrder => MainFocusComponent::parse_order(&split_list(content)).is_some(),
+            _ => true,
+        };
+        if valid {
+            return None;
+        }
+        Some(match self {
+            InputIdentifier::Model | InputIdentifier::Key => "required",
+            InputIdentifier::DailyRequestLimit => "expected a number or empty",
+            InputIdentifier::TitleMaxWidth => "expected a positive number",
+            InputIdentifier::MaxOutputTokens | InputIdentifier::TopK | InputIdentifier::CollapseLines => {
+                "expected a number"
+            }
+            InputIdentifier::Temperature => "expected a number between 0 and 2",
+            InputIdentifier::TopP => "expected a number between 0 and 1",
+            InputIdentifier::FocusOrder => "unknown component",
+            _ => "invalid key binding",
+        })
+    }
+}
+
+/// 拆分以逗号分隔的列表，忽略空项
+fn split_list(content: &str) -> Vec<String> {
+    content
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// 解析开关配置，on/true/1 为开启，off/false/0 为关闭
+fn parse_switch(content: &str) -> Option<bool> {
+    match content.trim() {
+        "on" | "true" | "1" => Some(true),
+        "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
 }
 
 impl SettingUI {
@@ -56,180 +155,444 @@ impl SettingUI {
     pub fn new() -> Self {
         let data = read_config().unwrap_or_default();
         Self {
-            select_input_field: InputIdentifier::SystemInstruction,
+            select_tab: SettingTab::Api,
             update: false,
-            data: data.clone(),
+            sections: Self::build_sections(data.clone()),
+            models: Vec::new(),
+            models_rx: Self::load_models(data.key.clone()),
+            data,
+            message: None,
+            confirm_discard: false,
             should_exit: false,
-            components: vec![
-                (
-                    Length(3),
-                    vec![
-                        SettingComponent {
-                            identifier: InputIdentifier::Model,
-                            label: "model".into(),
-                            layout: Length(30),
-                            input_component: Box::new(TextField::new(data.model.to_string())),
-                        },
-                        SettingComponent {
-                            identifier: InputIdentifier::Key,
-                            label: "key".into(),
-                            layout: Fill(20),
-                            input_component: Box::new(TextField::new(data.key)),
-                        },
-                    ],
-                ),
-                (
-                    Min(10),
-                    vec![SettingComponent {
-                        identifier: InputIdentifier::SystemInstruction,
-                        label: "system instruction".into(),
-                        layout: Fill(1),
-                        input_component: Box::new(TextArea::new(data.system_instruction.unwrap_or("".into()))),
-                    }],
-                ),
-                (
-                    Length(3),
-                    vec![
-                        SettingComponent {
-                            identifier: InputIdentifier::ResponseMineType,
-                            label: "response mine type".into(),
-                            layout: Fill(1),
-                            input_component: Box::new(TextField::new(
-                                data.options.response_mime_type.unwrap_or("".into()),
-                            )),
-                        },
-                        SettingComponent {
-                            identifier: InputIdentifier::MaxOutputTokens,
-                            label: "max output tokens".into(),
-                            layout: Fill(1),
-                            input_component: Box::new(TextField::new(
-                                data.options.max_output_tokens.unwrap_or(0).to_string(),
-                            )),
-                        },
-                    ],
-                ),
-                (
+        }
+    }
+
+    /// 在后台查询密钥可用的对话模型，查询失败时模型的下拉列表只包含当前配置的模型
+    fn load_models(key: String) -> Option<Receiver<Vec<String>>> {
+        if key.trim().is_empty() {
+            return None;
+        }
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            if let Ok(mut models) = list_models(key.trim()) {
+                // 微调模型的查询可能需要 OAuth 授权，查询失败时只列出基础模型
+                models.extend(list_tuned_models(key.trim()).unwrap_or_default());
+                let _ = tx.send(models);
+            }
+        });
+        Some(rx)
+    }
+
+    /// 接收后台查询到的模型列表
+    fn poll_models(&mut self) {
+        let Some(models) = self.models_rx.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return;
+        };
+        self.models_rx = None;
+        self.models = models;
+        self.apply_models();
+    }
+
+    /// 将模型列表填入模型与备用模型的下拉列表，备用模型可以为空
+    fn apply_models(&mut self) {
+        if self.models.is_empty() {
+            return;
+        }
+        let models = self.models.clone();
+        let Some((_, form)) = self.sections.iter_mut().find(|(tab, _)| *tab == SettingTab::Api) else {
+            return;
+        };
+        form.set_options(InputIdentifier::Model, models.clone());
+        form.set_options(
+            InputIdentifier::FallbackModel,
+            [String::new()].into_iter().chain(models).collect(),
+        );
+    }
+
+    /// 根据配置数据创建各分组的表单
+    fn build_sections(data: StoreData) -> Vec<(SettingTab, Form<InputIdentifier>)> {
+        let options = data.options.clone();
+        let themes = Theme::NAMES.split('/').map(String::from).collect();
+        vec![
+            (
+                SettingTab::Api,
+                Form::new(vec![
+                    (
+                        Length(3),
+                        vec![
+                            FormField::select(
+                                InputIdentifier::Model,
+                                "model (Enter: choose)",
+                                Length(30),
+                                Vec::new(),
+                                &data.model.to_string(),
+                            ),
+                            FormField::select(
+                                InputIdentifier::FallbackModel,
+                                "fallback model (Enter: choose)",
+                                Length(34),
+                                vec![String::new()],
+                                &data.fallback_model.unwrap_or_default(),
+                            ),
+                            FormField::text(InputIdentifier::Key, "key", Fill(20), data.key),
+                        ],
+                    ),
+                    (
+                        Length(3),
+                        vec![
+                            FormField::text(
+                                InputIdentifier::ImageModel,
+                                "image model (empty for default)",
+                                Fill(1),
+                                data.image_model.unwrap_or_default(),
+                            ),
+                            FormField::text(
+                                InputIdentifier::DailyRequestLimit,
+                                "daily request limit (empty for none)",
+                                Fill(1),
+                                data.daily_request_limit
+                                    .map(|limit| limit.to_string())
+                                    .unwrap_or_default(),
+                            ),
+                        ],
+                    ),
+                ]),
+            ),
+            (
+                SettingTab::Generation,
+                Form::new(vec![
+                    (
+                        Min(10),
+                        vec![FormField::text_area(
+                            InputIdentifier::SystemInstruction,
+                            "system instruction",
+                            Fill(1),
+                            data.system_instruction.unwrap_or("".into()),
+                        )],
+                    ),
+                    (
+                        Length(3),
+                        vec![
+                            FormField::text(
+                                InputIdentifier::ResponseMineType,
+                                "response mine type",
+                                Fill(1),
+                                options.response_mime_type.unwrap_or("".into()),
+                            ),
+                            FormField::text(
+                                InputIdentifier::MaxOutputTokens,
+                                "max output tokens",
+                                Fill(1),
+                                options.max_output_tokens.unwrap_or(0).to_string(),
+                            ),
+                        ],
+                    ),
+                    (
+                        Length(3),
+                        vec![
+                            FormField::slider(
+                                InputIdentifier::Temperature,
+                                "temperature (Left/Right)",
+                                Fill(1),
+                                0.0..=2.0,
+                                0.1,
+                                options.temperature.unwrap_or(0.0),
+                            ),
+                            FormField::slider(
+                                InputIdentifier::TopP,
+                                "top p (Left/Right)",
+                                Fill(1),
+                                0.0..=1.0,
+                                0.05,
+                                options.top_p.unwrap_or(0.0),
+                            ),
+                            FormField::text(
+                                InputIdentifier::TopK,
+                                "top k",
+                                Min(5),
+                                options.top_k.unwrap_or(0).to_string(),
+                            ),
+                        ],
+                    ),
+                ]),
+            ),
+            (
+                SettingTab::Translation,
+                Form::new(vec![(
                     Length(3),
                     vec![
-                        SettingComponent {
-                            identifier: InputIdentifier::Temperature,
-                            label: "temperature".into(),
-                            layout: Fill(1),
-                            input_component: Box::new(TextField::new(
-                                data.options.temperature.unwrap_or(0.0).to_string(),
-                            )),
-                        },
-                        SettingComponent {
-                            identifier: InputIdentifier::TopP,
-                            label: "top p".into(),
-                            layout: Min(5),
-                            input_component: Box::new(TextField::new(data.options.top_p.unwrap_or(0.0).to_string())),
-                        },
-                        SettingComponent {
-                            identifier: InputIdentifier::TopK,
-                            label: "top k".into(),
-                            layout: Min(5),
-                            input_component: Box::new(TextField::new(data.options.top_k.unwrap_or(0).to_string())),
-                        },
+                        FormField::text(
+                            InputIdentifier::SourceLanguage,
+                            "translation source language",
+                            Fill(1),
+                            data.source_language.unwrap_or(AUTO_LANGUAGE.into()),
+                        ),
+                        FormField::text(
+                            InputIdentifier::TargetLanguage,
+                            "translation target language",
+                            Fill(1),
+                            data.target_language.unwrap_or(DEFAULT_TARGET_LANGUAGE.into()),
+                        ),
                     ],
-                ),
-            ],
-        }
+                )]),
+            ),
+            (
+                SettingTab::Appearance,
+                Form::new(vec![
+                    (
+                        Length(3),
+                        vec![
+                            FormField::checkbox(
+                                InputIdentifier::Accessible,
+                                "accessibility mode for screen readers (Space)",
+                                Fill(1),
+                                data.accessible,
+                            ),
+                            FormField::select(
+                                InputIdentifier::Theme,
+                                "theme (Enter: choose)",
+                                Fill(1),
+                                themes,
+                                Theme::from_config(data.theme.as_deref()).name(),
+                            ),
+                        ],
+                    ),
+                    (
+                        Length(3),
+                        vec![
+                            FormField::checkbox(InputIdentifier::Icons, "nerd font icons (Space)", Fill(1), data.icons),
+                            FormField::text(
+                                InputIdentifier::CollapseLines,
+                                "collapse messages longer than (lines, 0 to disable)",
+                                Fill(1),
+                                data.collapse_lines.unwrap_or(DEFAULT_COLLAPSE_LINES).to_string(),
+                            ),
+                            FormField::text(
+                                InputIdentifier::TitleMaxWidth,
+                                "max generated title width (columns)",
+                                Fill(1),
+                                data.title_max_width.unwrap_or(DEFAULT_TITLE_MAX_WIDTH).to_string(),
+                            ),
+                        ],
+                    ),
+                ]),
+            ),
+            (
+                SettingTab::Keybindings,
+                Form::new(vec![
+                    (
+                        Length(3),
+                        vec![FormField::text(
+                            InputIdentifier::CopyReplyKey,
+                            &format!("copy last reply (empty for {})", DEFAULT_COPY_REPLY_KEY),
+                            Fill(1),
+                            data.copy_reply_key.unwrap_or_default(),
+                        )],
+                    ),
+                    (
+                        Length(3),
+                        vec![FormField::text(
+                            InputIdentifier::FocusOrder,
+                            "tab focus order, comma separated (empty for default)",
+                            Fill(1),
+                            data.focus_order.join(", "),
+                        )],
+                    ),
+                ]),
+            ),
+        ]
     }
+
     /// 处理用户输入
     pub fn handle_key(&mut self) {
         if let Ok(Event::Key(key)) = event::read() {
             if key.kind != KeyEventKind::Press {
                 return;
             }
-            // 获取当前选中的输入框
-            let component = self.get_current_input_field().unwrap();
+            self.poll_models();
+            // 下拉列表打开时，所有按键都交给下拉列表处理
+            if self.current_form().is_dropdown_open() {
+                return self.current_form().handle_key(key);
+            }
+            // 再次按下 Esc 时放弃修改，按下其他键则取消并清除提示
+            if key.code == event::KeyCode::Esc {
+                self.exit();
+                return;
+            }
+            self.confirm_discard = false;
+            self.message = None;
             match key.code {
-                event::KeyCode::Enter => component.input_component.handle_enter_key(),
-                event::KeyCode::Tab => self.next_input_field(),
-                event::KeyCode::Char('s') if key.modifiers.contains(event::KeyModifiers::CONTROL) => self.save_config(),
-                event::KeyCode::F(2) => self.save_config(),
-                event::KeyCode::Esc => self.should_exit = true,
-                event::KeyCode::Backspace => component.input_component.delete_pre_char(),
-                event::KeyCode::Delete => component.input_component.delete_suf_char(),
-                event::KeyCode::Left => component
-                    .input_component
-                    .move_cursor_left(component.input_component.get_current_char()),
-                event::KeyCode::Right => component
-                    .input_component
-                    .move_cursor_right(component.input_component.get_next_char()),
-                event::KeyCode::Up => component.input_component.move_cursor_up(),
-                event::KeyCode::Down => component.input_component.move_cursor_down(),
-                event::KeyCode::Home => {
-                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                        component.input_component.home_of_multiline()
-                    } else {
-                        component.input_component.home_of_cursor()
-                    }
+                event::KeyCode::PageDown => return self.switch_tab(1),
+                event::KeyCode::PageUp => return self.switch_tab(SettingTab::COUNT - 1),
+                event::KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return self.save_config()
+                }
+                event::KeyCode::F(2) => return self.save_config(),
+                event::KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return self.reset_to_defaults()
+                }
+                event::KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return self.export_config(false)
+                }
+                event::KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::ALT) => {
+                    return self.export_config(true)
                 }
-                event::KeyCode::End => {
-                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                        component.input_component.end_of_multiline()
-                    } else {
-                        component.input_component.end_of_cursor()
-                    }
+                event::KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return self.import_config()
                 }
-                event::KeyCode::Char(x) => component.input_component.enter_char(x),
+                event::KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => return self.test_key(),
                 _ => {}
             }
+            self.current_form().handle_key(key);
+        }
+    }
+
+    /// 当前分组的表单
+    fn current_form(&mut self) -> &mut Form<InputIdentifier> {
+        let tab = self.select_tab;
+        let (_, form) = self.sections.iter_mut().find(|(section, _)| *section == tab).unwrap();
+        form
+    }
+
+    /// 所有分组的字段
+    fn all_fields(&self) -> impl Iterator<Item = &FormField<InputIdentifier>> {
+        self.sections.iter().flat_map(|(_, form)| form.fields())
+    }
+
+    /// 向后切换 `step` 个分组
+    fn switch_tab(&mut self, step: usize) {
+        let next = (self.select_tab as usize + step) % SettingTab::COUNT;
+        self.select_tab = SettingTab::from_repr(next).unwrap();
+        self.current_form().select_first();
+    }
+
+    /// 是否存在未保存的修改
+    fn is_dirty(&self) -> bool {
+        self.sections.iter().any(|(_, form)| form.is_dirty())
+    }
+
+    /// 退出配置窗口，存在未保存的修改时需要再次确认
+    fn exit(&mut self) {
+        if self.is_dirty() && !self.confirm_discard {
+            self.confirm_discard = true;
+            self.message = Some("Unsaved changes, press Esc again to discard them".into());
+            return;
+        }
+        self.should_exit = true;
+    }
+
+    /// 将配置数据填入各表单，保留字段的初始内容，以便显示修改状态
+    fn load_data(&mut self, data: StoreData) {
+        let mut sections = Self::build_sections(data);
+        for ((_, new), (_, old)) in sections.iter_mut().zip(self.sections.iter()) {
+            new.keep_original(old);
         }
+        self.sections = sections;
+        self.apply_models();
+    }
+
+    /// 将界面中的配置恢复为默认值，密钥与未在界面中显示的配置保持不变，保存后生效
+    fn reset_to_defaults(&mut self) {
+        let data = StoreData {
+            key: self.form_data().key,
+            ..Default::default()
+        };
+        self.load_data(data);
+        self.message = Some("Reset to defaults, press F2 to save".into());
+    }
+
+    /// 将界面中的配置导出到当前目录，`include_key` 为 false 时隐藏密钥
+    fn export_config(&mut self, include_key: bool) {
+        let path = env::current_dir().unwrap_or_default().join(EXPORT_FILE_NAME);
+        self.message = Some(match export_config(self.form_data(), &path, include_key) {
+            Ok(_) if include_key => format!("Exported to {} (with key)", path.display()),
+            Ok(_) => format!("Exported to {} (key redacted)", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
     }
 
-    /// 获取当前选中的组件
-    fn get_current_input_field(&mut self) -> Option<&mut SettingComponent> {
-        for (_, components) in self.components.iter_mut() {
-            for component in components.iter_mut() {
-                if self.select_input_field == component.identifier {
-                    return Some(component);
+    /// 从当前目录导入配置，导入的配置中没有密钥时保留当前密钥，保存后生效
+    fn import_config(&mut self) {
+        let path = env::current_dir().unwrap_or_default().join(EXPORT_FILE_NAME);
+        match import_config(&path) {
+            Ok(mut data) => {
+                if data.key.is_empty() {
+                    data.key = self.form_data().key;
                 }
+                data.db_version = self.data.db_version.clone();
+                self.data = data.clone();
+                self.load_data(data);
+                self.message = Some(format!("Imported from {}, press F2 to save", path.display()));
             }
+            Err(e) => self.message = Some(format!("Import failed: {}", e)),
         }
-        None
     }
 
-    /// 切换到下一个输入组件
-    fn next_input_field(&mut self) {
-        let current = self.select_input_field.clone() as usize;
-        let next = (current + 1) % InputIdentifier::COUNT;
-        self.select_input_field = InputIdentifier::from_repr(next).unwrap();
+    /// 使用界面中的密钥、模型与生成参数测试配置，显示模型不支持的参数
+    fn test_key(&mut self) {
+        let data = self.form_data();
+        if data.key.trim().is_empty() {
+            self.message = Some("Key is empty".into());
+            return;
+        }
+        let model = data.model.to_string();
+        self.message = Some(match check_key_limits(data.key.trim(), &model, &data.options) {
+            Ok(warnings) if warnings.is_empty() => format!("Key OK, no problems found for {}", model),
+            Ok(warnings) => warnings.join("; "),
+            Err(e) => format!("Test failed: {}", e),
+        });
     }
 
-    /// 保存当前配置并退出配置窗口
-    fn save_config(&mut self) {
-        // 遍历所有组件，将其现在显示的值更新到配置中
-        for (_, line) in self.components.iter() {
-            for component in line.iter() {
-                match component.identifier {
-                    InputIdentifier::Model => self.data.model = component.input_component.get_content().into(),
-                    InputIdentifier::Key => self.data.key = component.input_component.get_content(),
-                    InputIdentifier::SystemInstruction => {
-                        self.data.system_instruction = Some(component.input_component.get_content())
-                    }
-                    InputIdentifier::ResponseMineType => {
-                        self.data.options.response_mime_type = Some(component.input_component.get_content())
-                    }
-                    InputIdentifier::MaxOutputTokens => {
-                        self.data.options.max_output_tokens =
-                            Some(component.input_component.get_content().parse().unwrap_or(0))
-                    }
-                    InputIdentifier::Temperature => {
-                        self.data.options.temperature =
-                            Some(component.input_component.get_content().parse().unwrap_or(0.0))
-                    }
-                    InputIdentifier::TopP => {
-                        self.data.options.top_p = Some(component.input_component.get_content().parse().unwrap_or(0.0))
-                    }
-                    InputIdentifier::TopK => {
-                        self.data.options.top_k = Some(component.input_component.get_content().parse().unwrap_or(0))
-                    }
+    /// 界面中的配置，未在界面中显示的配置保持不变
+    fn form_data(&self) -> StoreData {
+        let mut data = self.data.clone();
+        for field in self.all_fields() {
+            let content = field.value();
+            match field.identifier {
+                InputIdentifier::Model => data.model = content.into(),
+                InputIdentifier::FallbackModel => {
+                    data.fallback_model = Some(content.trim().to_string()).filter(|model| !model.is_empty())
+                }
+                InputIdentifier::Key => data.key = content,
+                InputIdentifier::ImageModel => {
+                    data.image_model = Some(content).filter(|model| !model.trim().is_empty())
+                }
+                InputIdentifier::DailyRequestLimit => data.daily_request_limit = content.trim().parse().ok(),
+                InputIdentifier::SystemInstruction => data.system_instruction = Some(content),
+                InputIdentifier::ResponseMineType => data.options.response_mime_type = Some(content),
+                InputIdentifier::MaxOutputTokens => data.options.max_output_tokens = content.trim().parse().ok(),
+                InputIdentifier::Temperature => data.options.temperature = content.trim().parse().ok(),
+                InputIdentifier::TopP => data.options.top_p = content.trim().parse().ok(),
+                InputIdentifier::TopK => data.options.top_k = content.trim().parse().ok(),
+                InputIdentifier::SourceLanguage => data.source_language = Some(content),
+                InputIdentifier::TargetLanguage => data.target_language = Some(content),
+                InputIdentifier::Accessible => data.accessible = parse_switch(&content).unwrap_or_default(),
+                InputIdentifier::Theme => data.theme = Some(content.trim().into()),
+                InputIdentifier::Icons => data.icons = parse_switch(&content).unwrap_or_default(),
+                InputIdentifier::CollapseLines => data.collapse_lines = content.trim().parse().ok(),
+                InputIdentifier::TitleMaxWidth => data.title_max_width = content.trim().parse().ok(),
+                InputIdentifier::FocusOrder => data.focus_order = split_list(&content),
+                InputIdentifier::CopyReplyKey => {
+                    data.copy_reply_key = Some(content.trim().to_string()).filter(|key| !key.is_empty())
                 }
             }
         }
+        data
+    }
+
+    /// 保存当前配置并退出配置窗口，存在不合法的配置时跳转到该配置
+    fn save_config(&mut self) {
+        let invalid = self
+            .sections
+            .iter()
+            .find_map(|(tab, form)| Some((*tab, form.first_error()?)));
+        if let Some((tab, (identifier, message))) = invalid {
+            self.select_tab = tab;
+            self.current_form().select(identifier);
+            self.message = Some(message);
+            return;
+        }
+        self.data = self.form_data();
         save_config(self.data.clone()).unwrap();
         self.update = true;
         self.should_exit = true;
@@ -237,10 +600,14 @@ impl SettingUI {
 
     /// 绘制配置窗口UI
     pub fn draw(&mut self, frame: &mut Frame) {
+        self.poll_models();
         let area = frame.area();
-        let [header_area, content_area] = Layout::vertical([Length(1), Fill(1)]).areas(area);
+        let [header_area, tab_area, content_area, message_area] =
+            Layout::vertical([Length(1), Length(1), Fill(1), Length(1)]).areas(area);
         self.render_header_area(frame, header_area);
+        self.render_tab_area(frame, tab_area);
         self.render_content_area(frame, content_area);
+        self.render_message_area(frame, message_area);
     }
 
     /// 绘制配置窗口头部区域
@@ -252,48 +619,46 @@ impl SettingUI {
         let right_paragraph = Paragraph::new("SAVE(F2)").style(Color::Green).right_aligned();
         frame.render_widget(right_paragraph, right);
 
-        let center_paragraph = Paragraph::new("System Setting")
+        let title = if self.is_dirty() {
+            "System Setting *"
+        } else {
+            "System Setting"
+        };
+        let center_paragraph = Paragraph::new(title)
             .style(Style::default().fg(Color::LightBlue))
             .centered();
         frame.render_widget(center_paragraph, center);
     }
 
+    /// 绘制分组标签栏，存在修改的分组标题后显示 `*`
+    fn render_tab_area(&mut self, frame: &mut Frame, tab_area: Rect) {
+        let titles: Vec<String> = self
+            .sections
+            .iter()
+            .map(|(tab, form)| format!("{}{}", tab.title(), if form.is_dirty() { " *" } else { "" }))
+            .collect();
+        let [tabs_area, tip_area] = Layout::horizontal([Fill(1), Length(24)]).areas(tab_area);
+        let tabs = Tabs::new(titles)
+            .select(self.select_tab as usize)
+            .style(Style::default().fg(Color::DarkGray))
+            .highlight_style(Style::default().fg(Color::LightBlue));
+        frame.render_widget(tabs, tabs_area);
+        let tip = Paragraph::new("PgUp/PgDn switch tabs")
+            .style(Color::DarkGray)
+            .right_aligned();
+        frame.render_widget(tip, tip_area);
+    }
+
     /// 绘制配置窗口内容区域
     fn render_content_area(&mut self, frame: &mut Frame, content_area: Rect) {
-        let v_list: Vec<Constraint> = self.components.iter().map(|x| x.0).collect();
-        let areas = Layout::vertical(v_list).split(content_area);
-        for (i, (_, components)) in self.components.iter_mut().enumerate() {
-            let h_list: Vec<Constraint> = components.iter().map(|x| x.layout).collect();
-            let area = areas.clone()[i];
-            let h_areas = Layout::horizontal(h_list).split(area);
-            for (j, component) in components.iter_mut().enumerate() {
-                let input_area = h_areas.clone()[j];
-                // 设置输入框高度
-                let height = (input_area.height as usize).saturating_sub(2);
-                // 设置输入框宽度
-                let width = (input_area.width as usize).saturating_sub(2);
-                component.input_component.set_width_height(width, height);
-                // 预设输入框边框颜色，当输入框被选中时显示为绿色，否则显示为白色
-                let block_style = if self.select_input_field == component.identifier {
-                    Style::default().fg(Color::Green)
-                } else {
-                    Style::default().fg(Color::White)
-                };
-                // 预设输入框边框
-                let block = Block::default()
-                    .title(component.label.as_str())
-                    .style(block_style)
-                    .borders(Borders::ALL);
-                let input_paragraph = Paragraph::new(component.input_component.should_show_text())
-                    .block(block)
-                    .wrap(Wrap { trim: false })
-                    .style(Style::default().fg(Color::Yellow));
-                frame.render_widget(input_paragraph, input_area);
-                if self.select_input_field == component.identifier {
-                    let (x, y) = component.input_component.get_cursor_position();
-                    frame.set_cursor_position(Position::new(input_area.x + x as u16 + 1, input_area.y + y as u16 + 1));
-                }
-            }
+        self.current_form().draw(frame, content_area);
+    }
+
+    /// 绘制提示信息
+    fn render_message_area(&mut self, frame: &mut Frame, message_area: Rect) {
+        if let Some(message) = self.message.as_deref() {
+            let paragraph = Paragraph::new(message).style(Color::LightYellow);
+            frame.render_widget(paragraph, message_area);
         }
     }
 }
@@ -0,0 +1,147 @@
+use chrono::{Duration, Local};
+use ratatui::{
+    crossterm::event::{self, Event, KeyEventKind},
+    layout::{
+        Constraint::{Fill, Length},
+        Layout, Rect,
+    },
+    style::{Color, Style},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Gauge, Paragraph},
+    Frame,
+};
+
+use crate::model::db::Usage;
+use crate::utils::db_utils::query_usage;
+use crate::utils::store_utils::read_config;
+use crate::utils::usage_utils::{daily_totals, weekly_totals, UsageTotal};
+
+/// 统计的天数
+const DAYS: i64 = 14;
+/// 统计的周数
+const WEEKS: i64 = 8;
+
+/// 用量统计窗口UI
+pub struct UsageUI {
+    /// 最近若干周的用量
+    usages: Vec<Usage>,
+    /// 每日请求次数上限
+    daily_request_limit: Option<u64>,
+    /// 图表显示 token 数，否则显示请求次数
+    show_tokens: bool,
+    /// 是否应该退出窗口
+    pub should_exit: bool,
+}
+
+impl UsageUI {
+    /// 启动此窗口UI
+    pub fn new() -> Self {
+        let since = Local::now().date_naive() - Duration::weeks(WEEKS);
+        Self {
+            usages: query_usage(since).unwrap_or_default(),
+            daily_request_limit: read_config().ok().and_then(|data| data.daily_request_limit),
+            show_tokens: false,
+            should_exit: false,
+        }
+    }
+
+    /// 处理用户输入
+    pub fn handle_key(&mut self) {
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.kind != KeyEventKind::Press {
+                return;
+            }
+            match key.code {
+                event::KeyCode::Esc => self.should_exit = true,
+                event::KeyCode::Tab => self.show_tokens = !self.show_tokens,
+                _ => {}
+            }
+        }
+    }
+
+    /// 绘制用量统计窗口UI
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        let [header_area, today_area, daily_area, weekly_area] =
+            Layout::vertical([Length(1), Length(3), Fill(1), Fill(1)]).areas(area);
+        self.render_header_area(frame, header_area);
+        self.render_today_area(frame, today_area);
+        let title = if self.show_tokens { "tokens" } else { "requests" };
+        let daily = daily_totals(&self.usages, DAYS);
+        self.render_chart(frame, daily_area, &format!("daily {}", title), &daily);
+        let weekly = weekly_totals(&self.usages, WEEKS);
+        self.render_chart(frame, weekly_area, &format!("weekly {} (week of)", title), &weekly);
+    }
+
+    /// 绘制头部区域
+    fn render_header_area(&mut self, frame: &mut Frame, header_area: Rect) {
+        let [left, center, right] = Layout::horizontal([Length(9), Fill(1), Length(16)]).areas(header_area);
+        let left_paragraph = Paragraph::new("EXIT(ESC)").style(Color::Red).left_aligned();
+        frame.render_widget(left_paragraph, left);
+        let right_text = if self.show_tokens {
+            "REQUESTS(TAB)"
+        } else {
+            "TOKENS(TAB)"
+        };
+        let right_paragraph = Paragraph::new(right_text).style(Color::Green).right_aligned();
+        frame.render_widget(right_paragraph, right);
+        let center_paragraph = Paragraph::new("Usage")
+            .style(Style::default().fg(Color::LightBlue))
+            .centered();
+        frame.render_widget(center_paragraph, center);
+    }
+
+    /// 绘制今日用量，配置了每日请求次数上限时显示进度条
+    fn render_today_area(&mut self, frame: &mut Frame, today_area: Rect) {
+        let today = daily_totals(&self.usages, 1).pop().unwrap_or_default();
+        // token 数为估算值，仅通过 REST 接口发送的请求使用接口返回的用量
+        let summary = format!("today: {} requests, ~{} tokens", today.requests, today.tokens);
+        let block = Block::default().title(summary).borders(Borders::ALL);
+        match self.daily_request_limit.filter(|limit| *limit > 0) {
+            Some(limit) => {
+                let ratio = (today.requests as f64 / limit as f64).min(1.0);
+                let color = if ratio >= 0.9 {
+                    Color::Red
+                } else if ratio >= 0.7 {
+                    Color::Yellow
+                } else {
+                    Color::Green
+                };
+                let gauge = Gauge::default()
+                    .block(block)
+                    .gauge_style(color)
+                    .ratio(ratio)
+                    .label(format!("{} / {} requests", today.requests, limit));
+                frame.render_widget(gauge, today_area);
+            }
+            None => {
+                let paragraph = Paragraph::new("set daily_request_limit in gemini.json to track the free-tier limit")
+                    .style(Color::DarkGray)
+                    .block(block);
+                frame.render_widget(paragraph, today_area);
+            }
+        }
+    }
+
+    /// 绘制柱状图
+    fn render_chart(&self, frame: &mut Frame, area: Rect, title: &str, totals: &[UsageTotal]) {
+        let bars: Vec<Bar> = totals
+            .iter()
+            .map(|total| {
+                let value = if self.show_tokens { total.tokens } else { total.requests };
+                Bar::default().label(total.label.clone().into()).value(value)
+            })
+            .collect();
+        // 根据柱子数量平分宽度，柱子之间留出一个字符的间隔
+        let bar_width = (area.width.saturating_sub(2) / totals.len().max(1) as u16)
+            .saturating_sub(1)
+            .max(1);
+        let chart = BarChart::default()
+            .block(Block::default().title(title.to_string()).borders(Borders::ALL))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .bar_style(Color::LightBlue)
+            .value_style(Style::default().fg(Color::Black).bg(Color::LightBlue));
+        frame.render_widget(chart, area);
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use ratatui::{
+    crossterm::event::{self, Event, KeyEventKind},
+    layout::{
+        Constraint::{Fill, Length},
+        Layout, Rect,
+    },
+    style::{Color, Style},
+    widgets::Paragraph,
+    DefaultTerminal, Frame,
+};
+
+use crate::model::view::ChatMessage;
+use crate::ui::component::scroll::chat_show::ChatShowScrollProps;
+use crate::ui::theme::Theme;
+use crate::utils::store_utils::read_config;
+
+use super::main_page::UI;
+
+/// 只读查看导出会话的窗口UI，不需要密钥，不读写数据库，也不发送网络请求
+pub struct ViewUI {
+    /// 会话标题
+    title: String,
+    /// 聊天内容显示区域
+    chat_show: ChatShowScrollProps,
+    /// 界面配色主题
+    theme: Theme,
+    /// 是否应该退出程序
+    should_exit: bool,
+}
+
+impl ViewUI {
+    pub fn new(title: String, chat_history: Vec<ChatMessage>) -> Self {
+        let store_data = read_config().ok();
+        Self {
+            title,
+            chat_show: ChatShowScrollProps {
+                chat_history,
+                icons: store_data.as_ref().is_some_and(|data| data.icons),
+                ..Default::default()
+            },
+            // 配置文件不存在时仍然需要识别 NO_COLOR
+            theme: Theme::from_config(store_data.and_then(|data| data.theme).as_deref()),
+            should_exit: false,
+        }
+    }
+
+    /// 启动UI
+    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        while !self.should_exit {
+            terminal.draw(|frame| {
+                self.draw(frame);
+                self.theme.apply(frame.buffer_mut());
+            })?;
+            self.handle_key();
+        }
+        Ok(())
+    }
+
+    /// 绘制UI
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header_area, chat_area] = Layout::vertical([Length(1), Fill(1)]).areas(frame.area());
+        self.render_header_area(frame, header_area);
+        self.chat_show
+            .draw(frame, chat_area, || UI::chat_area_width(chat_area), true);
+    }
+
+    /// 绘制头部区域
+    fn render_header_area(&mut self, frame: &mut Frame, header_area: Rect) {
+        let [left, center, right] = Layout::horizontal([Length(9), Fill(1), Length(9)]).areas(header_area);
+        let left_paragraph = Paragraph::new("EXIT(ESC)").style(Color::Red).left_aligned();
+        frame.render_widget(left_paragraph, left);
+        let right_paragraph = Paragraph::new("READONLY").style(Color::DarkGray).right_aligned();
+        frame.render_widget(right_paragraph, right);
+        let center_paragraph = Paragraph::new(self.title.as_str())
+            .style(Style::default().fg(Color::LightBlue))
+            .centered();
+        frame.render_widget(center_paragraph, center);
+    }
+
+    /// 处理用户输入
+    fn handle_key(&mut self) {
+        if let Ok(Event::Key(key)) = event::read() {
+            if key.kind != KeyEventKind::Press {
+                return;
+            }
+            let chat_show = &mut self.chat_show;
+            match key.code {
+                event::KeyCode::Esc | event::KeyCode::Char('q') => self.should_exit = true,
+                event::KeyCode::Up => chat_show.scroll_offset = chat_show.scroll_offset.saturating_sub(1),
+                event::KeyCode::Down => {
+                    chat_show.scroll_offset = chat_show
+                        .scroll_offset
+                        .saturating_add(1)
+                        .min(chat_show.chat_history_area_height)
+                }
+                event::KeyCode::Left | event::KeyCode::Char('h') => {
+                    chat_show.horizontal_offset = chat_show.horizontal_offset.saturating_sub(1)
+                }
+                event::KeyCode::Right | event::KeyCode::Char('l') => {
+                    chat_show.horizontal_offset = chat_show
+                        .horizontal_offset
+                        .saturating_add(1)
+                        .min(chat_show.max_horizontal_offset)
+                }
+                event::KeyCode::Char('n') => chat_show.line_numbers = !chat_show.line_numbers,
+                event::KeyCode::Char('[') => chat_show.prev_message(),
+                event::KeyCode::Char(']') => chat_show.next_message(),
+                event::KeyCode::Char('r') => chat_show.toggle_raw_view(),
+                _ => {}
+            }
+        }
+    }
+}
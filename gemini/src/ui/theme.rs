@@ -0,0 +1,96 @@
+use std::env;
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+/// 界面配色主题
+///
+/// 界面中的颜色按用途固定使用：绿色表示聚焦，蓝色与品红表示选中，红色表示错误，
+/// 主题在每次绘制完成后按用途替换缓冲区中的颜色
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Theme {
+    /// 默认配色
+    #[default]
+    Default,
+    /// 高对比度配色，适合弱视用户
+    HighContrast,
+    /// 红绿色弱友好配色
+    Deuteranopia,
+    /// 单色模式，使用粗体与下划线代替颜色指示焦点
+    Monochrome,
+}
+
+impl Theme {
+    /// 所有主题名称
+    pub const NAMES: &'static str = "default/high-contrast/deuteranopia/monochrome";
+
+    /// 根据配置的主题名称选择主题，设置了 NO_COLOR 环境变量时始终使用单色模式
+    pub fn from_config(name: Option<&str>) -> Self {
+        if env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+            return Self::Monochrome;
+        }
+        match name.map(str::trim) {
+            Some("high-contrast") => Self::HighContrast,
+            Some("deuteranopia") => Self::Deuteranopia,
+            Some("monochrome") => Self::Monochrome,
+            _ => Self::Default,
+        }
+    }
+
+    /// 主题名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::HighContrast => "high-contrast",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Monochrome => "monochrome",
+        }
+    }
+
+    /// 将主题应用到绘制完成的缓冲区
+    pub fn apply(&self, buf: &mut Buffer) {
+        if *self == Self::Default {
+            return;
+        }
+        for cell in buf.content.iter_mut() {
+            if *self == Self::Monochrome {
+                let mut modifier = match cell.fg {
+                    Color::Green | Color::Blue | Color::LightMagenta => Modifier::BOLD | Modifier::UNDERLINED,
+                    Color::Red | Color::LightRed => Modifier::BOLD,
+                    _ => Modifier::empty(),
+                };
+                if cell.bg != Color::Reset {
+                    modifier |= Modifier::REVERSED;
+                }
+                cell.modifier |= modifier;
+                cell.fg = Color::Reset;
+                cell.bg = Color::Reset;
+            } else {
+                cell.fg = self.map(cell.fg);
+                cell.bg = self.map(cell.bg);
+            }
+        }
+    }
+
+    /// 按用途替换颜色
+    fn map(&self, color: Color) -> Color {
+        match (self, color) {
+            // 高对比度：去掉暗色，聚焦与选中使用最亮的颜色
+            (Self::HighContrast, Color::DarkGray | Color::Gray) => Color::White,
+            (Self::HighContrast, Color::Green | Color::LightGreen) => Color::LightYellow,
+            (Self::HighContrast, Color::Blue | Color::LightBlue) => Color::LightCyan,
+            (Self::HighContrast, Color::Red) => Color::LightRed,
+            (Self::HighContrast, Color::Cyan) => Color::LightCyan,
+            (Self::HighContrast, Color::Yellow) => Color::LightYellow,
+            // 红绿色弱：使用 Okabe-Ito 配色，以蓝色与橙色区分聚焦和错误
+            (Self::Deuteranopia, Color::Green) => Color::Rgb(86, 180, 233),
+            (Self::Deuteranopia, Color::LightGreen) => Color::Rgb(0, 158, 115),
+            (Self::Deuteranopia, Color::Red | Color::LightRed) => Color::Rgb(230, 159, 0),
+            (Self::Deuteranopia, Color::Blue) => Color::Rgb(0, 114, 178),
+            (Self::Deuteranopia, Color::LightMagenta) => Color::Rgb(204, 121, 167),
+            (_, color) => color,
+        }
+    }
+}
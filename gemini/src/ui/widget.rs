@@ -1,47 +1,407 @@
-use std::cmp::max;
+use std::{cmp::max, collections::HashMap, ops::Range, rc::Rc};
 
 use ratatui::{
     buffer::Buffer,
     layout::{
         Alignment,
-        Constraint::{Fill, Length, Max},
+        Constraint::{self, Fill, Length, Max},
         Flex, Layout, Rect,
     },
-    style::{Color, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
     widgets::{
         block::{Position, Title},
-        Block, Borders, Paragraph, Widget, Wrap,
+        Block, BorderType, Borders, Paragraph, Row, Table, Widget,
     },
 };
 
-use crate::{model::view::ChatMessage, utils::char_utils::s_length};
+use crate::{
+    model::view::{ChatMessage, Rating},
+    utils::{
+        char_utils::{c_len, s_length, truncate},
+        git_utils::find_file_references,
+        link_utils::find_links,
+        markdown_utils::{classify_lines, parse_code_blocks, parse_tables, CodeBlock, LineKind, MarkdownTable},
+        time_utils::relative_time,
+    },
+};
 
-use crate::model::view::Sender::{Bot, Never, User};
+use crate::model::view::Attachment;
+use crate::model::view::Sender::{Bot, Never, Note, User};
 
 use super::component::popup::delete_popup::ButtonType::{Cancel, Confirm};
-use super::component::{popup::delete_popup::DeletePopup, scroll::chat_item_list::SelectableConversation};
+use super::component::{
+    popup::delete_popup::DeletePopup, progress::progress_bar::ProgressBar,
+    scroll::chat_item_list::SelectableConversation,
+};
+use super::icon::Icon;
+
+/// 已完成样式处理与换行的聊天消息
+pub struct WrappedChatMessage {
+    /// 聊天消息
+    pub message: ChatMessage,
+    /// 排版后的消息片段，与排版缓存共享
+    pub segments: Rc<[MessageSegment]>,
+    /// 是否被选中
+    pub selected: bool,
+    /// 是否使用 Nerd Font 图标
+    pub icons: bool,
+    /// 对回复的评价
+    pub rating: Option<Rating>,
+    /// 主模型容量不足时实际回答的备用模型
+    pub fallback_model: Option<String>,
+}
+
+/// 消息排版选项
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LayoutOptions {
+    /// 可用宽度
+    pub width: usize,
+    /// 代码行水平滚动偏移量
+    pub horizontal_offset: u16,
+    /// 代码块是否显示行号
+    pub line_numbers: bool,
+    /// 是否展示未经处理的原始文本
+    pub raw: bool,
+}
+
+/// 消息排版后的片段
+#[derive(Clone)]
+pub enum MessageSegment {
+    /// 已换行的文本行
+    Lines(Vec<Line<'static>>),
+    /// 代码块，代码行不换行，超出宽度部分通过水平滚动查看
+    Code {
+        /// 边框标题，包含语言与文件名
+        title: String,
+        /// 代码行
+        lines: Vec<Line<'static>>,
+        /// 可用宽度
+        width: usize,
+        /// 水平滚动偏移量
+        offset: u16,
+        /// 是否显示行号
+        line_numbers: bool,
+    },
+    /// 表格
+    Table {
+        /// 表头
+        header: Vec<String>,
+        /// 数据行
+        rows: Vec<Vec<String>>,
+        /// 每一列的宽度
+        widths: Vec<u16>,
+    },
+    /// 图片缩略图，每个字符显示上下两个像素
+    Image(Vec<Vec<[u8; 3]>>),
+}
+
+impl MessageSegment {
+    /// 片段高度
+    fn height(&self) -> u16 {
+        match self {
+            MessageSegment::Lines(lines) => lines.len() as u16,
+            // 上下边框替代了代码块的围栏
+            MessageSegment::Code { lines, .. } => lines.len() as u16 + 2,
+            // 上下边框加上表头
+            MessageSegment::Table { rows, .. } => rows.len() as u16 + 3,
+            MessageSegment::Image(pixels) => pixels.len().div_ceil(2) as u16,
+        }
+    }
+
+    /// 片段宽度
+    fn width(&self) -> usize {
+        match self {
+            MessageSegment::Lines(lines) => lines.iter().map(line_width).max().unwrap_or_default(),
+            MessageSegment::Code {
+                title,
+                lines,
+                width,
+                line_numbers,
+                ..
+            } => {
+                let max_width = lines.iter().map(line_width).max().unwrap_or_default();
+                let gutter = gutter_width(lines.len(), *line_numbers);
+                (max(max_width, s_length(title.clone())) + gutter + 2).min(*width)
+            }
+            MessageSegment::Table { widths, .. } => table_width(widths),
+            MessageSegment::Image(pixels) => pixels.first().map(Vec::len).unwrap_or_default(),
+        }
+    }
+
+    /// 渲染片段
+    fn render(&self, area: Rect, buf: &mut Buffer, style: Style) {
+        match self {
+            MessageSegment::Lines(lines) => Paragraph::new(borrowed_lines(lines)).style(style).render(area, buf),
+            MessageSegment::Code {
+                title,
+                lines,
+                offset,
+                line_numbers,
+                ..
+            } => {
+                let block = Block::bordered().border_style(Color::DarkGray).title(title.as_str());
+                let inner = block.inner(area);
+                block.render(area, buf);
+                let gutter = gutter_width(lines.len(), *line_numbers) as u16;
+                let [number_area, code_area] = Layout::horizontal([Length(gutter), Fill(1)]).areas(inner);
+                // 渲染行号
+                if *line_numbers {
+                    let numbers: Vec<Line> = (1..=lines.len())
+                        .map(|number| Line::from(format!("{} ", number)))
+                        .collect();
+                    Paragraph::new(numbers)
+                        .style(Color::DarkGray)
+                        .right_aligned()
+                        .render(number_area, buf);
+                }
+                Paragraph::new(borrowed_lines(lines))
+                    .style(style)
+                    .scroll((0, *offset))
+                    .render(code_area, buf);
+            }
+            MessageSegment::Table { header, rows, widths } => {
+                let header = Row::new(header.iter().map(String::as_str)).style(style.bold());
+                let rows: Vec<Row> = rows
+                    .iter()
+                    .map(|row| Row::new(row.iter().map(String::as_str)))
+                    .collect();
+                let widths: Vec<Constraint> = widths.iter().map(|width| Length(*width)).collect();
+                let table = Table::new(rows, widths)
+                    .header(header)
+                    .style(style)
+                    .column_spacing(1)
+                    .block(Block::bordered().border_style(Color::DarkGray));
+                Widget::render(table, area, buf);
+            }
+            MessageSegment::Image(pixels) => {
+                // 上半块字符的前景色为上方像素，背景色为下方像素
+                for (y, rows) in pixels.chunks(2).enumerate().take(area.height as usize) {
+                    for (x, top) in rows[0].iter().enumerate().take(area.width as usize) {
+                        let bottom = rows.get(1).and_then(|row| row.get(x)).unwrap_or(top);
+                        buf[(area.x + x as u16, area.y + y as u16)]
+                            .set_char('▀')
+                            .set_fg(Color::Rgb(top[0], top[1], top[2]))
+                            .set_bg(Color::Rgb(bottom[0], bottom[1], bottom[2]));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 消息排版缓存，避免每一帧都重新换行所有消息。已保存的消息按记录 id 缓存，其他消息按下标缓存，
+/// 原地修改消息内容后需要调用 [`LayoutCache::clear`]
+#[derive(Default)]
+pub struct LayoutCache {
+    /// 消息记录 id 对应的排版选项与排版结果
+    records: HashMap<String, (LayoutOptions, Rc<[MessageSegment]>)>,
+    /// 没有记录 id 的消息下标对应的排版选项与排版结果
+    indexes: HashMap<usize, (LayoutOptions, Rc<[MessageSegment]>)>,
+}
+
+impl LayoutCache {
+    /// 取出消息的排版结果，排版选项变化时重新排版
+    pub fn layout(&mut self, index: usize, message: &ChatMessage, options: LayoutOptions) -> Rc<[MessageSegment]> {
+        let cached = match &message.record_id {
+            Some(record_id) => self.records.get(record_id),
+            None => self.indexes.get(&index),
+        };
+        if let Some((cached_options, segments)) = cached {
+            if *cached_options == options {
+                return segments.clone();
+            }
+        }
+        let segments: Rc<[MessageSegment]> = if options.raw {
+            raw_message(message.raw.as_deref().unwrap_or(&message.message), options.width)
+        } else {
+            layout_message(&message.message, options)
+        }
+        .into();
+        let entry = (options, segments.clone());
+        match &message.record_id {
+            Some(record_id) => self.records.insert(record_id.clone(), entry),
+            None => self.indexes.insert(index, entry),
+        };
+        segments
+    }
+
+    /// 移除下标不小于 `len` 的消息的排版结果
+    pub fn truncate(&mut self, len: usize) {
+        self.indexes.retain(|index, _| *index < len);
+    }
+
+    /// 清空缓存
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.indexes.clear();
+    }
+}
+
+impl WrappedChatMessage {
+    /// 使用已排版的片段构造消息，片段由 [`LayoutCache`] 排版
+    pub fn with_segments(message: ChatMessage, segments: Rc<[MessageSegment]>) -> Self {
+        Self {
+            message,
+            segments,
+            selected: false,
+            icons: false,
+            rating: None,
+            fallback_model: None,
+        }
+    }
+
+    /// 代码行超出可用宽度的最大值，即最大水平滚动偏移量
+    pub fn code_overflow(&self) -> u16 {
+        self.segments
+            .iter()
+            .filter_map(|segment| match segment {
+                MessageSegment::Code {
+                    lines,
+                    width,
+                    line_numbers,
+                    ..
+                } => {
+                    let max_width = lines.iter().map(line_width).max().unwrap_or_default();
+                    let code_width = width.saturating_sub(gutter_width(lines.len(), *line_numbers) + 2);
+                    Some(max_width.saturating_sub(code_width) as u16)
+                }
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// 在消息下方追加思考摘要，未展开时只显示标题与行数
+    pub fn append_reasoning(&mut self, reasoning: &str, expanded: bool, width: usize) {
+        if expanded {
+            self.append_section("reasoning", reasoning, Color::Gray, width);
+        } else {
+            let label = format!("── reasoning, {} lines (z: expand) ──", reasoning.lines().count());
+            self.push_segment(MessageSegment::Lines(vec![Line::styled(label, Color::DarkGray)]));
+        }
+    }
+
+    /// 在消息下方追加译文
+    pub fn append_translation(&mut self, translation: &str, width: usize) {
+        self.append_section("translation", translation, Color::LightBlue, width);
+    }
+
+    /// 在消息末尾追加其他模型的备选回复，以模型名称标注
+    pub fn append_alternative(&mut self, model: &str, message: &str, width: usize) {
+        self.append_section(model, message, Color::LightGreen, width);
+    }
+
+    /// 在消息末尾追加附件的类型与保存路径，图片附件同时显示缩略图
+    pub fn append_attachment(&mut self, attachment: &Attachment, width: usize) {
+        let label = format!("{} {}", attachment.kind.label(), attachment.mime_type);
+        let path = attachment.path.display().to_string();
+        self.append_section(&label, &path, Color::LightMagenta, width);
+        if let Some(pixels) = &attachment.thumbnail {
+            self.push_segment(MessageSegment::Image(pixels.clone()));
+        }
+    }
+
+    /// 在消息末尾追加一段带标签的附加内容
+    fn append_section(&mut self, label: &str, text: &str, color: Color, width: usize) {
+        let mut lines = vec![Line::styled(format!("── {} ──", label), Color::DarkGray)];
+        lines.extend(
+            text.lines()
+                .flat_map(|line| wrap_spans(vec![Span::styled(line.to_string(), color)], width)),
+        );
+        self.push_segment(MessageSegment::Lines(lines));
+    }
+
+    /// 在消息末尾追加片段，片段与排版缓存共享，追加前先复制一份
+    fn push_segment(&mut self, segment: MessageSegment) {
+        let mut segments = self.segments.to_vec();
+        segments.push(segment);
+        self.segments = segments.into();
+    }
+
+    /// 折叠超出最大行数的内容，只保留开头部分作为预览，并在末尾提示被折叠的行数
+    pub fn collapse(&mut self, max_lines: u16) {
+        let height = self.height();
+        if height <= max_lines {
+            return;
+        }
+        let mut remaining = max_lines;
+        let mut segments = Vec::new();
+        for segment in self.segments.iter() {
+            let segment_height = segment.height();
+            if segment_height <= remaining {
+                remaining -= segment_height;
+                segments.push(segment.clone());
+                continue;
+            }
+            // 只有文本行可以部分展示，其他片段被截断时整体折叠
+            if let MessageSegment::Lines(lines) = segment {
+                segments.push(MessageSegment::Lines(lines[..remaining as usize].to_vec()));
+            }
+            break;
+        }
+        let shown: u16 = segments.iter().map(MessageSegment::height).sum();
+        segments.push(MessageSegment::Lines(vec![Line::styled(
+            format!("··· {} more lines, press z to expand ···", height - shown),
+            Color::DarkGray,
+        )]));
+        self.segments = segments.into();
+    }
 
-impl Widget for ChatMessage {
+    /// 每个代码块相对于消息顶部的起始位置，包含消息的上边框
+    pub fn code_block_offsets(&self) -> Vec<u16> {
+        let mut offset = 1;
+        let mut offsets = Vec::new();
+        for segment in self.segments.iter() {
+            if matches!(segment, MessageSegment::Code { .. }) {
+                offsets.push(offset);
+            }
+            offset += segment.height();
+        }
+        offsets
+    }
+
+    /// 消息内容高度
+    pub fn height(&self) -> u16 {
+        self.segments.iter().map(MessageSegment::height).sum()
+    }
+
+    /// 所有片段中最宽片段的宽度
+    fn max_line_width(&self) -> usize {
+        self.segments
+            .iter()
+            .map(MessageSegment::width)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// 在边框中渲染消息内容
+    fn render_content(segments: &[MessageSegment], block: Block, area: Rect, buf: &mut Buffer, style: Style) {
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let heights: Vec<Constraint> = segments.iter().map(|segment| Length(segment.height())).collect();
+        let areas = Layout::vertical(heights).split(inner);
+        for (segment, area) in segments.iter().zip(areas.iter()) {
+            segment.render(*area, buf, style);
+        }
+    }
+}
+
+impl Widget for WrappedChatMessage {
     fn render(self, area: Rect, buf: &mut Buffer)
     where
         Self: Sized,
     {
-        match self.sender {
+        // 拿到所有消息中最长一行的宽度
+        let x = self.max_line_width();
+        let message = self.message;
+        match message.sender {
             User(image_path) => {
-                // 拿到所有消息中最长一行的宽度
-                let x = self
-                    .message
-                    .clone()
-                    .lines()
-                    .map(Into::into)
-                    .map(s_length)
-                    .max()
-                    .unwrap_or_default();
                 // 标题
                 let title = if image_path.is_empty() {
                     "Simple".into()
                 } else {
-                    format!("Image {}", image_path)
+                    Icon::Image.label(self.icons, &image_path)
                 };
                 // 拿到最大宽度
                 let width = max(x, s_length(title.clone())) as u16;
@@ -52,7 +412,7 @@ impl Widget for ChatMessage {
                     .areas(area);
                 let [top, time_area] = Layout::vertical([Fill(1), Length(1)]).areas(right);
                 // 渲染时间
-                let time_paragraph = Paragraph::new(self.date_time.format(" %Y/%m/%d %H:%M:%S ").to_string())
+                let time_paragraph = Paragraph::new(message.date_time.format(" %Y/%m/%d %H:%M:%S ").to_string())
                     .style(Color::Blue)
                     .right_aligned();
                 time_paragraph.render(time_area, buf);
@@ -60,41 +420,28 @@ impl Widget for ChatMessage {
                     .flex(Flex::End)
                     .areas(top);
                 // 渲染头像
-                let avatar_paragraph = Paragraph::new("\n👤").left_aligned();
+                let avatar_paragraph = Paragraph::new(format!("\n{}", Icon::User.glyph(self.icons))).left_aligned();
                 avatar_paragraph.render(avatar_area, buf);
                 // 渲染消息内容
-                let message_block = if self.success {
+                let message_block = if message.success {
                     Block::default()
                         .title(title)
-                        .style(Color::DarkGray)
+                        .style(border_color(self.selected))
                         .borders(Borders::ALL)
                 } else {
                     Block::default().title(title).red().borders(Borders::ALL)
                 };
-                let message_paragraph = Paragraph::new(self.message)
-                    .wrap(Wrap { trim: false })
-                    .style(Color::Cyan)
-                    .block(message_block)
-                    .left_aligned();
-                message_paragraph.render(content_area, buf);
+                Self::render_content(&self.segments, message_block, content_area, buf, Color::Cyan.into());
             }
             Bot => {
-                // 拿到所有消息中最长一行的宽度
-                let width = self
-                    .message
-                    .clone()
-                    .lines()
-                    .map(Into::into)
-                    .map(s_length)
-                    .max()
-                    .unwrap_or_default() as u16;
+                let width = x as u16;
                 // 魔法数 5 为左右边框宽度 1 + 1 加上头像区域宽度 3
                 let [left] = Layout::horizontal([Max(max(width + 5, 21))])
                     .flex(Flex::Start)
                     .areas(area);
                 let [top, time_area] = Layout::vertical([Fill(1), Length(1)]).areas(left);
                 // 渲染时间
-                let time_paragraph = Paragraph::new(self.date_time.format(" %Y/%m/%d %H:%M:%S ").to_string())
+                let time_paragraph = Paragraph::new(message.date_time.format(" %Y/%m/%d %H:%M:%S ").to_string())
                     .style(Color::Blue)
                     .left_aligned();
                 time_paragraph.render(time_area, buf);
@@ -102,22 +449,261 @@ impl Widget for ChatMessage {
                     .flex(Flex::Start)
                     .areas(top);
                 // 渲染头像
-                let avatar_paragraph = Paragraph::new("\n🤖").right_aligned();
+                let avatar_paragraph = Paragraph::new(format!("\n{}", Icon::Bot.glyph(self.icons))).right_aligned();
                 avatar_paragraph.render(avatar_area, buf);
                 // 渲染消息内容
-                let message_block = Block::default().style(Color::DarkGray).borders(Borders::ALL);
-                let message_paragraph = Paragraph::new(self.message)
-                    .wrap(Wrap { trim: false })
-                    .style(Color::Yellow)
-                    .block(message_block)
-                    .left_aligned();
-                message_paragraph.render(content_area, buf);
+                let mut message_block = Block::default()
+                    .style(border_color(self.selected))
+                    .borders(Borders::ALL);
+                // 评价显示在边框标题中
+                match self.rating {
+                    Some(Rating::Good) => message_block = message_block.title(Span::styled("good", Color::Green)),
+                    Some(Rating::Bad(reason)) => {
+                        let title = if reason.is_empty() {
+                            "bad".into()
+                        } else {
+                            format!("bad: {}", truncate(&reason, width.saturating_sub(5) as usize))
+                        };
+                        message_block = message_block.title(Span::styled(title, Color::Red));
+                    }
+                    None => {}
+                }
+                // 备用模型显示在下边框中，不属于回复内容
+                if let Some(model) = self.fallback_model {
+                    let model = truncate(&format!("{} (fallback)", model), width as usize);
+                    message_block = message_block.title_bottom(Line::from(model).dark_gray().right_aligned());
+                }
+                Self::render_content(&self.segments, message_block, content_area, buf, Color::Yellow.into());
+            }
+            Note => {
+                let width = max(x, 4) as u16;
+                // 备注居中显示，不显示头像
+                let [center] = Layout::horizontal([Max(max(width + 2, 21))])
+                    .flex(Flex::Center)
+                    .areas(area);
+                let [content_area, time_area] = Layout::vertical([Fill(1), Length(1)]).areas(center);
+                let time_paragraph = Paragraph::new(message.date_time.format(" %Y/%m/%d %H:%M:%S ").to_string())
+                    .style(Color::DarkGray)
+                    .centered();
+                time_paragraph.render(time_area, buf);
+                let message_block = Block::default()
+                    .title("Note")
+                    .style(border_color(self.selected))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded);
+                let style = Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::DIM | Modifier::ITALIC);
+                Self::render_content(&self.segments, message_block, content_area, buf, style);
             }
             Never => {}
         }
     }
 }
 
+/// 借用排版缓存中的行进行渲染，不复制行中的文本
+fn borrowed_lines<'a>(lines: &'a [Line<'static>]) -> Text<'a> {
+    lines
+        .iter()
+        .map(|line| Line {
+            spans: line
+                .spans
+                .iter()
+                .map(|span| Span::styled(span.content.as_ref(), span.style))
+                .collect(),
+            style: line.style,
+            alignment: line.alignment,
+        })
+        .collect()
+}
+
+/// 消息边框颜色，选中的消息高亮显示
+fn border_color(selected: bool) -> Color {
+    if selected {
+        Color::LightMagenta
+    } else {
+        Color::DarkGray
+    }
+}
+
+/// 不做任何样式处理，仅按宽度换行
+pub fn raw_message(message: &str, width: usize) -> Vec<MessageSegment> {
+    let lines = message
+        .lines()
+        .flat_map(|line| wrap_spans(vec![Span::raw(line.to_string())], width))
+        .collect();
+    vec![MessageSegment::Lines(lines)]
+}
+
+/// 对消息进行样式处理与排版，文本按宽度换行并保留原有样式，表格在宽度足够时以表格形式展示，
+/// 代码块以带语言标题的边框展示，代码行不换行，避免在单词中间断开影响复制
+pub fn layout_message(message: &str, options: LayoutOptions) -> Vec<MessageSegment> {
+    let width = options.width;
+    let lines: Vec<&str> = message.lines().collect();
+    let kinds = classify_lines(message);
+    let blocks = parse_code_blocks(message);
+    let tables = parse_tables(message);
+    let mut segments = Vec::new();
+    let mut current = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if let Some(block) = blocks.iter().find(|block| block.start_line == index) {
+            if !current.is_empty() {
+                segments.push(MessageSegment::Lines(std::mem::take(&mut current)));
+            }
+            let code = (block.start_line + 1..block.end_line)
+                .map(|i| Line::from(style_line(lines[i], kinds[i])))
+                .collect();
+            segments.push(MessageSegment::Code {
+                title: code_block_title(block),
+                lines: code,
+                width,
+                offset: options.horizontal_offset,
+                line_numbers: options.line_numbers,
+            });
+            index = block.end_line + 1;
+            continue;
+        }
+        if let Some(table) = tables.iter().find(|table| table.start_line == index) {
+            let widths = column_widths(table);
+            // 表格过宽时按原始文本展示
+            if table_width(&widths) <= width {
+                if !current.is_empty() {
+                    segments.push(MessageSegment::Lines(std::mem::take(&mut current)));
+                }
+                segments.push(MessageSegment::Table {
+                    header: table.header.clone(),
+                    rows: table.rows.clone(),
+                    widths,
+                });
+                index = table.end_line + 1;
+                continue;
+            }
+        }
+        current.extend(wrap_spans(style_line(lines[index], kinds[index]), width));
+        index += 1;
+    }
+    if !current.is_empty() {
+        segments.push(MessageSegment::Lines(current));
+    }
+    segments
+}
+
+/// 代码块边框标题，如 ` rust src/main.rs `
+fn code_block_title(block: &CodeBlock) -> String {
+    let parts: Vec<&str> = [Some(block.language.as_str()), block.file_name.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" {} ", parts.join(" "))
+    }
+}
+
+/// 行号区域宽度，包含行号后的一个空格
+fn gutter_width(line_count: usize, line_numbers: bool) -> usize {
+    if line_numbers {
+        line_count.max(1).to_string().len() + 1
+    } else {
+        0
+    }
+}
+
+/// 计算表格每一列的宽度
+fn column_widths(table: &MarkdownTable) -> Vec<u16> {
+    (0..table.header.len())
+        .map(|column| {
+            std::iter::once(&table.header)
+                .chain(table.rows.iter())
+                .map(|row| s_length(row[column].clone()))
+                .max()
+                .unwrap_or_default() as u16
+        })
+        .collect()
+}
+
+/// 表格总宽度，包含列间距与左右边框
+fn table_width(widths: &[u16]) -> usize {
+    widths.iter().map(|w| *w as usize).sum::<usize>() + widths.len().saturating_sub(1) + 2
+}
+
+/// 一行文本的宽度
+fn line_width(line: &Line) -> usize {
+    line.spans.iter().map(|span| s_length(span.content.to_string())).sum()
+}
+
+/// 根据行类型设置样式
+fn style_line(line: &str, kind: LineKind) -> Vec<Span<'static>> {
+    let color = match kind {
+        LineKind::Text => return highlight_references(line),
+        LineKind::Code => return vec![Span::raw(line.to_string())],
+        LineKind::Fence => Color::DarkGray,
+        LineKind::DiffAdd => Color::Green,
+        LineKind::DiffRemove => Color::Red,
+        LineKind::DiffHunk => Color::LightCyan,
+    };
+    vec![Span::styled(line.to_string(), Style::default().fg(color))]
+}
+
+/// 将一行文本按宽度拆分为多行
+fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut current = Vec::new();
+    let mut line_width = 0;
+    for span in spans {
+        let mut text = String::new();
+        for c in span.content.chars() {
+            // 如果加上当前字符后超出宽度，则换行
+            if line_width + c_len(c) > width && line_width > 0 {
+                if !text.is_empty() {
+                    current.push(Span::styled(std::mem::take(&mut text), span.style));
+                }
+                lines.push(Line::from(std::mem::take(&mut current)));
+                line_width = 0;
+            }
+            text.push(c);
+            line_width += c_len(c);
+        }
+        if !text.is_empty() {
+            current.push(Span::styled(text, span.style));
+        }
+    }
+    lines.push(Line::from(current));
+    lines
+}
+
+/// 高亮一行文本中的链接与文件行号引用，如 `src/main.rs:12`
+fn highlight_references(line: &str) -> Vec<Span<'static>> {
+    let links = find_links(line);
+    // 链接中可能包含形似文件行号引用的部分，与链接重叠的引用不再高亮
+    let mut ranges: Vec<(Range<usize>, Color)> = find_file_references(line)
+        .into_iter()
+        .filter(|range| {
+            !links
+                .iter()
+                .any(|link| range.start < link.end && link.start < range.end)
+        })
+        .map(|range| (range, Color::LightGreen))
+        .chain(links.iter().cloned().map(|range| (range, Color::LightBlue)))
+        .collect();
+    ranges.sort_by_key(|(range, _)| range.start);
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for (range, color) in ranges {
+        spans.push(Span::raw(line[last..range.start].to_string()));
+        spans.push(Span::styled(
+            line[range.clone()].to_string(),
+            Style::default().fg(color).underlined(),
+        ));
+        last = range.end;
+    }
+    spans.push(Span::raw(line[last..].to_string()));
+    spans
+}
+
 impl Widget for SelectableConversation {
     fn render(self, area: Rect, buf: &mut Buffer)
     where
@@ -128,26 +714,66 @@ impl Widget for SelectableConversation {
         } else {
             Color::White
         };
-        let title = self.conversation.conversation_title;
-        let date_time = self
-            .conversation
-            .conversation_modify_time
-            .format(" %m/%d %H:%M ")
-            .to_string();
-        // 去掉上下两侧边框
-        let [_, title_area, _] = Layout::vertical([Length(1), Fill(1), Length(1)]).areas(area);
-        // 标题区域，两侧加上空格，避免文本被边框覆盖
-        let title_paragraph = Paragraph::new(format!(" {} ", title));
+        let date_time = format!(" {} ", relative_time(self.conversation.conversation_modify_time));
+        // 去掉上下两侧边框，上方为标题，下方为最后一条消息的预览
+        let [_, title_area, preview_area, _] =
+            Layout::vertical([Length(1), Length(1), Length(1), Length(1)]).areas(area);
+        // 标题过长时以省略号截断，为两侧空格、右侧边框、未读、置顶与锁定标记预留宽度
+        let mut reserved = 3;
+        if self.unread {
+            reserved += 2;
+        }
+        if self.conversation.pin_order.is_some() {
+            reserved += "[pinned] ".len();
+        }
+        if self.conversation.locked {
+            reserved += "[locked] ".len();
+        }
+        let title = truncate(
+            &self.conversation.conversation_title,
+            (title_area.width as usize).saturating_sub(reserved),
+        );
+        // 标题区域，两侧加上空格，避免文本被边框覆盖，有未读回复时在标题前显示标记，已锁定时在标题后显示标记
+        let mut title_line = if let Some(editing_title) = self.editing_title {
+            Line::from(Span::styled(format!(" {}", editing_title), Color::LightBlue))
+        } else if self.unread {
+            Line::from(vec![
+                Span::styled(" ● ", Color::LightRed),
+                Span::raw(format!("{} ", title)),
+            ])
+        } else {
+            Line::from(format!(" {} ", title))
+        };
+        if self.conversation.pin_order.is_some() {
+            title_line.push_span(Span::styled("[pinned] ", Color::LightGreen));
+        }
+        if self.conversation.locked {
+            title_line.push_span(Span::styled("[locked] ", Color::Yellow));
+        }
+        let title_paragraph = Paragraph::new(title_line);
         title_paragraph.render(title_area, buf);
-        // 边框
-        let border_block = Block::bordered()
-            .title(
-                Title::from(date_time)
-                    .position(Position::Bottom)
-                    .alignment(Alignment::Right),
-            )
-            .borders(Borders::ALL)
-            .border_style(border_color);
+        // 预览只取最后一条消息的第一行非空内容，并按宽度截断
+        let preview = self
+            .conversation
+            .last_message
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        let preview = truncate(preview, (preview_area.width as usize).saturating_sub(4));
+        Paragraph::new(format!(" {} ", preview))
+            .fg(Color::DarkGray)
+            .render(preview_area, buf);
+        // 边框，左上角显示快速打开的序号
+        let mut border_block = Block::bordered().title(
+            Title::from(date_time)
+                .position(Position::Bottom)
+                .alignment(Alignment::Right),
+        );
+        if let Some(index_hint) = self.index_hint {
+            border_block = border_block.title(Title::from(Span::styled(index_hint.to_string(), Color::DarkGray)));
+        }
+        let border_block = border_block.borders(Borders::ALL).border_style(border_color);
         border_block.render(area, buf);
     }
 }
@@ -190,3 +816,125 @@ impl Widget for DeletePopup {
         border_block.render(area, buf);
     }
 }
+
+impl Widget for ProgressBar {
+    fn render(self, area: Rect, buf: &mut Buffer)
+    where
+        Self: Sized,
+    {
+        let label = format!("{} ", self.label);
+        let count = format!(" {}/{}", self.done, self.total);
+        let bar_width = (area.width as usize).saturating_sub(s_length(label.clone()) + count.len());
+        let mut spans = vec![Span::raw(label)];
+        // 宽度不足时省略进度条，只显示名称与计数
+        if bar_width >= 4 {
+            let filled = (bar_width as f64 * self.ratio()).round() as usize;
+            spans.push(Span::raw("━".repeat(filled)));
+            spans.push(Span::styled("─".repeat(bar_width - filled), Color::DarkGray));
+        }
+        spans.push(Span::raw(count));
+        Line::from(spans).style(self.color).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 换行后每一行的文本
+    fn wrap(text: &str, width: usize) -> Vec<String> {
+        wrap_spans(vec![Span::raw(text.to_string())], width)
+            .iter()
+            .map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn wrap_ascii() {
+        assert_eq!(wrap("hello world", 5), ["hello", " worl", "d"]);
+        assert_eq!(wrap("short", 10), ["short"]);
+    }
+
+    #[test]
+    fn wrap_wide_chars() {
+        // 宽字符放不下时整体移到下一行
+        assert_eq!(wrap("你好世界", 5), ["你好", "世界"]);
+        assert_eq!(wrap("a你好", 4), ["a你", "好"]);
+        assert_eq!(wrap("😀😀😀", 4), ["😀😀", "😀"]);
+        assert_eq!(wrap("Hi 你好 😀!", 6), ["Hi 你", "好 😀!"]);
+    }
+
+    #[test]
+    fn wrap_combining_marks() {
+        // 组合字符不占宽度，与前一个字符留在同一行
+        assert_eq!(wrap("cafe\u{301}s", 4), ["cafe\u{301}", "s"]);
+    }
+
+    #[test]
+    fn wrapped_lines_fit_width() {
+        for line in wrap_spans(vec![Span::raw("混合 mixed 文本 with emoji 😀 and é")], 7) {
+            assert!(line_width(&line) <= 7);
+        }
+    }
+
+    #[test]
+    fn raw_view_shows_unmodified_reply() {
+        let message = ChatMessage {
+            success: true,
+            sender: Bot,
+            message: "First.\nSecond.".into(),
+            date_time: chrono::Local::now(),
+            record_id: None,
+            attachments: Vec::new(),
+            raw: Some("First.\n\nSecond.\n".into()),
+        };
+        let options = LayoutOptions {
+            width: 20,
+            raw: true,
+            ..Default::default()
+        };
+        let segments = LayoutCache::default().layout(0, &message, options);
+        assert!(matches!(&segments[0], MessageSegment::Lines(lines) if lines.len() == 3));
+    }
+
+    const TABLE: &str = "intro\n| name | 说明 |\n| --- | --- |\n| a | 中文字 | extra |\n| b |\nafter";
+
+    #[test]
+    fn column_widths_of_ragged_table() {
+        let table = &parse_tables(TABLE)[0];
+        // 宽字符占两列，多余的单元格被丢弃，缺少的单元格为空
+        assert_eq!(column_widths(table), [4, 6]);
+        assert_eq!(table_width(&column_widths(table)), 13);
+    }
+
+    #[test]
+    fn table_layout_depends_on_width() {
+        let options = |width| LayoutOptions {
+            width,
+            ..Default::default()
+        };
+        let segments = layout_message(TABLE, options(13));
+        assert_eq!(segments.len(), 3);
+        assert!(
+            matches!(&segments[1], MessageSegment::Table { rows, widths, .. } if rows.len() == 2 && widths == &[4, 6])
+        );
+        // 表格过宽时按原始文本换行展示
+        let segments = layout_message(TABLE, options(12));
+        assert_eq!(segments.len(), 1);
+        assert!(matches!(&segments[0], MessageSegment::Lines(_)));
+    }
+
+    #[test]
+    fn table_in_code_block_stays_code() {
+        let message = "```markdown\n| a | b |\n| - | - |\n| 1 | 2 |\n```";
+        let segments = layout_message(
+            message,
+            LayoutOptions {
+                width: 40,
+                ..Default::default()
+            },
+        );
+        assert_eq!(segments.len(), 1);
+        assert!(matches!(&segments[0], MessageSegment::Code { lines, .. } if lines.len() == 3));
+    }
+}
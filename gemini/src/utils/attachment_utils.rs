@@ -0,0 +1,158 @@
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+
+use crate::model::view::{Attachment, AttachmentKind};
+
+use super::{db_utils::generate_unique_id, image_utils::thumbnail_pixels};
+
+/// 图片缩略图的最大宽度（字符数）
+const THUMBNAIL_WIDTH: u32 = 32;
+/// 图片缩略图的最大高度（像素数，每个字符显示两个像素）
+const THUMBNAIL_HEIGHT: u32 = 32;
+
+/// 从接口响应中取出所有非文本内容的类型与 Base64 数据，
+/// 包括 generateContent 回复中的 `inlineData` 与 Imagen `predict` 接口返回的图片
+pub fn response_inline_data(value: &Value) -> Vec<(String, String)> {
+    let predictions = value["predictions"].as_array().into_iter().flatten().map(|prediction| {
+        (
+            prediction["mimeType"].as_str().unwrap_or("image/png").to_string(),
+            prediction["bytesBase64Encoded"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        )
+    });
+    let parts = value["candidates"][0]["content"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|part| {
+            let data = &part["inlineData"];
+            Some((
+                data["mimeType"]
+                    .as_str()
+                    .unwrap_or("application/octet-stream")
+                    .to_string(),
+                data["data"].as_str()?.to_string(),
+            ))
+        });
+    predictions.chain(parts).filter(|(_, data)| !data.is_empty()).collect()
+}
+
+/// 保存接口响应中的所有非文本内容
+pub fn save_response_attachments(value: &Value) -> Result<Vec<Attachment>> {
+    response_inline_data(value)
+        .into_iter()
+        .map(|(mime_type, data)| save_attachment(&mime_type, &data))
+        .collect()
+}
+
+/// 将 Base64 数据保存到程序所在目录的 data 文件夹中，图片附件同时生成缩略图，
+/// 原始 PCM 音频（如 `audio/L16;codec=pcm;rate=24000`）加上 WAV 文件头后保存，以便直接播放
+pub fn save_attachment(mime_type: &str, data: &str) -> Result<Attachment> {
+    let kind = AttachmentKind::from_mime(mime_type);
+    let mut bytes = STANDARD.decode(data)?;
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    let extension = match essence.split_once('/') {
+        Some(("audio", "L16" | "pcm")) => {
+            bytes = pcm_to_wav(&bytes, pcm_rate(mime_type));
+            "wav"
+        }
+        Some((_, "mpeg")) => "mp3",
+        Some((_, "x-wav")) => "wav",
+        Some((_, subtype)) if !subtype.is_empty() && subtype.chars().all(|c| c.is_ascii_alphanumeric()) => subtype,
+        _ => "bin",
+    };
+    let exe_path = env::current_exe()?;
+    let dir = exe_path.parent().context("无法获取程序所在目录")?.join("data");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("generated_{}.{}", generate_unique_id(), extension));
+    fs::write(&path, bytes)?;
+    let thumbnail = match kind {
+        AttachmentKind::Image => thumbnail_pixels(&path, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT).ok(),
+        _ => None,
+    };
+    Ok(Attachment {
+        kind,
+        mime_type: mime_type.to_string(),
+        path,
+        thumbnail,
+    })
+}
+
+/// 从 MIME 类型的 `rate` 参数中取出采样率，缺省时使用 Gemini 语音输出的 24000 Hz
+fn pcm_rate(mime_type: &str) -> u32 {
+    mime_type
+        .split(';')
+        .filter_map(|param| param.trim().strip_prefix("rate="))
+        .find_map(|rate| rate.parse().ok())
+        .unwrap_or(24000)
+}
+
+/// 为单声道 16 位小端 PCM 数据加上 WAV 文件头
+fn pcm_to_wav(pcm: &[u8], rate: u32) -> Vec<u8> {
+    let len = pcm.len() as u32;
+    let mut wav = Vec::with_capacity(pcm.len() + 44);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // PCM 格式，单声道
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&rate.to_le_bytes());
+    // 每秒字节数与每个采样的字节数
+    wav.extend_from_slice(&(rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&len.to_le_bytes());
+    wav.extend_from_slice(pcm);
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn inline_data_from_both_apis() {
+        let value = json!({
+            "predictions": [{ "mimeType": "image/png", "bytesBase64Encoded": "AA==" }],
+            "candidates": [{ "content": { "parts": [
+                { "text": "hello" },
+                { "inlineData": { "mimeType": "audio/L16;codec=pcm;rate=16000", "data": "AAA=" } },
+            ] } }],
+        });
+        assert_eq!(
+            response_inline_data(&value),
+            vec![
+                ("image/png".to_string(), "AA==".to_string()),
+                ("audio/L16;codec=pcm;rate=16000".to_string(), "AAA=".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pcm_gets_wav_header() {
+        assert_eq!(pcm_rate("audio/L16;codec=pcm;rate=16000"), 16000);
+        assert_eq!(pcm_rate("audio/L16"), 24000);
+        let wav = pcm_to_wav(&[1, 2, 3, 4], 16000);
+        assert_eq!(wav.len(), 48);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 16000);
+        assert_eq!(&wav[44..], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn attachment_kind_from_mime() {
+        assert_eq!(AttachmentKind::from_mime("image/png"), AttachmentKind::Image);
+        assert_eq!(AttachmentKind::from_mime("audio/wav"), AttachmentKind::Audio);
+        assert_eq!(AttachmentKind::from_mime("application/pdf"), AttachmentKind::File);
+    }
+}
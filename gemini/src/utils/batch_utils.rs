@@ -0,0 +1,146 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+
+use super::{
+    http_utils::{post_json, send_json, CLIENT},
+    model_utils::{model_resource, request_body, BASE_URL},
+    store_utils::StoreData,
+};
+
+/// 批量任务的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchState {
+    /// 排队或执行中，包含接口返回的状态名称
+    Running(String),
+    /// 执行成功
+    Succeeded,
+    /// 执行失败、取消或过期，包含接口返回的状态名称
+    Failed(String),
+}
+
+/// 读取 JSONL 文件，每一行为一个提示词
+///
+/// 每一行可以是 JSON 字符串，或包含 `prompt` 字段（文本提示词）或 `request` 字段（完整的
+/// generateContent 请求体）的对象，对象中的 `key` 字段用于在结果中标识该请求，缺省时使用行号
+pub fn read_batch_file<P: AsRef<Path>>(path: P, store_data: &StoreData) -> Result<Vec<Value>> {
+    let content = fs::read_to_string(path)?;
+    let mut requests = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(line).with_context(|| format!("第 {} 行不是有效的 JSON", i + 1))?;
+        let (key, request) = match &value {
+            Value::String(prompt) => ((i + 1).to_string(), prompt_request(prompt, store_data)?),
+            Value::Object(object) => {
+                let key = match object.get("key") {
+                    Some(Value::String(key)) => key.clone(),
+                    Some(key) => key.to_string(),
+                    None => (i + 1).to_string(),
+                };
+                let request = match (object.get("request"), object.get("prompt").and_then(Value::as_str)) {
+                    (Some(request), _) => request.clone(),
+                    (None, Some(prompt)) => prompt_request(prompt, store_data)?,
+                    (None, None) => bail!("第 {} 行缺少 prompt 或 request 字段", i + 1),
+                };
+                (key, request)
+            }
+            _ => bail!("第 {} 行不是字符串或对象", i + 1),
+        };
+        requests.push(json!({ "request": request, "metadata": { "key": key } }));
+    }
+    if requests.is_empty() {
+        bail!("批量请求文件为空")
+    }
+    Ok(requests)
+}
+
+/// 使用配置中的生成参数与系统指令构造请求体
+fn prompt_request(prompt: &str, store_data: &StoreData) -> Result<Value> {
+    request_body(
+        json!({ "contents": [{ "role": "user", "parts": [{ "text": prompt }] }] }),
+        &store_data.options,
+        store_data.system_instruction.as_deref(),
+    )
+}
+
+/// 提交批量任务，返回任务名称（batches/xxx）
+pub fn submit_batch(key: &str, model: &str, display_name: &str, requests: Vec<Value>) -> Result<String> {
+    let body = json!({
+        "batch": {
+            "display_name": display_name,
+            "input_config": { "requests": { "requests": requests } },
+        },
+    });
+    let url = format!(
+        "{}/v1beta/{}:batchGenerateContent?key={}",
+        BASE_URL,
+        model_resource(model),
+        key
+    );
+    let value = post_json(url, &body, "提交批量任务")?;
+    value["name"]
+        .as_str()
+        .map(String::from)
+        .context("提交批量任务失败：响应中没有任务名称")
+}
+
+/// 查询批量任务，返回任务状态与完整的响应
+pub fn batch_status(key: &str, name: &str) -> Result<(BatchState, Value)> {
+    let url = format!("{}/v1beta/{}?key={}", BASE_URL, name, key);
+    let value = send_json(CLIENT.get(url), "查询批量任务")?;
+    let state = value["metadata"]["state"]
+        .as_str()
+        .unwrap_or("BATCH_STATE_PENDING")
+        .to_string();
+    let state = match state.as_str() {
+        "BATCH_STATE_SUCCEEDED" | "JOB_STATE_SUCCEEDED" => BatchState::Succeeded,
+        "BATCH_STATE_FAILED" | "BATCH_STATE_CANCELLED" | "BATCH_STATE_EXPIRED" => BatchState::Failed(state),
+        _ => BatchState::Running(state),
+    };
+    Ok((state, value))
+}
+
+/// 获取已完成的批量任务的结果，每个结果为一行 JSON，包含 `key` 与 `response` 或 `error` 字段
+pub fn batch_results(key: &str, operation: &Value) -> Result<Vec<Value>> {
+    let output = &operation["response"];
+    let responses: Vec<Value> = if let Some(inlined) = output["inlinedResponses"]["inlinedResponses"].as_array() {
+        inlined.clone()
+    } else if let Some(file) = output["responsesFile"].as_str() {
+        // 结果较多时保存在文件中，需要单独下载
+        let url = format!("{}/download/v1beta/{}:download?alt=media&key={}", BASE_URL, file, key);
+        let response = CLIENT.get(url).send()?;
+        let status = response.status();
+        let text = response.text()?;
+        if !status.is_success() {
+            bail!("下载批量任务结果失败：{} {}", status, text)
+        }
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?
+    } else {
+        bail!("批量任务没有返回结果")
+    };
+    Ok(responses.iter().map(result_line).collect())
+}
+
+/// 将单个请求的响应转换为结果行
+fn result_line(response: &Value) -> Value {
+    let key = response["metadata"]["key"]
+        .as_str()
+        .or(response["key"].as_str())
+        .unwrap_or_default();
+    if let Some(error) = response.get("error") {
+        return json!({ "key": key, "error": error["message"].as_str().unwrap_or("unknown error") });
+    }
+    let text: String = response["response"]["candidates"][0]["content"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part["text"].as_str())
+        .collect();
+    json!({ "key": key, "response": text })
+}
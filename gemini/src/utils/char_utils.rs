@@ -1,23 +1,10 @@
 #![allow(unused)]
 
-/// 计算字符宽度
-///
-/// # Examples
-/// ```
-/// assert_eq!(c_len('a'), 1);
-/// assert_eq!(c_len('中'), 2);
-/// assert_eq!(c_len('\n'), 0);
-/// assert_eq!(c_len('\0'), 0);
-/// ```
+use unicode_width::UnicodeWidthChar;
+
+/// 计算字符在终端中的显示宽度，控制字符与组合字符宽度为 0
 pub(crate) fn c_len(c: char) -> usize {
-    let width_0 = ['\n', '\0'];
-    if width_0.contains(&c) {
-        0
-    } else if c.is_ascii() {
-        1
-    } else {
-        2
-    }
+    c.width().unwrap_or(0)
 }
 
 /// 判断是否为中文字符
@@ -71,14 +58,88 @@ fn is_chinese_punctuation(c: char) -> bool {
 }
 
 /// 获取输入框字符长度
-///
-/// # Examples
-/// ```
-/// let s = "你好，世界！";
-/// assert_eq!(s_length(s), 12);
-/// let s = "Hello, World!"
-/// assert_eq!(s_length(s), 13);
-/// ```
 pub(crate) fn s_length(str: String) -> usize {
     str.chars().map(c_len).sum()
 }
+
+/// 按显示宽度截断字符串，超出部分以省略号代替
+pub(crate) fn truncate(str: &str, width: usize) -> String {
+    if s_length(str.to_string()) <= width {
+        return str.to_string();
+    }
+    let mut result = String::new();
+    let mut length = 0;
+    for c in str.chars() {
+        // 预留省略号的宽度
+        if length + c_len(c) + 1 > width {
+            break;
+        }
+        length += c_len(c);
+        result.push(c);
+    }
+    result.push('…');
+    result
+}
+
+/// 模糊匹配，`pattern` 中的字符按顺序出现在 `text` 中即为匹配，忽略大小写，
+/// 返回匹配得分，连续匹配与从开头匹配的得分更高，不匹配时返回 None
+pub(crate) fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut last_index: Option<usize> = None;
+    for p in pattern.chars().flat_map(char::to_lowercase) {
+        let (index, _) = chars.find(|(_, c)| *c == p)?;
+        score += match last_index {
+            Some(last) if last + 1 == index => 5,
+            None if index == 0 => 5,
+            _ => 1,
+        };
+        last_index = Some(index);
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_width() {
+        assert_eq!(c_len('a'), 1);
+        assert_eq!(c_len('中'), 2);
+        assert_eq!(c_len('，'), 2);
+        assert_eq!(c_len('😀'), 2);
+        assert_eq!(c_len('é'), 1);
+        assert_eq!(c_len('\u{301}'), 0);
+        assert_eq!(c_len('\n'), 0);
+        assert_eq!(c_len('\0'), 0);
+    }
+
+    #[test]
+    fn string_width() {
+        assert_eq!(s_length("Hello, World!".into()), 13);
+        assert_eq!(s_length("你好，世界！".into()), 12);
+        assert_eq!(s_length("Hi 你好 😀".into()), 10);
+        // e 与组合重音符号只占一列
+        assert_eq!(s_length("cafe\u{301}".into()), 4);
+        assert_eq!(s_length(String::new()), 0);
+    }
+
+    #[test]
+    fn truncate_by_width() {
+        assert_eq!(truncate("Hi", 8), "Hi");
+        assert_eq!(truncate("Hello, World!", 8), "Hello, …");
+        assert_eq!(truncate("你好，世界", 5), "你好…");
+        // 宽字符放不下时不会被截成半个
+        assert_eq!(truncate("a你好", 4), "a你…");
+        assert_eq!(truncate("😀😀😀", 4), "😀…");
+    }
+
+    #[test]
+    fn fuzzy_match() {
+        assert!(fuzzy_score("rst", "Rust tips").is_some());
+        assert!(fuzzy_score("tsr", "Rust tips").is_none());
+        assert!(fuzzy_score("rus", "Rust tips") > fuzzy_score("rts", "Rust tips"));
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+}
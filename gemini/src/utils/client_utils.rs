@@ -0,0 +1,90 @@
+use gemini_api::{
+    body::{request::GenerationConfig, Content, Part, Role},
+    model::blocking::Gemini,
+    param::LanguageModel,
+};
+
+use crate::model::view::{
+    ChatMessage,
+    Sender::{Bot, Never, Note, User},
+};
+
+use super::store_utils::StoreData;
+
+/// Gemini 客户端的构造器，集中处理 `Gemini::rebuild` 与系统指令的组合，
+/// 修改模型或上下文时只复制需要的字段，不必复制整个客户端
+#[derive(Default)]
+pub struct GeminiBuilder {
+    key: String,
+    model: LanguageModel,
+    contents: Vec<Content>,
+    options: GenerationConfig,
+    system_instruction: String,
+}
+
+impl GeminiBuilder {
+    /// 使用配置文件中的密钥、模型、生成参数与系统指令，上下文为空
+    pub fn from_config(store_data: &StoreData) -> Self {
+        Self {
+            key: store_data.key.clone(),
+            model: store_data.model.clone(),
+            options: store_data.options.clone(),
+            system_instruction: store_data.system_instruction.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// 沿用已有客户端的密钥、模型、生成参数与系统指令，不复制上下文
+    pub fn from_client(gemini: &Gemini) -> Self {
+        Self {
+            key: gemini.key.clone(),
+            model: gemini.model.clone(),
+            options: gemini.options.clone(),
+            system_instruction: gemini.system_instruction.clone().unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// 设置模型
+    pub fn model(mut self, model: LanguageModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// 设置起始上下文
+    pub fn contents(mut self, contents: Vec<Content>) -> Self {
+        self.contents = contents;
+        self
+    }
+
+    /// 设置系统指令
+    pub fn system_instruction(mut self, system_instruction: String) -> Self {
+        self.system_instruction = system_instruction;
+        self
+    }
+
+    /// 构造客户端
+    pub fn build(self) -> Gemini {
+        let mut gemini = Gemini::rebuild(self.key, self.model, self.contents, self.options);
+        gemini.set_system_instruction(self.system_instruction);
+        gemini
+    }
+}
+
+/// 将聊天记录转换为客户端的上下文，只包含文本，备注只在本地显示，不加入上下文
+pub fn history_contents(messages: &[ChatMessage]) -> Vec<Content> {
+    messages
+        .iter()
+        .filter_map(|message| {
+            let role = match message.sender {
+                User(_) => Role::User,
+                Bot => Role::Model,
+                Note | Never => return None,
+            };
+            Some(Content {
+                parts: vec![Part::Text(message.message.clone())],
+                role: Some(role),
+            })
+        })
+        .collect()
+}
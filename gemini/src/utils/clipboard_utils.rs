@@ -0,0 +1,231 @@
+use std::{
+    env,
+    io::{self, Write},
+    process::{Command, Stdio},
+    sync::{LazyLock, Mutex},
+};
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use super::store_utils::read_config;
+
+/// 剪贴板配置
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ClipboardConfig {
+    /// 剪贴板后端，command/arboard/osc52，未配置时在 SSH 会话中使用 osc52，否则使用 command
+    #[serde(default)]
+    pub backend: Option<String>,
+    /// command 后端写入剪贴板的命令，文本通过标准输入传入，未配置时按顺序尝试常用命令
+    #[serde(default)]
+    pub copy_command: Option<String>,
+    /// command 后端读取剪贴板的命令，从标准输出读取文本，未配置时按顺序尝试常用命令
+    #[serde(default)]
+    pub paste_command: Option<String>,
+    /// 复制时总是同时发送 OSC 52 控制序列，远程会话中本地剪贴板也能收到复制的文本
+    #[serde(default)]
+    pub osc52: bool,
+}
+
+/// 剪贴板后端
+trait Clipboard {
+    /// 读取剪贴板文本
+    fn read(&self) -> Result<String>;
+
+    /// 将文本写入剪贴板
+    fn write(&self, text: &str) -> Result<()>;
+}
+
+/// 通过外部命令访问剪贴板
+struct CommandClipboard {
+    /// 写入剪贴板的命令
+    copy_command: Option<String>,
+    /// 读取剪贴板的命令
+    paste_command: Option<String>,
+}
+
+/// 通过 arboard 直接访问系统剪贴板
+struct ArboardClipboard;
+
+/// 通过 OSC 52 控制序列让终端写入剪贴板，适用于 SSH 与 tmux 会话，大部分终端不支持读取
+struct Osc52Clipboard;
+
+/// 读取剪贴板的命令，按顺序尝试
+fn paste_commands() -> Vec<(&'static str, Vec<&'static str>)> {
+    if cfg!(windows) {
+        vec![("powershell", vec!["-NoProfile", "-Command", "Get-Clipboard -Raw"])]
+    } else if cfg!(target_os = "macos") {
+        vec![("pbpaste", vec![])]
+    } else {
+        vec![
+            ("wl-paste", vec!["--no-newline"]),
+            ("xclip", vec!["-selection", "clipboard", "-o"]),
+            ("xsel", vec!["--clipboard", "--output"]),
+        ]
+    }
+}
+
+/// 写入剪贴板的命令，按顺序尝试
+fn copy_commands() -> Vec<(&'static str, Vec<&'static str>)> {
+    if cfg!(windows) {
+        vec![("clip", vec![])]
+    } else if cfg!(target_os = "macos") {
+        vec![("pbcopy", vec![])]
+    } else {
+        vec![
+            ("wl-copy", vec![]),
+            ("xclip", vec!["-selection", "clipboard"]),
+            ("xsel", vec!["--clipboard", "--input"]),
+        ]
+    }
+}
+
+/// 通过 shell 执行配置的命令
+fn shell_command(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", command_line]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", command_line]);
+        command
+    }
+}
+
+/// 执行写入剪贴板的命令，返回命令是否执行成功
+fn run_copy_command(mut command: Command, text: &str) -> Result<bool> {
+    let Ok(mut child) = command.stdin(Stdio::piped()).spawn() else {
+        return Ok(false);
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    Ok(child.wait()?.success())
+}
+
+impl Clipboard for CommandClipboard {
+    fn read(&self) -> Result<String> {
+        if let Some(command_line) = self.paste_command.as_deref() {
+            let output = shell_command(command_line).output()?;
+            if !output.status.success() {
+                bail!("读取剪贴板的命令执行失败：{}", output.status)
+            }
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+        for (program, args) in paste_commands() {
+            if let Ok(output) = Command::new(program).args(args).output() {
+                if output.status.success() {
+                    return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+                }
+            }
+        }
+        bail!("无法读取剪贴板，请安装 wl-clipboard、xclip 或 xsel")
+    }
+
+    fn write(&self, text: &str) -> Result<()> {
+        if let Some(command_line) = self.copy_command.as_deref() {
+            if !run_copy_command(shell_command(command_line), text)? {
+                bail!("写入剪贴板的命令执行失败：{}", command_line)
+            }
+            return Ok(());
+        }
+        for (program, args) in copy_commands() {
+            let mut command = Command::new(program);
+            command.args(args);
+            if run_copy_command(command, text)? {
+                return Ok(());
+            }
+        }
+        bail!("无法写入剪贴板，请安装 wl-clipboard、xclip 或 xsel")
+    }
+}
+
+/// arboard 剪贴板实例，Linux 下剪贴板内容由持有实例的进程提供，因此在程序运行期间一直保留
+static ARBOARD: LazyLock<Mutex<Option<arboard::Clipboard>>> = LazyLock::new(|| Mutex::new(None));
+
+impl ArboardClipboard {
+    /// 使用 arboard 剪贴板实例，第一次使用时创建
+    fn with<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T> {
+        let mut clipboard = ARBOARD.lock().unwrap();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().context("无法访问系统剪贴板")?);
+        }
+        Ok(f(clipboard.as_mut().unwrap())?)
+    }
+}
+
+impl Clipboard for ArboardClipboard {
+    fn read(&self) -> Result<String> {
+        Self::with(|clipboard| clipboard.get_text())
+    }
+
+    fn write(&self, text: &str) -> Result<()> {
+        Self::with(|clipboard| clipboard.set_text(text))
+    }
+}
+
+impl Clipboard for Osc52Clipboard {
+    fn read(&self) -> Result<String> {
+        bail!("osc52 剪贴板不支持读取，请使用终端的粘贴功能")
+    }
+
+    fn write(&self, text: &str) -> Result<()> {
+        let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+        // tmux 与 screen 中需要使用 DCS 透传控制序列，tmux 中序列里的 ESC 需要转义
+        let sequence = if env::var_os("TMUX").is_some() {
+            format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+        } else if env::var("TERM").is_ok_and(|term| term.starts_with("screen")) {
+            format!("\x1bP{}\x1b\\", sequence)
+        } else {
+            sequence
+        };
+        let mut stdout = io::stdout();
+        stdout.write_all(sequence.as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// 读取剪贴板配置
+fn clipboard_config() -> ClipboardConfig {
+    read_config().map(|store_data| store_data.clipboard).unwrap_or_default()
+}
+
+/// 根据配置选择剪贴板后端
+fn clipboard(config: ClipboardConfig) -> Result<Box<dyn Clipboard>> {
+    let backend = config.backend.unwrap_or_else(|| {
+        if env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some() {
+            "osc52".into()
+        } else {
+            "command".into()
+        }
+    });
+    let clipboard: Box<dyn Clipboard> = match backend.as_str() {
+        "command" => Box::new(CommandClipboard {
+            copy_command: config.copy_command.filter(|command| !command.trim().is_empty()),
+            paste_command: config.paste_command.filter(|command| !command.trim().is_empty()),
+        }),
+        "arboard" => Box::new(ArboardClipboard),
+        "osc52" => Box::new(Osc52Clipboard),
+        _ => bail!("不支持的剪贴板后端 {}，可选值为 command、arboard、osc52", backend),
+    };
+    Ok(clipboard)
+}
+
+/// 读取剪贴板文本
+pub fn read_clipboard() -> Result<String> {
+    clipboard(clipboard_config())?.read()
+}
+
+/// 将文本写入剪贴板，开启 `osc52` 时同时通过终端复制，此时配置的后端写入失败也视为成功
+pub fn write_clipboard(text: &str) -> Result<()> {
+    let config = clipboard_config();
+    let osc52 = config.osc52;
+    let result = clipboard(config).and_then(|clipboard| clipboard.write(text));
+    if osc52 {
+        return Osc52Clipboard.write(text);
+    }
+    result
+}
@@ -1,54 +1,138 @@
 use std::{
     borrow::{Borrow, BorrowMut},
-    env,
+    env, fs,
+    path::PathBuf,
     sync::{LazyLock, Mutex},
 };
 
+use chrono::{Local, NaiveDate};
 use nanoid::nanoid;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use rusqlite::Connection;
 
 use crate::model::{
-    db::{Conversation, ImageRecord, MessageRecord},
-    view::{ChatMessage, Sender},
+    db::{Conversation, ImageRecord, MessageRecord, Schedule, Usage},
+    view::{ChatMessage, Rating, Sender},
 };
 
-use super::image_utils::{cache_image, delete_image_cache};
+use super::image_utils::{cache_image_by_hash, delete_image_cache};
 
 /// 数据库连接
-static DB_CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| {
+static DB_CONNECTION: LazyLock<Mutex<Connection>> = LazyLock::new(|| Mutex::new(open_connection().unwrap()));
+
+/// 打开数据库文件
+#[cfg(not(test))]
+fn open_connection() -> Result<Connection> {
+    Ok(Connection::open(db_path())?)
+}
+
+/// 测试使用已创建表结构的内存数据库，不读写程序目录下的数据库文件
+#[cfg(test)]
+fn open_connection() -> Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    for sql_file in MIGRATIONS {
+        conn.execute_batch(sql_file)?;
+    }
+    Ok(conn)
+}
+
+/// 数据库文件路径
+fn db_path() -> PathBuf {
     let exe_path = env::current_exe().unwrap();
-    let db_path = exe_path.parent().unwrap().join("gemini.db");
-    Mutex::new(Connection::open(db_path).unwrap())
-});
+    exe_path.parent().unwrap().join("gemini.db")
+}
+
+/// 最近一次备份的数据库文件路径
+fn backup_path() -> PathBuf {
+    db_path().with_extension("db.bak")
+}
 
 /// 当前数据库版本
 pub fn current_db_version() -> String {
-    "20241025_add_index".into()
+    "20241210_add_raw".into()
 }
 
+/// 按顺序执行的建表与迁移脚本
+const MIGRATIONS: [&str; 9] = [
+    include_str!("../../migrations/20240929_create.sql"),
+    include_str!("../../migrations/20241025_add_index.sql"),
+    include_str!("../../migrations/20241101_add_schedule.sql"),
+    include_str!("../../migrations/20241110_add_usage.sql"),
+    include_str!("../../migrations/20241115_dedup_image.sql"),
+    include_str!("../../migrations/20241120_add_rating.sql"),
+    include_str!("../../migrations/20241201_add_lock.sql"),
+    include_str!("../../migrations/20241205_add_pin.sql"),
+    include_str!("../../migrations/20241210_add_raw.sql"),
+];
+
 /// 创建表结构
 pub fn update_db_structure() -> Result<()> {
-    let sql_files = vec![
-        include_str!("../../migrations/20240929_create.sql"),
-        include_str!("../../migrations/20241025_add_index.sql"),
-    ];
     let mut binding = DB_CONNECTION.lock().unwrap();
     let conn = binding.borrow_mut();
-    for sql_file in sql_files {
+    for sql_file in MIGRATIONS {
         conn.execute_batch(sql_file)?;
     }
     Ok(())
 }
 
+/// 检查数据库完整性，返回发现的问题，数据库无法读取时返回错误
+pub fn check_integrity() -> Result<Vec<String>> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let problems: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+    Ok(problems.into_iter().filter(|problem| problem != "ok").collect())
+}
+
+/// 是否存在数据库备份
+pub fn has_backup() -> bool {
+    backup_path().exists()
+}
+
+/// 备份数据库，覆盖之前的备份，只应在完整性检查通过后调用
+pub fn backup_db() -> Result<()> {
+    let backup_path = backup_path();
+    // VACUUM INTO 要求目标文件不存在，先写入临时文件再替换，避免备份失败时丢失之前的备份
+    let temp_path = backup_path.with_extension("bak.tmp");
+    let _ = fs::remove_file(&temp_path);
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    conn.execute("VACUUM INTO ?1", [temp_path.to_string_lossy()])?;
+    fs::rename(temp_path, backup_path)?;
+    Ok(())
+}
+
+/// 使用最近一次的备份替换数据库文件，损坏的数据库文件保留为 `gemini.db.damaged`
+pub fn restore_backup() -> Result<()> {
+    let backup_path = backup_path();
+    if !backup_path.exists() {
+        bail!("没有可以恢复的数据库备份")
+    }
+    let db_path = db_path();
+    let mut conn = DB_CONNECTION.lock().unwrap();
+    // 先关闭数据库文件再替换
+    *conn = Connection::open_in_memory()?;
+    fs::rename(&db_path, db_path.with_extension("db.damaged"))?;
+    fs::copy(&backup_path, &db_path)?;
+    *conn = Connection::open(db_path)?;
+    Ok(())
+}
+
 /// 查询所有会话
 pub fn query_all() -> Result<Vec<Conversation>> {
     let binding = DB_CONNECTION.lock().unwrap();
     let conn = binding.borrow();
     let mut stmt = conn.prepare(
-        r#"SELECT conversation_id, conversation_title, conversation_start_time, conversation_modify_time
-        FROM gemini_conversation ORDER BY conversation_modify_time DESC"#,
+        r#"SELECT conversation_id, conversation_title, conversation_start_time, conversation_modify_time,
+        (SELECT record_content FROM gemini_message_record
+        WHERE gemini_message_record.conversation_id = gemini_conversation.conversation_id
+        ORDER BY sort_index DESC LIMIT 1),
+        EXISTS (SELECT 1 FROM gemini_conversation_lock
+        WHERE gemini_conversation_lock.conversation_id = gemini_conversation.conversation_id),
+        pin_order
+        FROM gemini_conversation LEFT JOIN gemini_conversation_pin USING (conversation_id)
+        ORDER BY pin_order IS NULL, pin_order, conversation_modify_time DESC"#,
     )?;
     let mut rows = stmt.query_map([], |row| {
         Ok(Conversation {
@@ -56,6 +140,9 @@ pub fn query_all() -> Result<Vec<Conversation>> {
             conversation_title: row.get(1)?,
             conversation_start_time: row.get(2)?,
             conversation_modify_time: row.get(3)?,
+            last_message: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            locked: row.get(5)?,
+            pin_order: row.get(6)?,
             conversation_records: vec![],
         })
     })?;
@@ -73,9 +160,13 @@ pub fn query_detail_by_id(conversation: Conversation) -> Result<Conversation> {
     let mut stmt = conn.prepare(
         r#"SELECT
         gemini_message_record.record_id, record_content, record_time, record_sender, sort_index,
-        image_record_id, image_path, image_type
-        FROM gemini_message_record LEFT JOIN gemini_image_record
-        ON gemini_message_record.record_id = gemini_image_record.record_id
+        gemini_message_image.image_record_id, gemini_message_image.image_path, image_type, rating, rating_reason,
+        raw_content
+        FROM gemini_message_record
+        LEFT JOIN gemini_message_image ON gemini_message_record.record_id = gemini_message_image.record_id
+        LEFT JOIN gemini_image_record ON gemini_message_image.image_record_id = gemini_image_record.image_record_id
+        LEFT JOIN gemini_message_rating ON gemini_message_record.record_id = gemini_message_rating.record_id
+        LEFT JOIN gemini_message_raw ON gemini_message_record.record_id = gemini_message_raw.record_id
         WHERE conversation_id = ?1
         ORDER BY sort_index ASC"#,
     )?;
@@ -86,7 +177,7 @@ pub fn query_detail_by_id(conversation: Conversation) -> Result<Conversation> {
                 image_record_id,
                 record_id: row.get(0)?,
                 image_path: row.get(6)?,
-                image_type: row.get(7)?,
+                image_type: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
             })
         } else {
             None
@@ -96,8 +187,14 @@ pub fn query_detail_by_id(conversation: Conversation) -> Result<Conversation> {
         let record_sender = match sender_str.as_str() {
             "User" => Sender::User(image_path.unwrap_or_default()),
             "Bot" => Sender::Bot,
+            "Note" => Sender::Note,
             _ => Sender::Never,
         };
+        let rating = match row.get::<_, Option<String>>(8)?.as_deref() {
+            Some("Good") => Some(Rating::Good),
+            Some("Bad") => Some(Rating::Bad(row.get::<_, Option<String>>(9)?.unwrap_or_default())),
+            _ => None,
+        };
         Ok(MessageRecord {
             conversation_id: conversation.conversation_id.clone(),
             record_id: row.get(0)?,
@@ -106,6 +203,8 @@ pub fn query_detail_by_id(conversation: Conversation) -> Result<Conversation> {
             record_sender,
             sort_index: row.get(4)?,
             image_record,
+            rating,
+            raw_content: row.get(10)?,
         })
     })?;
 
@@ -124,16 +223,6 @@ pub fn query_detail_by_id(conversation: Conversation) -> Result<Conversation> {
 pub fn delete_one(conversation: Conversation) -> Result<()> {
     let binding = DB_CONNECTION.lock().unwrap();
     let conn = binding.borrow();
-    // 删除图片记录
-    conversation
-        .conversation_records
-        .iter()
-        .filter(|record| record.image_record.is_some())
-        .map(|record| record.image_record.clone())
-        .for_each(|record| {
-            let image_record_id = record.unwrap().image_record_id;
-            let _ = delete_image_cache(image_record_id);
-        });
     // 删除表
     let sql = format!(
         r#"
@@ -144,11 +233,42 @@ pub fn delete_one(conversation: Conversation) -> Result<()> {
         conversation.conversation_id
     );
     conn.execute_batch(sql.as_str())?;
+    // 删除不再被任何消息引用的图片记录及缓存
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT image_record_id FROM gemini_image_record
+        WHERE image_record_id NOT IN (SELECT image_record_id FROM gemini_message_image)
+        "#,
+    )?;
+    let image_record_ids: Vec<String> = stmt.query_map([], |row| row.get(0))?.flatten().collect();
+    for image_record_id in image_record_ids {
+        let _ = delete_image_cache(image_record_id.clone());
+        conn.execute(
+            "DELETE FROM gemini_image_record WHERE image_record_id = ?1",
+            [image_record_id],
+        )?;
+    }
     Ok(())
 }
 
-/// 保存对话
-pub fn save_conversation(conversation_id: String, conversation_title: String, message: ChatMessage) -> Result<()> {
+/// 保存对话，返回新增的消息记录 id
+pub fn save_conversation(conversation_id: String, conversation_title: String, message: ChatMessage) -> Result<String> {
+    save_conversation_with_image(conversation_id, conversation_title, message, None)
+}
+
+/// 保存消息，返回新增的消息记录 id，之后可以通过该 id 修改或评价这条消息，消息已带有 id 时使用该 id。
+/// `cached_image` 为已在后台缓存的图片记录 id，为 None 时在此处压缩并缓存图片，
+/// 相同内容的图片只保存一条图片记录，由多条消息共同引用
+pub fn save_conversation_with_image(
+    conversation_id: String,
+    conversation_title: String,
+    message: ChatMessage,
+    cached_image: Option<String>,
+) -> Result<String> {
+    if matches!(message.sender, crate::model::view::Sender::Never) {
+        bail!("该类型的消息不需要保存")
+    }
+    let record_id = message.record_id.clone().unwrap_or_else(generate_unique_id);
     let binding = DB_CONNECTION.lock().unwrap();
     let conn = binding.borrow();
     // 查询是否存在此会话
@@ -199,7 +319,6 @@ pub fn save_conversation(conversation_id: String, conversation_title: String, me
     // 新增一条消息到对应会话
     match message.sender {
         crate::model::view::Sender::User(image_url) => {
-            let record_id = generate_unique_id();
             let conversation_id = conversation_id.clone();
             let record_content = message.message.clone();
             let record_time = message.date_time;
@@ -210,36 +329,55 @@ pub fn save_conversation(conversation_id: String, conversation_title: String, me
             "#, [record_id.clone(), conversation_id, record_content.to_string(), record_time.to_string(), record_sender, sort_index.to_string()])?;
             // 如果图片路径不为空，则插入图片记录
             if !image_url.is_empty() {
-                let image_record_id = generate_unique_id();
                 let image_path = image_url.clone();
-                // 写入文件
-                cache_image(image_url, image_record_id.clone())?;
+                let image_record_id = match cached_image {
+                    Some(image_record_id) => image_record_id,
+                    // 写入文件
+                    None => cache_image_by_hash(image_url)?,
+                };
                 // 压缩后的图片格式
-                let image_type = "image/jpeg".into();
+                let image_type = "image/jpeg".to_string();
+                conn.execute(
+                    r#"
+                    INSERT OR IGNORE INTO gemini_image_record (image_record_id, image_path, image_type)
+                    VALUES (?1, ?2, ?3)
+                "#,
+                    [image_record_id.clone(), image_path.clone(), image_type],
+                )?;
                 conn.execute(
                     r#"
-                    INSERT INTO gemini_image_record (image_record_id, record_id, image_path, image_type)
-                    VALUES (?1, ?2, ?3, ?4)
+                    INSERT INTO gemini_message_image (record_id, image_record_id, image_path)
+                    VALUES (?1, ?2, ?3)
                 "#,
-                    [image_record_id, record_id, image_path, image_type],
+                    [record_id.clone(), image_record_id, image_path],
                 )?;
             }
         }
-        crate::model::view::Sender::Bot => {
-            let record_id = generate_unique_id();
+        crate::model::view::Sender::Bot | crate::model::view::Sender::Note => {
             let conversation_id = conversation_id.clone();
             let record_content = message.message.clone();
             let record_time = message.date_time;
-            let record_sender = "Bot".to_string();
+            let record_sender = if matches!(message.sender, crate::model::view::Sender::Note) {
+                "Note".to_string()
+            } else {
+                "Bot".to_string()
+            };
             conn.execute(r#"
             INSERT INTO gemini_message_record (record_id, conversation_id, record_content, record_time, record_sender, sort_index)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-            "#, [record_id, conversation_id, record_content.to_string(), record_time.to_string(), record_sender, sort_index.to_string()])?;
+            "#, [record_id.clone(), conversation_id, record_content.to_string(), record_time.to_string(), record_sender, sort_index.to_string()])?;
+            // 回复原文与整理后的内容不同时另外保存原文
+            if let Some(raw_content) = message.raw.filter(|raw| *raw != record_content) {
+                conn.execute(
+                    "INSERT OR REPLACE INTO gemini_message_raw (record_id, raw_content) VALUES (?1, ?2)",
+                    [record_id.clone(), raw_content],
+                )?;
+            }
         }
         crate::model::view::Sender::Never => {}
     }
 
-    Ok(())
+    Ok(record_id)
 }
 
 /// 修改会话标题
@@ -256,7 +394,250 @@ pub fn modify_title(conversation_id: String, conversation_title: String) -> Resu
     Ok(())
 }
 
+/// 修改一条消息的内容
+pub fn modify_reply(record_id: String, record_content: String) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    let _ = conn.execute(
+        r#"
+        UPDATE gemini_message_record SET record_content = ?1
+        WHERE record_id = ?2
+        "#,
+        [record_content, record_id.clone()],
+    )?;
+    // 修改后的内容即为原文
+    conn.execute("DELETE FROM gemini_message_raw WHERE record_id = ?1", [record_id])?;
+    Ok(())
+}
+
+/// 评价一条回复，评价为空时清除评价
+pub fn rate_reply(record_id: String, rating: Option<Rating>) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    match rating {
+        Some(rating) => {
+            let (rating, reason) = match rating {
+                Rating::Good => ("Good", None),
+                Rating::Bad(reason) => ("Bad", Some(reason)),
+            };
+            conn.execute(
+                r#"
+                INSERT OR REPLACE INTO gemini_message_rating (record_id, rating, rating_reason)
+                VALUES (?1, ?2, ?3)
+                "#,
+                rusqlite::params![record_id, rating, reason],
+            )?;
+        }
+        None => {
+            conn.execute("DELETE FROM gemini_message_rating WHERE record_id = ?1", [record_id])?;
+        }
+    }
+    Ok(())
+}
+
+/// 锁定或解锁会话
+pub fn lock_conversation(conversation_id: String, locked: bool) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    if locked {
+        conn.execute(
+            "INSERT OR IGNORE INTO gemini_conversation_lock (conversation_id) VALUES (?1)",
+            [conversation_id],
+        )?;
+    } else {
+        conn.execute(
+            "DELETE FROM gemini_conversation_lock WHERE conversation_id = ?1",
+            [conversation_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 置顶或取消置顶会话，新置顶的会话排在已置顶会话的最后
+pub fn pin_conversation(conversation_id: String, pinned: bool) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    if pinned {
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO gemini_conversation_pin (conversation_id, pin_order)
+            SELECT ?1, COALESCE(MAX(pin_order), 0) + 1 FROM gemini_conversation_pin
+            "#,
+            [conversation_id],
+        )?;
+    } else {
+        conn.execute(
+            "DELETE FROM gemini_conversation_pin WHERE conversation_id = ?1",
+            [conversation_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 按给定的顺序保存置顶会话的排列顺序
+pub fn reorder_pins(conversation_ids: &[String]) -> Result<()> {
+    let mut binding = DB_CONNECTION.lock().unwrap();
+    let tx = binding.transaction()?;
+    for (index, conversation_id) in conversation_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE gemini_conversation_pin SET pin_order = ?1 WHERE conversation_id = ?2",
+            rusqlite::params![index as i64 + 1, conversation_id],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// 会话是否已锁定
+pub fn is_conversation_locked(conversation_id: &str) -> Result<bool> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    let locked = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM gemini_conversation_lock WHERE conversation_id = ?1)",
+        [conversation_id],
+        |row| row.get(0),
+    )?;
+    Ok(locked)
+}
+
+/// 查询所有定时任务
+pub fn query_schedules() -> Result<Vec<Schedule>> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    let mut stmt = conn.prepare(
+        r#"SELECT schedule_id, schedule_name, schedule_prompt, watch_file, repeat_minutes, next_run_time, enabled
+        FROM gemini_schedule ORDER BY next_run_time ASC"#,
+    )?;
+    let mut rows = stmt.query_map([], |row| {
+        Ok(Schedule {
+            schedule_id: row.get(0)?,
+            schedule_name: row.get(1)?,
+            schedule_prompt: row.get(2)?,
+            watch_file: row.get(3)?,
+            repeat_minutes: row.get(4)?,
+            next_run_time: row.get(5)?,
+            enabled: row.get(6)?,
+        })
+    })?;
+    let mut schedules = Vec::new();
+    while let Some(Ok(schedule)) = rows.next() {
+        schedules.push(schedule);
+    }
+    Ok(schedules)
+}
+
+/// 新增或更新定时任务
+pub fn save_schedule(schedule: &Schedule) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO gemini_schedule
+        (schedule_id, schedule_name, schedule_prompt, watch_file, repeat_minutes, next_run_time, enabled)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "#,
+        rusqlite::params![
+            schedule.schedule_id,
+            schedule.schedule_name,
+            schedule.schedule_prompt,
+            schedule.watch_file,
+            schedule.repeat_minutes,
+            schedule.next_run_time,
+            schedule.enabled,
+        ],
+    )?;
+    Ok(())
+}
+
+/// 删除定时任务
+pub fn delete_schedule(schedule_id: String) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    conn.execute("DELETE FROM gemini_schedule WHERE schedule_id = ?1", [schedule_id])?;
+    Ok(())
+}
+
+/// 记录一次请求的用量，累加到当天对应模型的用量中
+pub fn record_usage(model: &str, prompt_tokens: i64, response_tokens: i64) -> Result<()> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    conn.execute(
+        r#"
+        INSERT INTO gemini_usage (usage_date, model, request_count, prompt_tokens, response_tokens)
+        VALUES (?1, ?2, 1, ?3, ?4)
+        ON CONFLICT (usage_date, model) DO UPDATE SET
+        request_count = request_count + 1,
+        prompt_tokens = prompt_tokens + excluded.prompt_tokens,
+        response_tokens = response_tokens + excluded.response_tokens
+        "#,
+        rusqlite::params![Local::now().date_naive(), model, prompt_tokens, response_tokens],
+    )?;
+    Ok(())
+}
+
+/// 查询指定日期之后的用量
+pub fn query_usage(since: NaiveDate) -> Result<Vec<Usage>> {
+    let binding = DB_CONNECTION.lock().unwrap();
+    let conn = binding.borrow();
+    let mut stmt = conn.prepare(
+        r#"SELECT usage_date, model, request_count, prompt_tokens, response_tokens
+        FROM gemini_usage WHERE usage_date >= ?1 ORDER BY usage_date ASC"#,
+    )?;
+    let mut rows = stmt.query_map([since], |row| {
+        Ok(Usage {
+            usage_date: row.get(0)?,
+            model: row.get(1)?,
+            request_count: row.get(2)?,
+            prompt_tokens: row.get(3)?,
+            response_tokens: row.get(4)?,
+        })
+    })?;
+    let mut usages = Vec::new();
+    while let Some(Ok(usage)) = rows.next() {
+        usages.push(usage);
+    }
+    Ok(usages)
+}
+
 /// 生成唯一 ID
 pub fn generate_unique_id() -> String {
     nanoid!(10)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_reply_is_kept_until_edited() {
+        let conversation = Conversation {
+            conversation_id: generate_unique_id(),
+            conversation_title: "Raw".into(),
+            conversation_start_time: Local::now(),
+            conversation_modify_time: Local::now(),
+            last_message: String::new(),
+            locked: false,
+            pin_order: None,
+            conversation_records: Vec::new(),
+        };
+        let reply = ChatMessage {
+            success: true,
+            sender: Sender::Bot,
+            message: "First.\nSecond.".into(),
+            date_time: Local::now(),
+            record_id: None,
+            attachments: Vec::new(),
+            raw: Some("First.\n\nSecond.\n".into()),
+        };
+        let record_id = save_conversation(conversation.conversation_id.clone(), "Raw".into(), reply).unwrap();
+        let detail = query_detail_by_id(conversation.clone()).unwrap();
+        assert_eq!(
+            detail.conversation_records[0].raw_content.as_deref(),
+            Some("First.\n\nSecond.\n")
+        );
+        modify_reply(record_id, "Edited.".into()).unwrap();
+        let detail = query_detail_by_id(conversation).unwrap();
+        assert_eq!(detail.conversation_records[0].record_content, "Edited.");
+        assert!(detail.conversation_records[0].raw_content.is_none());
+    }
+}
@@ -0,0 +1,56 @@
+use std::{
+    env, fs, io,
+    path::Path,
+    process::{ExitStatus, Stdio},
+};
+
+use anyhow::{bail, Result};
+
+use super::{db_utils::generate_unique_id, tool_utils::template_command};
+
+/// 使用外部编辑器编辑文本，编辑器依次读取 `VISUAL`、`EDITOR` 环境变量，
+/// 调用前需要先恢复终端，编辑器退出后返回编辑后的文本
+pub fn edit_text(text: &str) -> Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or(if cfg!(windows) { "notepad" } else { "vi" }.into());
+    let path = env::temp_dir().join(format!("gemini-edit-{}.md", generate_unique_id()));
+    fs::write(&path, text)?;
+    let status = open_file(&editor, &path);
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    match status {
+        Ok(status) if status.success() => Ok(edited?.trim_end().to_string()),
+        Ok(status) => bail!("编辑器异常退出：{}", status),
+        Err(e) => bail!("无法启动编辑器 {}：{}", editor, e),
+    }
+}
+
+/// 使用分页器查看文本，分页器读取 `PAGER` 环境变量，`viewer` 为 `editor` 时改用编辑器查看，
+/// 编辑器中的修改不会保存，调用前需要先恢复终端
+pub fn view_text(text: &str, viewer: &str) -> Result<()> {
+    let program = if viewer == "editor" {
+        env::var("VISUAL").or_else(|_| env::var("EDITOR"))
+    } else {
+        env::var("PAGER")
+    }
+    .unwrap_or(if cfg!(windows) { "more" } else { "less" }.into());
+    let path = env::temp_dir().join(format!("gemini-view-{}.md", generate_unique_id()));
+    fs::write(&path, text)?;
+    let status = open_file(&program, &path);
+    let _ = fs::remove_file(&path);
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => bail!("{} 异常退出：{}", program, status),
+        Err(e) => bail!("无法启动 {}：{}", program, e),
+    }
+}
+
+/// 执行程序打开文件，程序需要继承终端的标准输入输出
+fn open_file(program: &str, path: &Path) -> io::Result<ExitStatus> {
+    template_command(&format!("{} {{file}}", program), &[("file", &path.to_string_lossy())])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+}
@@ -0,0 +1,83 @@
+/// 常见的接口错误，按顺序匹配错误信息中的关键字，匹配到任意一个即使用对应的提示
+const KNOWN_ERRORS: [(&[&str], &str); 6] = [
+    (
+        &["API_KEY_INVALID", "API key not valid", "API key expired"],
+        "Invalid API key, open the settings and check the key",
+    ),
+    (
+        &["User location is not supported", "FAILED_PRECONDITION"],
+        "Gemini API is not available in your region, try a proxy or another network",
+    ),
+    (
+        &["RESOURCE_EXHAUSTED", "exceeded your current quota"],
+        "Quota exceeded, retry later or switch to another model in the settings",
+    ),
+    (
+        &["NOT_FOUND", "is not found"],
+        "Model not found, switch to another model in the settings",
+    ),
+    (
+        &["SAFETY", "blockReason", "PROHIBITED_CONTENT", "BLOCKLIST", "RECITATION"],
+        "The content was blocked by the safety filters, rephrase the message and retry",
+    ),
+    (
+        &["UNAVAILABLE", "INTERNAL"],
+        "Gemini service is unavailable right now, retry later",
+    ),
+];
+
+/// 模型容量不足时的错误关键字，包括接口返回的状态与 HTTP 状态码
+const CAPACITY_ERRORS: [&str; 4] = ["UNAVAILABLE", "overloaded", "\"code\": 503", "503 Service Unavailable"];
+
+/// 错误是否由模型暂时容量不足引起，此时可以改用其他模型重试
+pub fn is_capacity_error(raw: &str) -> bool {
+    CAPACITY_ERRORS.iter().any(|keyword| raw.contains(keyword))
+}
+
+/// 将接口返回的原始错误转换为一行带有处理建议的提示，无法识别的错误原样返回
+pub fn friendly_error(raw: &str) -> String {
+    KNOWN_ERRORS
+        .iter()
+        .find(|(keywords, _)| keywords.iter().any(|keyword| raw.contains(keyword)))
+        .map(|(_, message)| message.to_string())
+        .unwrap_or_else(|| raw.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_errors_get_hints() {
+        let raw = r#"{"error": {"code": 400, "message": "API key not valid. Please pass a valid API key.", "status": "INVALID_ARGUMENT"}}"#;
+        assert_eq!(
+            friendly_error(raw),
+            "Invalid API key, open the settings and check the key"
+        );
+        assert_eq!(
+            friendly_error(r#"{"error": {"code": 429, "status": "RESOURCE_EXHAUSTED"}}"#),
+            "Quota exceeded, retry later or switch to another model in the settings"
+        );
+        assert_eq!(
+            friendly_error(r#"{"promptFeedback": {"blockReason": "SAFETY"}}"#),
+            "The content was blocked by the safety filters, rephrase the message and retry"
+        );
+    }
+
+    #[test]
+    fn unknown_errors_are_kept() {
+        assert_eq!(friendly_error("Network unreachable"), "Network unreachable");
+        assert_eq!(friendly_error(""), "");
+    }
+
+    #[test]
+    fn capacity_errors_allow_fallback() {
+        assert!(is_capacity_error(
+            r#"{"error": {"code": 503, "status": "UNAVAILABLE"}}"#
+        ));
+        assert!(is_capacity_error("The model is overloaded. Please try again later."));
+        assert!(!is_capacity_error(
+            r#"{"error": {"code": 429, "status": "RESOURCE_EXHAUSTED"}}"#
+        ));
+    }
+}
@@ -0,0 +1,434 @@
+use std::{env, fs, path::PathBuf};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::model::{
+    db::{Conversation, MessageRecord},
+    view::{ChatMessage, Sender},
+};
+
+use super::{
+    db_utils::{query_all, query_detail_by_id},
+    image_utils::read_image_cache,
+    markdown_utils::{classify_lines, parse_code_blocks, parse_tables, LineKind},
+    model_utils::model_resource,
+    store_utils::StoreData,
+};
+
+/// 导出页面的内嵌样式
+const EXPORT_CSS: &str = r#"
+body { margin: 0 auto; max-width: 860px; padding: 24px; font-family: -apple-system, "Segoe UI", "Microsoft YaHei", sans-serif; background: #f6f7f9; color: #1f2328; }
+h1.title { font-size: 1.6em; }
+.message { margin: 16px 0; padding: 12px 16px; border-radius: 8px; background: #fff; box-shadow: 0 1px 3px rgba(0, 0, 0, .1); }
+.message.user { margin-left: 15%; border-left: 4px solid #0aa; }
+.message.bot { margin-right: 15%; border-left: 4px solid #d9a400; }
+.message.note { margin: 16px 10%; background: transparent; box-shadow: none; border: 1px dashed #bbb; color: #6e7781; }
+.meta { color: #888; font-size: .85em; margin-bottom: 8px; }
+.message img { max-width: 100%; border-radius: 4px; }
+pre { background: #1e1e1e; color: #d4d4d4; padding: 12px; border-radius: 6px; overflow-x: auto; }
+pre .lang { display: block; color: #888; font-size: .8em; margin-bottom: 6px; }
+code { font-family: Consolas, "Courier New", monospace; }
+p code { background: #eee; padding: 1px 4px; border-radius: 3px; }
+table { border-collapse: collapse; margin: 8px 0; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; }
+th { background: #f0f0f0; }
+.kw { color: #569cd6; }
+.str { color: #ce9178; }
+.num { color: #b5cea8; }
+.cmt { color: #6a9955; }
+.add { color: #89d185; }
+.del { color: #f48771; }
+.hunk { color: #4ec9b0; }
+"#;
+
+/// 代码高亮使用的常见关键字
+const KEYWORDS: &str = "as async await break case catch class const continue def default do else enum export extends \
+false fn for from func function if impl import in interface let loop match mod mut new null \
+package pub return self static struct switch this throw trait true try type use var void while \
+yield";
+
+/// 将会话导出为单个自包含的 HTML 页面，图片从缓存中读取并内嵌
+pub fn export_html(conversation: &Conversation) -> String {
+    let messages: String = conversation
+        .conversation_records
+        .iter()
+        .filter(|record| !matches!(record.record_sender, Sender::Never))
+        .map(render_record)
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>{EXPORT_CSS}</style>
+</head>
+<body>
+<h1 class="title">{title}</h1>
+{messages}</body>
+</html>
+"#,
+        title = escape_html(&conversation.conversation_title),
+    )
+}
+
+/// 渲染单条消息记录
+fn render_record(record: &MessageRecord) -> String {
+    let (class, name) = match record.record_sender {
+        Sender::User(_) => ("user", "User"),
+        Sender::Note => ("note", "Note"),
+        _ => ("bot", "Gemini"),
+    };
+    // 内嵌图片
+    let image = record
+        .image_record
+        .as_ref()
+        .and_then(|image_record| read_image_cache(image_record.image_record_id.clone()).ok())
+        .map(|(mime_type, data)| format!("<img src=\"data:{};base64,{}\">\n", mime_type, data))
+        .unwrap_or_default();
+    format!(
+        "<div class=\"message {}\">\n<div class=\"meta\">{} · {}</div>\n{}{}</div>\n",
+        class,
+        name,
+        record.record_time.format("%Y/%m/%d %H:%M:%S"),
+        image,
+        markdown_to_html(&record.record_content),
+    )
+}
+
+/// 将 Markdown 文本转换为 HTML，支持标题、列表、表格、代码块与行内样式
+pub fn markdown_to_html(message: &str) -> String {
+    let lines: Vec<&str> = message.lines().collect();
+    let kinds = classify_lines(message);
+    let blocks = parse_code_blocks(message);
+    let tables = parse_tables(message);
+    let mut html = String::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_list = false;
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        let block = blocks.iter().find(|block| block.start_line == index);
+        let table = tables.iter().find(|table| table.start_line == index);
+        let list_item = line
+            .trim_start()
+            .strip_prefix("- ")
+            .or_else(|| line.trim_start().strip_prefix("* "));
+        // 段落与列表在遇到其他元素时结束
+        if !paragraph.is_empty()
+            && (block.is_some() || table.is_some() || list_item.is_some() || line.trim().is_empty())
+        {
+            html.push_str(&format!("<p>{}</p>\n", paragraph.join("<br>\n")));
+            paragraph.clear();
+        }
+        if in_list && list_item.is_none() {
+            html.push_str("</ul>\n");
+            in_list = false;
+        }
+        if let Some(block) = block {
+            let code: String = (block.start_line + 1..block.end_line)
+                .map(|i| highlight_code_line(lines[i], kinds[i]) + "\n")
+                .collect();
+            let language = if block.language.is_empty() {
+                String::new()
+            } else {
+                format!("<span class=\"lang\">{}</span>", escape_html(&block.language))
+            };
+            html.push_str(&format!("<pre>{}<code>{}</code></pre>\n", language, code));
+            index = block.end_line + 1;
+            continue;
+        }
+        if let Some(table) = table {
+            let header: String = table
+                .header
+                .iter()
+                .map(|cell| format!("<th>{}</th>", inline_to_html(cell)))
+                .collect();
+            let rows: String = table
+                .rows
+                .iter()
+                .map(|row| {
+                    let cells: String = row
+                        .iter()
+                        .map(|cell| format!("<td>{}</td>", inline_to_html(cell)))
+                        .collect();
+                    format!("<tr>{}</tr>\n", cells)
+                })
+                .collect();
+            html.push_str(&format!("<table>\n<tr>{}</tr>\n{}</table>\n", header, rows));
+            index = table.end_line + 1;
+            continue;
+        }
+        if let Some(item) = list_item {
+            if !in_list {
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline_to_html(item)));
+        } else if let Some((level, heading)) = parse_heading(line) {
+            html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, inline_to_html(heading)));
+        } else if !line.trim().is_empty() {
+            paragraph.push(inline_to_html(line));
+        }
+        index += 1;
+    }
+    if !paragraph.is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", paragraph.join("<br>\n")));
+    }
+    if in_list {
+        html.push_str("</ul>\n");
+    }
+    html
+}
+
+/// 解析标题行，返回 (级别, 标题内容)，页面标题占用 h1，因此级别顺延一级
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) {
+        line[level..]
+            .strip_prefix(' ')
+            .map(|heading| ((level + 1).min(6), heading))
+    } else {
+        None
+    }
+}
+
+/// 转换行内样式：`代码`、**粗体**、*斜体*
+fn inline_to_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let styled = [("`", "code"), ("**", "strong"), ("*", "em")]
+            .into_iter()
+            .find_map(|(mark, tag)| {
+                let inner = rest.strip_prefix(mark)?;
+                let end = inner.find(mark).filter(|end| *end > 0)?;
+                Some((tag, &inner[..end], &inner[end + mark.len()..]))
+            });
+        if let Some((tag, inner, remain)) = styled {
+            let inner = if tag == "code" {
+                escape_html(inner)
+            } else {
+                inline_to_html(inner)
+            };
+            html.push_str(&format!("<{0}>{1}</{0}>", tag, inner));
+            rest = remain;
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            html.push_str(&escape_html(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    html
+}
+
+/// 高亮一行代码，diff 按增删着色，其他代码高亮关键字、字符串、数字与注释
+fn highlight_code_line(line: &str, kind: LineKind) -> String {
+    let class = match kind {
+        LineKind::DiffAdd => "add",
+        LineKind::DiffRemove => "del",
+        LineKind::DiffHunk => "hunk",
+        _ => return highlight_code(line),
+    };
+    format!("<span class=\"{}\">{}</span>", class, escape_html(line))
+}
+
+/// 对代码进行简单的词法高亮
+fn highlight_code(line: &str) -> String {
+    let trimmed = line.trim_start();
+    if ["//", "#", "--"].iter().any(|p| trimmed.starts_with(p)) {
+        return format!("<span class=\"cmt\">{}</span>", escape_html(line));
+    }
+    let chars: Vec<char> = line.chars().collect();
+    let mut html = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        let start = index;
+        if c == '"' || c == '\'' {
+            // 字符串
+            index += 1;
+            while index < chars.len() && chars[index] != c {
+                index += if chars[index] == '\\' { 2 } else { 1 };
+            }
+            index = (index + 1).min(chars.len());
+            let token: String = chars[start..index].iter().collect();
+            html.push_str(&format!("<span class=\"str\">{}</span>", escape_html(&token)));
+        } else if c.is_alphanumeric() || c == '_' {
+            // 单词或数字
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+            let token: String = chars[start..index].iter().collect();
+            if c.is_ascii_digit() {
+                html.push_str(&format!("<span class=\"num\">{}</span>", token));
+            } else if KEYWORDS.split_whitespace().any(|keyword| keyword == token) {
+                html.push_str(&format!("<span class=\"kw\">{}</span>", token));
+            } else {
+                html.push_str(&escape_html(&token));
+            }
+        } else {
+            html.push_str(&escape_html(&c.to_string()));
+            index += 1;
+        }
+    }
+    html
+}
+
+/// 将会话导出为 Markdown 文本，每条消息以发送者与时间作为二级标题
+pub fn export_markdown(title: &str, messages: &[ChatMessage]) -> String {
+    let mut markdown = format!("# {}\n", title);
+    for message in messages {
+        let name = match message.sender {
+            Sender::User(_) => "User",
+            Sender::Bot => "Gemini",
+            Sender::Note => "Note",
+            Sender::Never => continue,
+        };
+        markdown.push_str(&format!(
+            "\n## {} · {}\n\n{}\n",
+            name,
+            message.date_time.format("%Y/%m/%d %H:%M:%S"),
+            message.message.trim_end()
+        ));
+    }
+    markdown
+}
+
+/// 将数据库中仍能读取的会话导出为 Markdown 文件，用于数据库损坏时挽救聊天记录，
+/// 文件保存在程序所在目录的 `salvage` 文件夹中，可以使用 `gemini --view` 查看，返回导出的会话数量与目录，
+/// 每导出一个会话调用一次 `on_progress(已导出数量, 会话总数)`
+pub fn salvage_conversations(mut on_progress: impl FnMut(usize, usize)) -> Result<(usize, PathBuf)> {
+    let exe_path = env::current_exe()?;
+    let dir = exe_path.parent().unwrap().join("salvage");
+    fs::create_dir_all(&dir)?;
+    let conversations = query_all()?;
+    let total = conversations.len();
+    let mut count = 0;
+    for conversation in conversations {
+        // 读取失败的会话只导出标题，读取中途出错时保留已读取的消息
+        let conversation = query_detail_by_id(conversation.clone()).unwrap_or(conversation);
+        let messages: Vec<ChatMessage> = conversation
+            .conversation_records
+            .iter()
+            .map(|record| ChatMessage {
+                success: true,
+                sender: record.record_sender.clone(),
+                message: record.record_content.clone(),
+                date_time: record.record_time,
+                record_id: Some(record.record_id.clone()),
+                attachments: Vec::new(),
+                raw: record.raw_content.clone(),
+            })
+            .collect();
+        let file_name = export_file_name(&conversation.conversation_title, &conversation.conversation_id, "md");
+        fs::write(
+            dir.join(file_name),
+            export_markdown(&conversation.conversation_title, &messages),
+        )?;
+        count += 1;
+        on_progress(count, total);
+    }
+    Ok((count, dir))
+}
+
+/// 将会话导出为 Google AI Studio 的提示词 JSON，可在网页端继续对话
+pub fn export_ai_studio(conversation: &Conversation, config: &StoreData) -> String {
+    let options = &config.options;
+    let mut chunks: Vec<Value> = Vec::new();
+    for record in &conversation.conversation_records {
+        let role = match record.record_sender {
+            Sender::User(_) => "user",
+            Sender::Bot => "model",
+            // 备注不属于对话内容
+            Sender::Note | Sender::Never => continue,
+        };
+        // 图片作为单独的片段放在文本之前
+        if let Some((mime_type, data)) = record
+            .image_record
+            .as_ref()
+            .and_then(|image_record| read_image_cache(image_record.image_record_id.clone()).ok())
+        {
+            chunks.push(json!({
+                "inlineImage": { "mimeType": mime_type, "data": data },
+                "role": role,
+            }));
+        }
+        chunks.push(json!({ "text": record.record_content, "role": role }));
+    }
+    let mut run_settings = json!({ "model": model_resource(&config.model.to_string()) });
+    for (name, value) in [
+        ("temperature", options.temperature.map(Value::from)),
+        ("topP", options.top_p.map(Value::from)),
+        ("topK", options.top_k.map(Value::from)),
+        ("maxOutputTokens", options.max_output_tokens.map(Value::from)),
+        ("responseMimeType", options.response_mime_type.clone().map(Value::from)),
+    ] {
+        if let Some(value) = value {
+            run_settings[name] = value;
+        }
+    }
+    let mut prompt = json!({
+        "runSettings": run_settings,
+        "chunkedPrompt": { "chunks": chunks },
+    });
+    if let Some(system_instruction) = config.system_instruction.as_ref().filter(|s| !s.trim().is_empty()) {
+        prompt["systemInstruction"] = json!({ "text": system_instruction });
+    }
+    serde_json::to_string_pretty(&prompt).unwrap_or_default()
+}
+
+/// 根据会话标题生成导出文件名，替换文件名中不允许出现的字符，标题为空时使用会话 id
+pub fn export_file_name(title: &str, conversation_id: &str, extension: &str) -> String {
+    let title: String = title
+        .chars()
+        .map(|c| {
+            if "\\/:*?\"<>|".contains(c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let title = title.trim();
+    if title.is_empty() {
+        format!("{}.{}", conversation_id, extension)
+    } else {
+        format!("{}.{}", title, extension)
+    }
+}
+
+/// 转义 HTML 特殊字符
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_special_chars() {
+        assert_eq!(
+            escape_html("<a href=\"x\" title='y'>&</a>"),
+            "&lt;a href=&quot;x&quot; title=&#39;y&#39;&gt;&amp;&lt;/a&gt;"
+        );
+        // & 最先转义，已转义的实体不会被还原
+        assert_eq!(escape_html("&lt;"), "&amp;lt;");
+    }
+
+    #[test]
+    fn markdown_html_is_escaped() {
+        let message = "<script>alert(1)</script>\n\n# <b>x</b>\n\n- `<i>` **<u>**\n\n| <a> | b |\n| --- | --- |\n| <img src=x onerror=alert(1)> | c |\n\n```html\n<script>\n```";
+        let html = markdown_to_html(message);
+        for tag in ["<script", "<b>", "<i>", "<u>", "<a>", "<img"] {
+            assert!(!html.contains(tag), "{} in {}", tag, html);
+        }
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    io::Write,
+    ops::Range,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Result};
+
+/// 默认代码审查提示词
+pub const DEFAULT_REVIEW_PROMPT: &str = "Please review the following git diff. List bugs, risky changes and style \
+issues, and reference each finding as path/to/file:line.";
+
+/// 获取 git diff 内容
+///
+/// 未指定范围时优先使用暂存区的改动，暂存区为空则使用工作区的改动
+pub fn git_diff(range: Option<&str>) -> Result<String> {
+    let diff = match range {
+        Some(range) if !range.trim().is_empty() => run_git(&["diff", range.trim()])?,
+        _ => {
+            let staged = run_git(&["diff", "--staged"])?;
+            if staged.trim().is_empty() {
+                run_git(&["diff"])?
+            } else {
+                staged
+            }
+        }
+    };
+    if diff.trim().is_empty() {
+        bail!("No changes to review")
+    }
+    Ok(diff)
+}
+
+/// 构造代码审查消息
+pub fn review_message(prompt: &str, diff: &str) -> String {
+    format!("{}\n```diff\n{}\n```", prompt, diff.trim_end())
+}
+
+/// 使用 git apply 将 diff 应用到工作区
+pub fn git_apply(diff: &str) -> Result<()> {
+    let mut child = Command::new("git")
+        .args(["apply", "-"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(diff.as_bytes())?;
+        stdin.write_all(b"\n")?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim())
+    }
+    Ok(())
+}
+
+/// 执行 git 命令并返回标准输出
+fn run_git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim())
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 查找一行文本中形如 `path/to/file.rs:12` 的文件行号引用，返回字节范围
+pub fn find_file_references(line: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        let is_token_char = c.is_alphanumeric() || "./_-:".contains(c);
+        match (start, is_token_char) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                if let Some(len) = file_reference_len(&line[s..i]) {
+                    ranges.push(s..s + len);
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// 判断一个单词是否以文件行号引用开头，返回引用的长度
+fn file_reference_len(token: &str) -> Option<usize> {
+    let colon = token.find(':')?;
+    let (path, rest) = token.split_at(colon);
+    // 文件名需要包含扩展名
+    let file_name = path.rsplit('/').next()?;
+    let (stem, ext) = file_name.rsplit_once('.')?;
+    if stem.is_empty() || ext.is_empty() || !ext.chars().all(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    let digits = rest[1..].chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    Some(path.len() + 1 + digits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_references_in_line() {
+        assert_eq!(find_file_references("see src/main.rs:12 now"), vec![4..18]);
+        // 列号不计入引用，标点结束引用，范围按字节计算
+        assert_eq!(find_file_references("at src/lib.rs:3:7"), vec![3..15]);
+        assert_eq!(find_file_references("(a.rs:1), Cargo.toml:20."), vec![1..7, 10..23]);
+        assert_eq!(find_file_references("见 src/a.rs:1"), vec![4..14]);
+    }
+
+    #[test]
+    fn non_references_are_skipped() {
+        assert_eq!(find_file_references("time 12:30"), vec![]);
+        assert_eq!(find_file_references("src/main.rs: no line"), vec![]);
+        assert_eq!(find_file_references("http://localhost:8080"), vec![]);
+        assert_eq!(find_file_references(".rs:1 Makefile:3"), vec![]);
+    }
+}
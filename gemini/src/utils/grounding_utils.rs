@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use gemini_api::{
+    body::{Content, Part, Role},
+    model::blocking::Gemini,
+};
+use serde_json::{json, Value};
+
+use crate::model::view::ChatReply;
+
+use super::model_utils::{generate_content, text_contents};
+
+/// 使用 Google 搜索回答问题，上下文中只保存模型回复的原文，
+/// 存在引用来源时另外返回以 `[1]` 形式标注脚注、末尾附上来源列表的文本，只用于显示
+pub fn send_grounded_message(gemini: &mut Gemini, message: String) -> Result<ChatReply> {
+    let question = Content {
+        parts: vec![Part::Text(message)],
+        role: Some(Role::User),
+    };
+    // 上下文中只发送文本，图片等其他内容在联网搜索时忽略
+    let body = json!({
+        "contents": text_contents(&gemini.contents, &question),
+        "tools": [{ "google_search": {} }],
+    });
+    let value = generate_content(gemini, body, "联网搜索")?;
+    let candidate = &value["candidates"][0];
+    let answer = candidate_text(candidate).context("联网搜索结果为空")?;
+    let citations = cite_response(candidate, &answer);
+    gemini.contents.push(question);
+    gemini.contents.push(Content {
+        parts: vec![Part::Text(answer.clone())],
+        role: Some(Role::Model),
+    });
+    Ok(ChatReply {
+        citations,
+        ..ChatReply::text(answer)
+    })
+}
+
+/// 候选回复的文本，没有文本时返回 None
+fn candidate_text(candidate: &Value) -> Option<String> {
+    let text: String = candidate["content"]["parts"]
+        .as_array()?
+        .iter()
+        .filter_map(|part| part["text"].as_str())
+        .collect();
+    (!text.is_empty()).then_some(text)
+}
+
+/// 存在 `groundingMetadata` 时在引用片段末尾插入脚注编号，并在末尾附上来源列表，没有来源时返回 None
+fn cite_response(candidate: &Value, answer: &str) -> Option<String> {
+    let mut text = answer.to_string();
+    let metadata = &candidate["groundingMetadata"];
+    let sources: Vec<(String, String)> = metadata["groundingChunks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|chunk| {
+            let web = &chunk["web"];
+            (
+                web["title"].as_str().unwrap_or_default().to_string(),
+                web["uri"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    if sources.is_empty() {
+        return None;
+    }
+    // 片段位置为 UTF-8 字节下标，从后往前插入，避免影响前面的位置
+    let mut markers: Vec<(usize, String)> = metadata["groundingSupports"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|support| {
+            let end = support["segment"]["endIndex"].as_u64()? as usize;
+            let marker: String = support["groundingChunkIndices"]
+                .as_array()?
+                .iter()
+                .filter_map(Value::as_u64)
+                .filter(|index| (*index as usize) < sources.len())
+                .map(|index| format!("[{}]", index + 1))
+                .collect();
+            (!marker.is_empty()).then_some((end, marker))
+        })
+        .collect();
+    markers.sort_by(|(a, _), (b, _)| b.cmp(a));
+    for (end, marker) in markers {
+        let mut end = end.min(text.len());
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        text.insert_str(end, &marker);
+    }
+    text.push_str("\n\nSources:");
+    for (i, (title, uri)) in sources.iter().enumerate() {
+        text.push_str(&format!("\n[{}] {} {}", i + 1, title, uri));
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn citations_are_kept_out_of_answer() {
+        let candidate = json!({
+            "content": { "parts": [{ "text": "Rust 1.0 was released in 2015." }] },
+            "groundingMetadata": {
+                "groundingChunks": [{ "web": { "title": "rust-lang.org", "uri": "https://www.rust-lang.org" } }],
+                "groundingSupports": [{ "segment": { "endIndex": 30 }, "groundingChunkIndices": [0] }],
+            },
+        });
+        let answer = candidate_text(&candidate).unwrap();
+        assert_eq!(answer, "Rust 1.0 was released in 2015.");
+        assert_eq!(
+            cite_response(&candidate, &answer).unwrap(),
+            "Rust 1.0 was released in 2015.[1]\n\nSources:\n[1] rust-lang.org https://www.rust-lang.org"
+        );
+        let candidate = json!({ "content": { "parts": [{ "text": "No sources." }] } });
+        assert!(cite_response(&candidate, "No sources.").is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use reqwest::{
+    blocking::{Client, Request, RequestBuilder},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::store_utils::read_config;
+
+/// 默认的空闲连接保留时间（秒）
+const DEFAULT_POOL_IDLE_TIMEOUT: u64 = 90;
+/// 默认的 TCP keep-alive 间隔（秒）
+const DEFAULT_TCP_KEEPALIVE: u64 = 60;
+
+/// 网络连接配置
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct HttpConfig {
+    /// 空闲连接在连接池中保留的时间（秒），为 0 时不保留空闲连接
+    pub pool_idle_timeout: Option<u64>,
+    /// TCP keep-alive 间隔（秒），为 0 时关闭
+    pub tcp_keepalive: Option<u64>,
+}
+
+/// 共享的请求客户端，复用连接池，启动后第一次使用时按配置文件创建
+pub static CLIENT: LazyLock<Client> = LazyLock::new(|| build_client(&read_config().unwrap_or_default().http));
+
+/// 根据配置创建请求客户端，开启 gzip/deflate 响应压缩，HTTPS 连接通过 ALPN 协商使用 HTTP/2，
+/// 开启 `native-tls` feature 时使用系统的 TLS 实现
+pub fn build_client(config: &HttpConfig) -> Client {
+    let seconds = |value: Option<u64>, default: u64| {
+        Some(value.unwrap_or(default))
+            .filter(|s| *s > 0)
+            .map(Duration::from_secs)
+    };
+    let builder = Client::builder();
+    #[cfg(feature = "native-tls")]
+    let builder = builder.use_native_tls();
+    builder
+        .gzip(true)
+        .deflate(true)
+        .pool_idle_timeout(seconds(config.pool_idle_timeout, DEFAULT_POOL_IDLE_TIMEOUT))
+        .tcp_keepalive(seconds(config.tcp_keepalive, DEFAULT_TCP_KEEPALIVE))
+        .build()
+        .unwrap_or_default()
+}
+
+/// 发送请求并将响应解析为 JSON，失败时以 `action` 描述错误，并附上状态码与接口返回的错误内容
+/// 设置 `GEMINI_RECORD_DIR` 时将每次请求的响应录制到该目录，设置 `GEMINI_REPLAY_DIR` 时从该目录回放，不再访问网络
+pub fn send_json(request: RequestBuilder, action: &str) -> Result<Value> {
+    let request = request.build()?;
+    let (status, text) = match Cassette::from_env() {
+        Some(Cassette::Replay(dir)) => read_fixture(&fixture_path(&dir, &request))?,
+        Some(Cassette::Record(dir)) => {
+            let path = fixture_path(&dir, &request);
+            let (status, text) = execute(request)?;
+            write_fixture(&path, status, &text)?;
+            (status, text)
+        }
+        None => execute(request)?,
+    };
+    if !status.is_success() {
+        bail!("{}失败：{} {}", action, status, text)
+    }
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// 以 JSON 请求体发送 POST 请求，响应处理与 [`send_json`] 一致
+pub fn post_json(url: String, body: &Value, action: &str) -> Result<Value> {
+    let request = CLIENT
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body.to_string());
+    send_json(request, action)
+}
+
+/// 接口请求的录制或回放目录
+enum Cassette {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl Cassette {
+    /// 从环境变量中读取录制或回放目录，同时设置时优先回放
+    fn from_env() -> Option<Self> {
+        match (env::var_os("GEMINI_REPLAY_DIR"), env::var_os("GEMINI_RECORD_DIR")) {
+            (Some(dir), _) => Some(Cassette::Replay(dir.into())),
+            (None, Some(dir)) => Some(Cassette::Record(dir.into())),
+            (None, None) => None,
+        }
+    }
+}
+
+/// 发送请求，返回状态码与响应内容
+fn execute(request: Request) -> Result<(StatusCode, String)> {
+    let response = CLIENT.execute(request)?;
+    let status = response.status();
+    Ok((status, response.text()?))
+}
+
+/// 请求对应的录制文件，以请求方法、去掉密钥后的地址与请求体的哈希命名，使录制文件中不包含密钥
+fn fixture_path(dir: &Path, request: &Request) -> PathBuf {
+    let mut url = request.url().clone();
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| name != "key")
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    url.set_query(None);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(request.method().as_str());
+    hasher.update(url.as_str());
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        hasher.update(body);
+    }
+    dir.join(format!("{:x}.json", hasher.finalize()))
+}
+
+/// 读取录制的状态码与响应内容
+fn read_fixture(path: &Path) -> Result<(StatusCode, String)> {
+    let content = fs::read_to_string(path).with_context(|| format!("没有找到录制的响应：{}", path.display()))?;
+    let fixture: Value = serde_json::from_str(&content)?;
+    let status = fixture["status"].as_u64().context("录制文件中缺少状态码")?;
+    let status = StatusCode::from_u16(status as u16)?;
+    let text = fixture["response"].as_str().context("录制文件中缺少响应内容")?;
+    Ok((status, text.to_string()))
+}
+
+/// 保存状态码与响应内容
+fn write_fixture(path: &Path, status: StatusCode, text: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let fixture = json!({ "status": status.as_u16(), "response": text });
+    fs::write(path, serde_json::to_string_pretty(&fixture)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture_ignores_key() {
+        let dir = Path::new("fixtures");
+        let request = |url: &str, body: &str| Client::new().post(url).body(body.to_string()).build().unwrap();
+        let first = fixture_path(dir, &request("https://example.com/v1/models?key=a&alt=json", "{}"));
+        let second = fixture_path(dir, &request("https://example.com/v1/models?key=b&alt=json", "{}"));
+        let other = fixture_path(dir, &request("https://example.com/v1/models?key=a&alt=json", "[]"));
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn fixture_round_trip() {
+        let path = env::temp_dir().join("gemini-tui-fixture-test").join("fixture.json");
+        write_fixture(&path, StatusCode::SERVICE_UNAVAILABLE, "{\"error\":1}").unwrap();
+        let (status, text) = read_fixture(&path).unwrap();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(text, "{\"error\":1}");
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::{bail, Result};
+use serde_json::json;
+
+use crate::model::view::Attachment;
+
+use super::{attachment_utils::save_response_attachments, model_utils::call_model};
+
+/// 默认的图片生成模型
+pub const DEFAULT_IMAGE_MODEL: &str = "imagen-3.0-generate-002";
+
+/// 使用图片生成模型生成图片，并保存到缓存目录，返回保存的图片附件
+///
+/// `imagen` 系列模型通过 `predict` 接口生成图片，其他模型（如可以输出图片的 Gemini 模型）通过
+/// `generateContent` 接口生成，并从回复中取出所有图片
+pub fn generate_image(key: &str, model: &str, prompt: &str) -> Result<Vec<Attachment>> {
+    let (method, body) = if model.starts_with("imagen") {
+        (
+            "predict",
+            json!({
+                "instances": [{ "prompt": prompt }],
+                "parameters": { "sampleCount": 1 },
+            }),
+        )
+    } else {
+        (
+            "generateContent",
+            json!({
+                "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+                "generationConfig": { "responseModalities": ["TEXT", "IMAGE"] },
+            }),
+        )
+    };
+    let value = call_model(key, model, method, &body, "图片生成")?;
+    let images = save_response_attachments(&value)?;
+    if images.is_empty() {
+        bail!("图片生成结果为空")
+    }
+    Ok(images)
+}
@@ -1,13 +1,20 @@
 use anyhow::{bail, Result};
 use gemini_api::utils::image::blocking::get_image_type_and_base64_string;
 use image::codecs::jpeg::JpegEncoder;
-use image::GenericImageView;
-use reqwest::blocking::Client;
+use image::imageops::FilterType;
+use image::{GenericImageView, ImageFormat, ImageReader};
+use sha2::{Digest, Sha256};
 use std::env;
-use std::fs::{create_dir_all, File};
+use std::fs::{self, create_dir_all, File};
 use std::io::BufWriter;
 use std::path::Path;
-use std::sync::LazyLock;
+
+use super::{db_utils::generate_unique_id, http_utils::CLIENT};
+
+/// 直接随消息发送的图片文件大小上限（字节），超过时先缩小再发送
+const MAX_INLINE_IMAGE_SIZE: u64 = 4 * 1024 * 1024;
+/// 缩小图片时的最大边长（像素），Gemini 也会将更大的图片缩小到该尺寸以内
+const MAX_INLINE_IMAGE_SIDE: u32 = 3072;
 
 /// 保存图片
 pub fn cache_image(url: String, file_name: String) -> Result<()> {
@@ -25,6 +32,105 @@ pub fn cache_image(url: String, file_name: String) -> Result<()> {
     Ok(())
 }
 
+/// 图片附件的基本信息
+pub struct ImageInfo {
+    /// 图片 MIME 类型
+    pub mime_type: &'static str,
+    /// 图片宽度
+    pub width: u32,
+    /// 图片高度
+    pub height: u32,
+    /// 文件大小，单位字节
+    pub size: u64,
+}
+
+impl ImageInfo {
+    /// 用于显示的简短描述，如 `image/png 1920x1080 1.2 MB`
+    pub fn summary(&self) -> String {
+        let size = self.size as f64;
+        let size = if size >= 1024.0 * 1024.0 {
+            format!("{:.1} MB", size / 1024.0 / 1024.0)
+        } else {
+            format!("{:.1} KB", size / 1024.0)
+        };
+        format!("{} {}x{} {}", self.mime_type, self.width, self.height, size)
+    }
+}
+
+/// 读取本地图片的类型、尺寸与大小，只读取文件头，不支持的格式返回错误
+pub fn inspect_image(path: &str) -> Result<ImageInfo> {
+    let file_path = Path::new(path);
+    if !file_path.is_file() {
+        bail!("图片文件不存在")
+    }
+    let size = fs::metadata(file_path)?.len();
+    let reader = ImageReader::open(file_path)?.with_guessed_format()?;
+    // Gemini 支持的图片格式
+    let mime_type = match reader.format() {
+        Some(ImageFormat::Png) => "image/png",
+        Some(ImageFormat::Jpeg) => "image/jpeg",
+        Some(ImageFormat::WebP) => "image/webp",
+        Some(format) => bail!("不支持的图片格式：{}", format.to_mime_type()),
+        None => bail!("无法识别的图片格式"),
+    };
+    let (width, height) = reader.into_dimensions()?;
+    Ok(ImageInfo {
+        mime_type,
+        width,
+        height,
+        size,
+    })
+}
+
+/// 本地图片文件超过直接发送的大小上限时，将边长缩小到上限以内并以 JPEG 格式保存到 data 文件夹，
+/// 返回实际发送的图片路径，未超过上限的图片与网络图片原样返回
+pub fn shrink_inline_image(path: &str) -> Result<String> {
+    let file_path = Path::new(path);
+    if !file_path.is_file() || fs::metadata(file_path)?.len() <= MAX_INLINE_IMAGE_SIZE {
+        return Ok(path.to_string());
+    }
+    let mut img = image::open(file_path)?;
+    if img.width().max(img.height()) > MAX_INLINE_IMAGE_SIDE {
+        img = img.resize(MAX_INLINE_IMAGE_SIDE, MAX_INLINE_IMAGE_SIDE, FilterType::Triangle);
+    }
+    let exe_path = env::current_exe()?;
+    let output_path = exe_path
+        .parent()
+        .unwrap()
+        .join("data")
+        .join(format!("inline_{}.jpg", generate_unique_id()));
+    create_dir_all(output_path.parent().unwrap())?;
+    let writer = BufWriter::new(File::create(&output_path)?);
+    let mut encoder = JpegEncoder::new_with_quality(writer, 85);
+    encoder.encode_image(&img.to_rgb8())?;
+    Ok(output_path.display().to_string())
+}
+
+/// 按图片内容缓存图片，返回图片记录 id，id 为原图片内容的哈希值，相同内容的图片只保存一份
+pub fn cache_image_by_hash(url: String) -> Result<String> {
+    let bytes = if url.starts_with("https://") || url.starts_with("http://") {
+        let response = CLIENT.get(url).send()?;
+        if !response.status().is_success() {
+            bail!("Failed to download image")
+        }
+        response.bytes()?.to_vec()
+    } else {
+        fs::read(url)?
+    };
+    let image_record_id = format!("{:x}", Sha256::digest(&bytes));
+    let exe_path = env::current_exe()?;
+    let output_path = exe_path.parent().unwrap().join("data").join(&image_record_id);
+    // 已经缓存过相同内容的图片时直接复用
+    if !output_path.exists() {
+        create_dir_all(output_path.parent().unwrap())?;
+        let img = image::load_from_memory(&bytes)?.to_rgb8();
+        let writer = BufWriter::new(File::create(output_path)?);
+        let mut encoder = JpegEncoder::new_with_quality(writer, 80);
+        encoder.encode_image(&img)?;
+    }
+    Ok(image_record_id)
+}
+
 /// 压缩图片并保存
 pub fn compress_local_image<P>(path: String, file_path: P, quality: u8) -> Result<()>
 where
@@ -64,8 +170,6 @@ pub fn delete_image_cache(file_name: String) -> Result<()> {
     }
     Ok(())
 }
-/// 下载网络图片的请求客户端
-static CLIENT: LazyLock<Client> = LazyLock::new(Client::new);
 
 /// 下载网络图片
 pub fn compress_network_image<P>(path: String, file_path: P, quality: u8) -> Result<()>
@@ -93,3 +197,12 @@ where
         bail!("Failed to download image")
     }
 }
+
+/// 生成缩略图的像素，缩略图保持原图比例且不超过指定大小
+pub fn thumbnail_pixels<P>(path: P, width: u32, height: u32) -> Result<Vec<Vec<[u8; 3]>>>
+where
+    P: AsRef<Path>,
+{
+    let img = image::open(path)?.thumbnail(width, height).to_rgb8();
+    Ok(img.rows().map(|row| row.map(|pixel| pixel.0).collect()).collect())
+}
@@ -0,0 +1,99 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDateTime};
+use serde_json::Value;
+
+use crate::model::view::{
+    ChatMessage,
+    Sender::{self, Bot, User},
+};
+
+/// 读取导出的会话文件，支持 Markdown 与 Google AI Studio 提示词 JSON，返回 (标题, 消息列表)
+pub fn load_exported_conversation(path: &str) -> Result<(String, Vec<ChatMessage>)> {
+    let content = fs::read_to_string(path).with_context(|| format!("无法读取文件 {}", path))?;
+    let file_stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("md") => Ok(parse_markdown(&content, file_stem)),
+        Some("json") => Ok((file_stem, parse_ai_studio(&content)?)),
+        _ => bail!("仅支持 .md 与 .json 格式的导出文件"),
+    }
+}
+
+/// 解析导出的 Markdown，标题行为会话标题，`## 发送者 · 时间` 为每条消息的开头
+fn parse_markdown(content: &str, default_title: String) -> (String, Vec<ChatMessage>) {
+    let mut title = default_title;
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        if let Some(heading) = line.strip_prefix("# ").filter(|_| messages.is_empty()) {
+            title = heading.trim().to_string();
+        } else if let Some((sender, date_time)) = parse_message_heading(line) {
+            if let Some(message) = messages.last_mut() {
+                message.message = lines.join("\n").trim().to_string();
+            }
+            lines.clear();
+            messages.push(ChatMessage {
+                success: true,
+                message: String::new(),
+                sender,
+                date_time,
+                record_id: None,
+                attachments: Vec::new(),
+                raw: None,
+            });
+        } else {
+            lines.push(line);
+        }
+    }
+    if let Some(message) = messages.last_mut() {
+        message.message = lines.join("\n").trim().to_string();
+    }
+    (title, messages)
+}
+
+/// 解析消息开头的标题行
+fn parse_message_heading(line: &str) -> Option<(Sender, chrono::DateTime<Local>)> {
+    let (name, date_time) = line.strip_prefix("## ")?.split_once(" · ")?;
+    let sender = match name {
+        "User" => User("".into()),
+        "Gemini" => Bot,
+        "Note" => Sender::Note,
+        _ => return None,
+    };
+    let date_time = NaiveDateTime::parse_from_str(date_time.trim(), "%Y/%m/%d %H:%M:%S")
+        .ok()?
+        .and_local_timezone(Local)
+        .single()?;
+    Some((sender, date_time))
+}
+
+/// 解析 Google AI Studio 提示词 JSON 中的对话片段，图片片段以占位文本显示
+fn parse_ai_studio(content: &str) -> Result<Vec<ChatMessage>> {
+    let value: Value = serde_json::from_str(content)?;
+    let chunks = value["chunkedPrompt"]["chunks"]
+        .as_array()
+        .context("不是 Google AI Studio 提示词文件")?;
+    let messages = chunks
+        .iter()
+        .map(|chunk| {
+            let message = match chunk["text"].as_str() {
+                Some(text) => text.to_string(),
+                None => "[image]".into(),
+            };
+            ChatMessage {
+                success: true,
+                message,
+                sender: if chunk["role"] == "model" { Bot } else { User("".into()) },
+                date_time: Local::now(),
+                record_id: None,
+                attachments: Vec::new(),
+                raw: None,
+            }
+        })
+        .collect();
+    Ok(messages)
+}
@@ -0,0 +1,93 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// 默认的复制最后一条回复快捷键
+pub const DEFAULT_COPY_REPLY_KEY: &str = "ctrl+shift+c";
+
+/// 快捷键绑定
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyBinding {
+    /// 按键
+    pub code: KeyCode,
+    /// 修饰键
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// 解析形如 `ctrl+shift+c`、`alt+f2` 的快捷键描述，大小写不敏感
+    pub fn parse(binding: &str) -> Option<Self> {
+        let binding = binding.trim().to_lowercase();
+        let mut parts: Vec<&str> = binding.split('+').map(str::trim).collect();
+        let key = parts.pop()?;
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "shift" => KeyModifiers::SHIFT,
+                "alt" => KeyModifiers::ALT,
+                _ => return None,
+            };
+        }
+        let code = match key {
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            _ if key.chars().count() == 1 => KeyCode::Char(key.chars().next()?),
+            _ => KeyCode::F(key.strip_prefix('f')?.parse().ok()?),
+        };
+        Some(Self { code, modifiers })
+    }
+
+    /// 判断按键事件是否匹配该快捷键，字符按键忽略大小写
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        if key.modifiers != self.modifiers {
+            return false;
+        }
+        match (self.code, key.code) {
+            (KeyCode::Char(expected), KeyCode::Char(actual)) => expected.eq_ignore_ascii_case(&actual),
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+impl Default for KeyBinding {
+    fn default() -> Self {
+        Self::parse(DEFAULT_COPY_REPLY_KEY).unwrap()
+    }
+}
+
+/// 将粘贴的文本转换为逐个字符的按键事件，换行转换为空格，避免粘贴多行文本时触发发送
+pub fn paste_key_events(text: &str) -> Vec<KeyEvent> {
+    text.chars()
+        .filter(|c| *c != '\r')
+        .map(|c| if c == '\n' || c == '\t' { ' ' } else { c })
+        .map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bindings() {
+        let binding = KeyBinding::parse("Ctrl+Shift+C").unwrap();
+        assert_eq!(binding.code, KeyCode::Char('c'));
+        assert_eq!(binding.modifiers, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        assert_eq!(KeyBinding::parse("alt+f2").unwrap().code, KeyCode::F(2));
+        assert_eq!(KeyBinding::parse(" ctrl + space ").unwrap().code, KeyCode::Char(' '));
+        assert_eq!(KeyBinding::parse("enter").unwrap().modifiers, KeyModifiers::NONE);
+        // 缺少按键、未知的修饰键或按键
+        assert!(KeyBinding::parse("ctrl+").is_none());
+        assert!(KeyBinding::parse("super+c").is_none());
+        assert!(KeyBinding::parse("ctrl+home").is_none());
+    }
+
+    #[test]
+    fn matches_ignores_char_case() {
+        let binding = KeyBinding::default();
+        let modifiers = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+        assert!(binding.matches(&KeyEvent::new(KeyCode::Char('C'), modifiers)));
+        assert!(!binding.matches(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+}
@@ -0,0 +1,127 @@
+/// 使用拉丁字母的语言及其常用词，用于区分这些语言
+const LATIN_STOPWORDS: [(&str, &[&str]); 6] = [
+    (
+        "English",
+        &[
+            "the", "and", "is", "are", "what", "how", "to", "of", "in", "you", "can", "this",
+        ],
+    ),
+    (
+        "Spanish",
+        &[
+            "el", "la", "los", "las", "que", "es", "por", "para", "cómo", "qué", "una", "con",
+        ],
+    ),
+    (
+        "French",
+        &[
+            "le", "la", "les", "est", "que", "pour", "une", "des", "dans", "comment", "vous", "avec",
+        ],
+    ),
+    (
+        "German",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ich", "wie", "mit", "ein", "eine", "zu",
+        ],
+    ),
+    (
+        "Portuguese",
+        &[
+            "o", "os", "que", "não", "para", "uma", "com", "como", "você", "é", "do", "da",
+        ],
+    ),
+    (
+        "Italian",
+        &[
+            "il", "che", "non", "per", "una", "sono", "come", "con", "della", "è", "gli", "di",
+        ],
+    ),
+];
+
+/// 非拉丁书写系统的一个字符大致相当于拉丁字母的数量，用于与拉丁字母的数量比较
+const NON_LATIN_WEIGHT: usize = 4;
+
+/// 根据书写系统与常用词推测文本的语言，返回英文的语言名称，代码块中的内容不参与判断，无法判断时返回 English
+pub fn detect_language(text: &str) -> &'static str {
+    let mut prose = String::new();
+    let mut in_code_block = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            prose.push_str(line);
+            prose.push('\n');
+        }
+    }
+    let (mut latin, mut kana) = (0, 0);
+    let mut scripts: Vec<(&'static str, usize)> = Vec::new();
+    for c in prose.chars() {
+        let script = match c as u32 {
+            0x3040..=0x30FF => {
+                kana += 1;
+                "Japanese"
+            }
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF => "Chinese",
+            0x1100..=0x11FF | 0xAC00..=0xD7AF => "Korean",
+            0x0400..=0x04FF => "Russian",
+            0x0600..=0x06FF => "Arabic",
+            0x0590..=0x05FF => "Hebrew",
+            0x0370..=0x03FF => "Greek",
+            0x0900..=0x097F => "Hindi",
+            0x0E00..=0x0E7F => "Thai",
+            _ => {
+                if c.is_alphabetic() {
+                    latin += 1;
+                }
+                continue;
+            }
+        };
+        match scripts.iter_mut().find(|(name, _)| *name == script) {
+            Some((_, count)) => *count += NON_LATIN_WEIGHT,
+            None => scripts.push((script, NON_LATIN_WEIGHT)),
+        }
+    }
+    // 日文中混有汉字，出现假名时将汉字计入日文
+    if kana > 0 {
+        let han = scripts
+            .iter()
+            .find(|(name, _)| *name == "Chinese")
+            .map_or(0, |(_, count)| *count);
+        scripts.retain(|(name, _)| *name != "Chinese");
+        if let Some((_, count)) = scripts.iter_mut().find(|(name, _)| *name == "Japanese") {
+            *count += han;
+        }
+    }
+    match scripts.into_iter().max_by_key(|(_, count)| *count) {
+        Some((script, count)) if count >= latin => script,
+        _ => detect_latin_language(&prose),
+    }
+}
+
+/// 根据常用词出现的次数区分使用拉丁字母的语言
+fn detect_latin_language(text: &str) -> &'static str {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    LATIN_STOPWORDS
+        .iter()
+        .map(|(language, stopwords)| {
+            let hits = words.iter().filter(|word| stopwords.contains(&word.as_str())).count();
+            (*language, hits)
+        })
+        // 出现次数相同时优先使用靠前的语言
+        .rev()
+        .max_by_key(|(_, hits)| *hits)
+        .map_or("English", |(language, _)| language)
+}
+
+/// 要求模型使用指定语言回复的提示，英文提示词默认得到英文回复，语言为 English 时返回空字符串
+pub fn reply_language_hint(language: &str) -> String {
+    if language == "English" {
+        String::new()
+    } else {
+        format!(" Reply in {}.", language)
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    ops::Range,
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::{bail, Result};
+
+/// 查找一行文本中的链接，返回每个链接的字节范围，链接末尾的标点不计入链接
+pub fn find_links(line: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut search_from = 0;
+    while let Some(start) = ["https://", "http://"]
+        .iter()
+        .filter_map(|scheme| line[search_from..].find(scheme))
+        .min()
+        .map(|index| search_from + index)
+    {
+        let rest = &line[start..];
+        let mut end = rest
+            .find(|c: char| c.is_whitespace() || "<>\"'`".contains(c))
+            .unwrap_or(rest.len());
+        // 去掉末尾的标点，括号只有在不成对时才去掉
+        while let Some(c) = rest[..end].chars().next_back() {
+            let unbalanced = match c {
+                ')' => rest[..end].matches('(').count() < rest[..end].matches(')').count(),
+                ']' => rest[..end].matches('[').count() < rest[..end].matches(']').count(),
+                _ => ".,;:!?*".contains(c),
+            };
+            if !unbalanced {
+                break;
+            }
+            end -= c.len_utf8();
+        }
+        // 只有协议头的不算链接
+        if rest[..end].ends_with("://") {
+            search_from = start + end;
+            continue;
+        }
+        ranges.push(start..start + end);
+        search_from = start + end;
+    }
+    ranges
+}
+
+/// 提取文本中的所有链接，重复的链接只保留第一个
+pub fn extract_links(text: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for line in text.lines() {
+        for range in find_links(line) {
+            let link = &line[range];
+            if !links.iter().any(|item| item == link) {
+                links.push(link.into());
+            }
+        }
+    }
+    links
+}
+
+/// 判断文本是否为可以打开的网页链接，只接受 `http://` 与 `https://` 开头且不含空白与控制字符的链接
+fn is_web_link(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    (lower.starts_with("https://") || lower.starts_with("http://"))
+        && !url.ends_with("://")
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// 使用系统默认浏览器打开链接，链接来自模型回复，只打开网页链接，且不经过 shell 启动，
+/// 避免链接中的 `&`、`|` 等字符被当作命令执行
+pub fn open_link(url: &str) -> Result<()> {
+    if !is_web_link(url) {
+        bail!("只能打开 http 或 https 链接：{}", url)
+    }
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("rundll32");
+        command.args(["url.dll,FileProtocolHandler", url]);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    };
+    // 不继承标准输入输出，避免破坏终端界面
+    let result = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match result {
+        Ok(mut child) => {
+            // 在后台等待进程退出，避免产生僵尸进程
+            thread::spawn(move || child.wait());
+            Ok(())
+        }
+        Err(e) => bail!("无法打开链接：{}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_web_links_are_opened() {
+        assert!(is_web_link("https://example.com/a?b=1&c=2"));
+        assert!(is_web_link("HTTP://example.com"));
+        assert!(!is_web_link("file:///etc/passwd"));
+        assert!(!is_web_link("javascript:alert(1)"));
+        assert!(!is_web_link("https://"));
+        assert!(!is_web_link("https://example.com\n&calc"));
+        assert!(open_link("ms-settings:").is_err());
+    }
+
+    #[test]
+    fn links_in_line() {
+        assert_eq!(find_links("see https://example.com."), vec![4..23]);
+        // 成对的括号属于链接，链接外的括号不属于
+        assert_eq!(
+            find_links("(https://en.wikipedia.org/wiki/Rust_(language))"),
+            vec![1..46]
+        );
+        assert_eq!(find_links("http://a.com, https://b.com"), vec![0..12, 14..27]);
+        assert_eq!(find_links("no links here"), vec![]);
+    }
+}
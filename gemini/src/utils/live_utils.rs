@@ -0,0 +1,176 @@
+use std::{
+    io::ErrorKind,
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use tungstenite::{client_tls_with_config, stream::MaybeTlsStream, Error, Message, WebSocket};
+
+use super::model_utils::model_resource;
+
+/// 默认的 Live API 模型
+pub const DEFAULT_LIVE_MODEL: &str = "gemini-2.0-flash-exp";
+/// Live API 服务器
+const LIVE_HOST: &str = "generativelanguage.googleapis.com";
+/// Live API 的 WebSocket 路径
+const LIVE_PATH: &str = "/ws/google.ai.generativelanguage.v1beta.GenerativeService.BidiGenerateContent";
+/// 读取消息的超时时间，超时后返回以便调用方发送新的消息
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// 等待服务端确认会话配置的时间
+const SETUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Live API 服务端消息中与文本对话有关的事件
+#[derive(Debug, PartialEq)]
+pub enum LiveEvent {
+    /// 会话配置完成，可以开始发送消息
+    SetupComplete,
+    /// 模型回复的一段文本
+    Text(String),
+    /// 模型的回复被新的消息打断
+    Interrupted,
+    /// 模型本轮回复结束
+    TurnComplete,
+}
+
+/// 与 Live API 的双向流式会话，消息与回复在同一个 WebSocket 连接上同时收发
+pub struct LiveSession {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+impl LiveSession {
+    /// 连接 Live API 并发送会话配置，等待服务端确认后返回
+    pub fn connect(key: &str, model: &str, system_instruction: Option<&str>) -> Result<Self> {
+        let stream = TcpStream::connect((LIVE_HOST, 443))?;
+        let handle = stream.try_clone()?;
+        let url = format!("wss://{}{}?key={}", LIVE_HOST, LIVE_PATH, key);
+        let (socket, _) =
+            client_tls_with_config(url, stream, None, None).map_err(|e| anyhow!("连接 Live API 失败：{}", e))?;
+        // 握手完成后再设置读取超时，避免握手被超时打断
+        handle.set_read_timeout(Some(POLL_INTERVAL))?;
+        let mut session = LiveSession { socket };
+        session.send(setup_message(model, system_instruction))?;
+        let started_at = Instant::now();
+        while started_at.elapsed() < SETUP_TIMEOUT {
+            if session.poll()?.contains(&LiveEvent::SetupComplete) {
+                return Ok(session);
+            }
+        }
+        bail!("Live API 没有确认会话配置，请检查模型 {} 是否支持 Live API", model)
+    }
+
+    /// 发送一条文本消息，模型正在回复时会打断当前回复
+    pub fn send_text(&mut self, text: &str) -> Result<()> {
+        self.send(text_message(text))
+    }
+
+    /// 读取服务端消息，没有新消息时等待约 100 毫秒后返回空列表
+    pub fn poll(&mut self) -> Result<Vec<LiveEvent>> {
+        let message = match self.socket.read() {
+            Ok(message) => message,
+            Err(Error::Io(e)) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Ok(Vec::new())
+            }
+            Err(Error::ConnectionClosed | Error::AlreadyClosed) => bail!("Live API 连接已关闭"),
+            Err(e) => return Err(e.into()),
+        };
+        // 服务端以二进制帧发送 JSON
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Binary(bytes) => String::from_utf8(bytes)?,
+            Message::Close(frame) => bail!(
+                "Live API 连接已关闭：{}",
+                frame.map(|frame| frame.reason.into_owned()).unwrap_or_default()
+            ),
+            _ => return Ok(Vec::new()),
+        };
+        Ok(server_events(&serde_json::from_str(&text)?))
+    }
+
+    /// 关闭连接
+    pub fn close(mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+
+    fn send(&mut self, value: Value) -> Result<()> {
+        self.socket.send(Message::Text(value.to_string()))?;
+        Ok(())
+    }
+}
+
+/// 会话配置消息，只请求文本回复
+fn setup_message(model: &str, system_instruction: Option<&str>) -> Value {
+    let mut setup = json!({
+        "model": model_resource(model),
+        "generationConfig": { "responseModalities": ["TEXT"] },
+    });
+    if let Some(instruction) = system_instruction.filter(|instruction| !instruction.trim().is_empty()) {
+        setup["systemInstruction"] = json!({ "parts": [{ "text": instruction }] });
+    }
+    json!({ "setup": setup })
+}
+
+/// 用户的一轮文本消息
+fn text_message(text: &str) -> Value {
+    json!({
+        "clientContent": {
+            "turns": [{ "role": "user", "parts": [{ "text": text }] }],
+            "turnComplete": true,
+        }
+    })
+}
+
+/// 从服务端消息中取出事件，忽略工具调用等其他内容
+fn server_events(value: &Value) -> Vec<LiveEvent> {
+    let mut events = Vec::new();
+    if value.get("setupComplete").is_some() {
+        events.push(LiveEvent::SetupComplete);
+    }
+    let content = &value["serverContent"];
+    let texts = content["modelTurn"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part["text"].as_str())
+        .map(|text| LiveEvent::Text(text.to_string()));
+    events.extend(texts);
+    if content["interrupted"].as_bool() == Some(true) {
+        events.push(LiveEvent::Interrupted);
+    }
+    if content["turnComplete"].as_bool() == Some(true) {
+        events.push(LiveEvent::TurnComplete);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_from_server_messages() {
+        assert_eq!(
+            server_events(&json!({ "setupComplete": {} })),
+            vec![LiveEvent::SetupComplete]
+        );
+        let value = json!({
+            "serverContent": {
+                "modelTurn": { "parts": [{ "text": "Hel" }, { "text": "lo" }] },
+                "turnComplete": true,
+            }
+        });
+        assert_eq!(
+            server_events(&value),
+            vec![
+                LiveEvent::Text("Hel".into()),
+                LiveEvent::Text("lo".into()),
+                LiveEvent::TurnComplete
+            ]
+        );
+        let setup = setup_message("gemini-2.0-flash-exp", Some(" "));
+        assert_eq!(setup["setup"]["model"], "models/gemini-2.0-flash-exp");
+        assert!(setup["setup"].get("systemInstruction").is_none());
+    }
+}
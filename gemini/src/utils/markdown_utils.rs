@@ -0,0 +1,289 @@
+/// 代码块围栏
+pub const CODE_FENCE: &str = "```";
+
+/// 消息中的代码块
+#[derive(Debug, Clone, Default)]
+pub struct CodeBlock {
+    /// 声明的语言
+    pub language: String,
+    /// 文件名提示
+    pub file_name: Option<String>,
+    /// 代码内容
+    pub content: String,
+    /// 开始围栏所在行
+    pub start_line: usize,
+    /// 结束围栏所在行
+    pub end_line: usize,
+}
+
+impl CodeBlock {
+    /// 是否为统一格式的 diff 代码块，声明了其他语言的代码块不视为 diff
+    pub fn is_diff(&self) -> bool {
+        let first_line = self.content.lines().next().unwrap_or_default();
+        ["diff", "patch"].contains(&self.language.as_str())
+            || (self.language.is_empty() && ["--- ", "diff --git", "@@ "].iter().any(|p| first_line.starts_with(p)))
+    }
+}
+
+/// 消息中每一行的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// 普通文本
+    Text,
+    /// 代码块围栏
+    Fence,
+    /// 代码
+    Code,
+    /// diff 新增行
+    DiffAdd,
+    /// diff 删除行
+    DiffRemove,
+    /// diff 区块头
+    DiffHunk,
+}
+
+/// 计算消息中每一行的类型，返回值与 `message.lines()` 一一对应
+pub fn classify_lines(message: &str) -> Vec<LineKind> {
+    let lines: Vec<&str> = message.lines().collect();
+    let mut kinds = vec![LineKind::Text; lines.len()];
+    for block in parse_code_blocks(message) {
+        kinds[block.start_line] = LineKind::Fence;
+        kinds[block.end_line] = LineKind::Fence;
+        let is_diff = block.is_diff();
+        for index in block.start_line + 1..block.end_line {
+            let line = lines[index];
+            kinds[index] = if !is_diff {
+                LineKind::Code
+            } else if ["@@", "+++", "---"].iter().any(|p| line.starts_with(p)) {
+                LineKind::DiffHunk
+            } else if line.starts_with('+') {
+                LineKind::DiffAdd
+            } else if line.starts_with('-') {
+                LineKind::DiffRemove
+            } else {
+                LineKind::Code
+            };
+        }
+    }
+    kinds
+}
+
+/// 解析消息中的所有代码块，未闭合的代码块会被忽略
+pub fn parse_code_blocks(message: &str) -> Vec<CodeBlock> {
+    let lines: Vec<&str> = message.lines().collect();
+    let mut blocks = Vec::new();
+    let mut current: Option<(CodeBlock, Vec<&str>)> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        match current.take() {
+            None => {
+                if let Some(info) = trimmed.strip_prefix(CODE_FENCE) {
+                    let (language, file_name) = parse_info_string(info);
+                    // 围栏中没有文件名时，尝试从上一行中获取
+                    let file_name = file_name.or_else(|| {
+                        index
+                            .checked_sub(1)
+                            .and_then(|i| lines.get(i))
+                            .and_then(|line| file_name_of_line(line))
+                    });
+                    let block = CodeBlock {
+                        language,
+                        file_name,
+                        start_line: index,
+                        ..Default::default()
+                    };
+                    current = Some((block, Vec::new()));
+                }
+            }
+            Some((mut block, mut content)) => {
+                if trimmed.starts_with(CODE_FENCE) && trimmed.trim_end() == CODE_FENCE {
+                    block.end_line = index;
+                    block.content = content.join("\n");
+                    blocks.push(block);
+                } else {
+                    content.push(line);
+                    current = Some((block, content));
+                }
+            }
+        }
+    }
+    blocks
+}
+
+/// 解析围栏后的信息字符串，返回 (语言, 文件名)
+fn parse_info_string(info: &str) -> (String, Option<String>) {
+    let mut language = String::new();
+    let mut file_name = None;
+    for part in info
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .filter(|p| !p.is_empty())
+    {
+        if is_path_like(part) {
+            file_name.get_or_insert(part.to_string());
+        } else if language.is_empty() {
+            language = part.to_string();
+        }
+    }
+    (language, file_name)
+}
+
+/// 从代码块上一行中提取文件名，如 `**src/main.rs**` 或 `File: src/main.rs`
+fn file_name_of_line(line: &str) -> Option<String> {
+    let line = line.trim().trim_end_matches(':');
+    let line = line
+        .strip_prefix("File")
+        .or_else(|| line.strip_prefix("file"))
+        .unwrap_or(line);
+    let candidate = line.trim_matches(|c: char| c.is_whitespace() || "*`#:".contains(c));
+    if is_path_like(candidate) {
+        Some(candidate.to_string())
+    } else {
+        None
+    }
+}
+
+/// 判断字符串是否像一个文件路径
+fn is_path_like(s: &str) -> bool {
+    let file_name = s.rsplit(['/', '\\']).next().unwrap_or_default();
+    !s.is_empty()
+        && !s.contains(char::is_whitespace)
+        && file_name
+            .rsplit_once('.')
+            .is_some_and(|(stem, ext)| !stem.is_empty() && !ext.is_empty())
+}
+
+/// 消息中的 Markdown 表格
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownTable {
+    /// 表头
+    pub header: Vec<String>,
+    /// 数据行，每一行的列数与表头一致
+    pub rows: Vec<Vec<String>>,
+    /// 表头所在行
+    pub start_line: usize,
+    /// 最后一行数据所在行
+    pub end_line: usize,
+}
+
+/// 解析消息中代码块之外的所有管道表格
+pub fn parse_tables(message: &str) -> Vec<MarkdownTable> {
+    let lines: Vec<&str> = message.lines().collect();
+    let kinds = classify_lines(message);
+    let is_text_row = |index: usize| kinds[index] == LineKind::Text && is_table_row(lines[index]);
+    let mut tables = Vec::new();
+    let mut index = 0;
+    while index + 1 < lines.len() {
+        if !is_text_row(index) || !is_separator_row(lines[index + 1]) {
+            index += 1;
+            continue;
+        }
+        let header = split_row(lines[index]);
+        let mut rows = Vec::new();
+        let mut end = index + 1;
+        while end + 1 < lines.len() && is_text_row(end + 1) {
+            end += 1;
+            let mut row = split_row(lines[end]);
+            row.resize(header.len(), String::new());
+            rows.push(row);
+        }
+        tables.push(MarkdownTable {
+            header,
+            rows,
+            start_line: index,
+            end_line: end,
+        });
+        index = end + 1;
+    }
+    tables
+}
+
+/// 判断是否为表格行
+fn is_table_row(line: &str) -> bool {
+    let line = line.trim();
+    line.len() > 1 && line.starts_with('|')
+}
+
+/// 判断是否为表头分隔行，如 `|---|:---:|`
+fn is_separator_row(line: &str) -> bool {
+    is_table_row(line) && line.contains('-') && line.trim().chars().all(|c| "|-: ".contains(c))
+}
+
+/// 拆分表格行中的单元格
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_string_language_and_file() {
+        let expected = ("rust".to_string(), Some("src/main.rs".to_string()));
+        assert_eq!(parse_info_string("rust src/main.rs"), expected);
+        assert_eq!(parse_info_string("rust:src/main.rs"), expected);
+        assert_eq!(parse_info_string(" src/main.rs  rust "), expected);
+        assert_eq!(parse_info_string("python"), ("python".into(), None));
+        assert_eq!(parse_info_string(""), (String::new(), None));
+        // 只取第一个语言与第一个文件名
+        assert_eq!(
+            parse_info_string("toml Cargo.toml other.toml"),
+            ("toml".into(), Some("Cargo.toml".into()))
+        );
+    }
+
+    #[test]
+    fn split_row_cells() {
+        assert_eq!(split_row("| a | b |"), ["a", "b"]);
+        assert_eq!(split_row("  a |b  "), ["a", "b"]);
+        assert_eq!(split_row("| a || c |"), ["a", "", "c"]);
+        assert_eq!(split_row("|"), [""]);
+    }
+
+    #[test]
+    fn ragged_rows_follow_header() {
+        let message = "intro\n| name | 说明 |\n|:---|---:|\n| a | 中文字 | extra |\n| b |\nafter";
+        let tables = parse_tables(message);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].header, ["name", "说明"]);
+        assert_eq!(tables[0].rows, [vec!["a", "中文字"], vec!["b", ""]]);
+        assert_eq!((tables[0].start_line, tables[0].end_line), (1, 4));
+    }
+
+    #[test]
+    fn tables_need_separator_outside_code() {
+        assert!(parse_tables("```\n| a | b |\n| - | - |\n| 1 | 2 |\n```").is_empty());
+        assert!(parse_tables("| a | b |\n| 1 | 2 |").is_empty());
+        assert!(parse_tables("| a | b |").is_empty());
+        // 代码块之后的表格不受影响
+        let tables = parse_tables("```\n| x |\n```\n| a |\n| - |\n| 1 |");
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].start_line, 3);
+    }
+
+    #[test]
+    fn diff_lines_in_diff_blocks() {
+        use LineKind::*;
+        let message = "```diff\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1 +1 @@\n-old\n+new\n context\n```";
+        assert_eq!(
+            classify_lines(message),
+            [Fence, DiffHunk, DiffHunk, DiffHunk, DiffRemove, DiffAdd, Code, Fence]
+        );
+        // 未声明语言时根据首行识别
+        assert_eq!(classify_lines("```\n@@ -1 +1 @@\n+new\n```")[2], DiffAdd);
+    }
+
+    #[test]
+    fn diff_like_lines_outside_diff_blocks() {
+        use LineKind::*;
+        // 代码块之外的分隔线与列表项
+        let message = "---\n+++ bold\n- item\n+ item\n--- a/x\n@@ -1 +1 @@";
+        assert_eq!(classify_lines(message), [Text; 6]);
+        // 其他语言的代码块
+        assert_eq!(classify_lines("```markdown\n- item\n+ item\n```")[1..3], [Code, Code]);
+        assert_eq!(classify_lines("```yaml\n--- \n- a\n```")[1..3], [Code, Code]);
+    }
+}
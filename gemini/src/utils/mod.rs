@@ -1,4 +1,38 @@
+pub(crate) mod attachment_utils;
+pub(crate) mod batch_utils;
 pub(crate) mod char_utils;
+pub(crate) mod client_utils;
+pub(crate) mod clipboard_utils;
 pub(crate) mod db_utils;
+pub(crate) mod editor_utils;
+pub(crate) mod error_utils;
+pub(crate) mod export_utils;
+pub(crate) mod git_utils;
+pub(crate) mod grounding_utils;
+pub(crate) mod http_utils;
+pub(crate) mod image_gen_utils;
 pub(crate) mod image_utils;
+pub(crate) mod import_utils;
+pub(crate) mod key_utils;
+pub(crate) mod language_utils;
+pub(crate) mod link_utils;
+pub(crate) mod live_utils;
+pub(crate) mod markdown_utils;
+pub(crate) mod model_utils;
+pub(crate) mod path_utils;
+pub(crate) mod quota_utils;
+pub(crate) mod rate_limit_utils;
+pub(crate) mod retention_utils;
+pub(crate) mod schedule_utils;
+pub(crate) mod screenshot_utils;
+pub(crate) mod session_utils;
+pub(crate) mod share_utils;
 pub(crate) mod store_utils;
+pub(crate) mod suggest_utils;
+pub(crate) mod template_utils;
+pub(crate) mod time_utils;
+pub(crate) mod tool_utils;
+pub(crate) mod translate_utils;
+pub(crate) mod tts_utils;
+pub(crate) mod usage_utils;
+pub(crate) mod voice_utils;
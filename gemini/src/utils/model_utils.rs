@@ -0,0 +1,326 @@
+use anyhow::{bail, Result};
+use gemini_api::{
+    body::{request::GenerationConfig, Content, Part, Role},
+    model::blocking::Gemini,
+};
+use serde_json::{json, Value};
+
+use crate::model::view::ChatReply;
+
+use super::{
+    attachment_utils::save_response_attachments,
+    http_utils::{post_json, send_json, CLIENT},
+    session_utils::SendOptions,
+    tool_utils::{function_declarations, parse_function_call, tool_call_message, tool_output_message, ToolCall},
+    usage_utils::track_usage_metadata,
+};
+
+/// Gemini API 地址
+pub const BASE_URL: &str = "https://generativelanguage.googleapis.com";
+/// 微调模型名称的前缀
+const TUNED_MODEL_PREFIX: &str = "tunedModels/";
+
+/// 查询密钥可用的对话模型名称，只保留支持 generateContent 的模型，按接口返回的顺序排列
+pub fn list_models(key: &str) -> Result<Vec<String>> {
+    list_pages(key, "models", |model| {
+        let methods = model["supportedGenerationMethods"].as_array()?;
+        if !methods.iter().any(|method| method == "generateContent") {
+            return None;
+        }
+        let name = model["name"].as_str()?;
+        Some(name.strip_prefix("models/").unwrap_or(name).to_string())
+    })
+}
+
+/// 查询已训练完成的微调模型，名称保留 `tunedModels/` 前缀，以便与基础模型区分
+pub fn list_tuned_models(key: &str) -> Result<Vec<String>> {
+    list_pages(key, "tunedModels", |model| {
+        (model["state"] == "ACTIVE").then(|| model["name"].as_str().map(String::from))?
+    })
+}
+
+/// 逐页查询模型列表，`collection` 为接口中的集合名称，`select` 从每个模型中取出需要的名称
+fn list_pages(key: &str, collection: &str, select: impl Fn(&Value) -> Option<String>) -> Result<Vec<String>> {
+    let mut models = Vec::new();
+    let mut page_token = String::new();
+    loop {
+        let url = format!(
+            "{}/v1beta/{}?pageSize=1000&pageToken={}&key={}",
+            BASE_URL, collection, page_token, key
+        );
+        let value = send_json(CLIENT.get(url), "查询模型列表")?;
+        let page = value[collection].as_array().cloned().unwrap_or_default();
+        models.extend(page.iter().filter_map(&select));
+        match value["nextPageToken"].as_str() {
+            Some(token) if !token.is_empty() => page_token = token.into(),
+            _ => break,
+        }
+    }
+    Ok(models)
+}
+
+/// 判断模型名称是否为 `tunedModels/<id>` 形式的微调模型
+pub fn is_tuned_model(model: &str) -> bool {
+    model.starts_with(TUNED_MODEL_PREFIX)
+}
+
+/// 模型在接口地址中的资源名，微调模型原样使用，其他模型加上 `models/` 前缀
+pub fn model_resource(model: &str) -> String {
+    if is_tuned_model(model) {
+        model.to_string()
+    } else {
+        format!("models/{}", model.strip_prefix("models/").unwrap_or(model))
+    }
+}
+
+/// 将上下文与新问题转换为接口使用的 JSON，只保留非空的文本内容，没有文本的消息会被跳过
+pub fn text_contents(contents: &[Content], question: &Content) -> Vec<Value> {
+    contents.iter().chain([question]).filter_map(text_content).collect()
+}
+
+/// 将一条消息转换为接口使用的 JSON，只保留非空的文本内容，没有文本时返回 None
+fn text_content(content: &Content) -> Option<Value> {
+    let parts: Vec<Value> = content
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Text(text) if !text.is_empty() => Some(json!({ "text": text })),
+            _ => None,
+        })
+        .collect();
+    let role = if matches!(content.role, Some(Role::Model)) {
+        "model"
+    } else {
+        "user"
+    };
+    (!parts.is_empty()).then(|| json!({ "role": role, "parts": parts }))
+}
+
+/// 使用客户端的模型、生成参数与系统指令调用 generateContent 接口，`body` 中需要已包含 `contents`，
+/// 请求失败时以 `action` 描述错误，成功后记录用量并返回接口的响应
+pub fn generate_content(gemini: &Gemini, body: Value, action: &str) -> Result<Value> {
+    let body = request_body(body, &gemini.options, gemini.system_instruction.as_deref())?;
+    call_model(&gemini.key, &gemini.model.to_string(), "generateContent", &body, action)
+}
+
+/// 在请求体中加入生成参数与系统指令，请求体中已有的生成参数（如输出类型）优先，系统指令为空时不发送
+pub fn request_body(mut body: Value, options: &GenerationConfig, system_instruction: Option<&str>) -> Result<Value> {
+    let mut config = serde_json::to_value(options)?;
+    if let (Some(config), Some(extra)) = (config.as_object_mut(), body["generationConfig"].as_object()) {
+        config.extend(extra.clone());
+    }
+    body["generationConfig"] = config;
+    if let Some(system_instruction) = system_instruction.filter(|s| !s.is_empty()) {
+        body["systemInstruction"] = json!({ "parts": [{ "text": system_instruction }] });
+    }
+    Ok(body)
+}
+
+/// 调用模型的接口方法，如 `generateContent`、`predict`，TUI 中直接调用接口的请求都经过这里，
+/// 请求失败时以 `action` 描述错误，成功后记录用量并返回接口的响应
+pub fn call_model(key: &str, model: &str, method: &str, body: &Value, action: &str) -> Result<Value> {
+    let url = format!("{}/v1beta/{}:{}?key={}", BASE_URL, model_resource(model), method, key);
+    let value = post_json(url, body, action)?;
+    track_usage_metadata(model, &value);
+    Ok(value)
+}
+
+/// 通过接口直接发送消息，用于微调模型、多模态输出、思考模型与本地工具，上下文中只发送文本，
+/// `modalities` 不为空时要求模型按指定类型输出，回复中的图片、音频等内容保存为附件，
+/// `thinking_budget` 不为空时设置思考预算并要求返回思考摘要，思考摘要与回复分开返回，
+/// 配置了本地工具时以函数声明发送，成功后将问答的文本加入客户端的上下文
+pub fn send_rest_message(gemini: &mut Gemini, message: String, options: &SendOptions) -> Result<ChatReply> {
+    let question = Content {
+        parts: vec![Part::Text(message)],
+        role: Some(Role::User),
+    };
+    let contents = text_contents(&gemini.contents, &question);
+    let reply = send_rest_contents(gemini, contents, options)?;
+    gemini.contents.push(question);
+    gemini.contents.push(Content {
+        parts: vec![Part::Text(reply.text.clone())],
+        role: Some(Role::Model),
+    });
+    Ok(reply)
+}
+
+/// 将本地工具的执行结果作为函数响应发送给模型，上下文的最后一条须为请求调用该工具的回复，
+/// 上下文中只保存文本，工具调用与执行结果在之后的请求中以 [`tool_call_message`] 与
+/// [`tool_output_message`] 的文本发送
+pub fn send_tool_response(
+    gemini: &mut Gemini,
+    call: &ToolCall,
+    output: String,
+    options: &SendOptions,
+) -> Result<ChatReply> {
+    let contents = tool_response_contents(&gemini.contents, call, &output)?;
+    let reply = send_rest_contents(gemini, contents, options)?;
+    gemini.contents.push(Content {
+        parts: vec![Part::Text(tool_output_message(&call.name, &output))],
+        role: Some(Role::User),
+    });
+    gemini.contents.push(Content {
+        parts: vec![Part::Text(reply.text.clone())],
+        role: Some(Role::Model),
+    });
+    Ok(reply)
+}
+
+/// 工具响应请求的 `contents`，上下文中最后一条回复替换为函数调用，之后追加函数响应
+fn tool_response_contents(contents: &[Content], call: &ToolCall, output: &str) -> Result<Vec<Value>> {
+    let history = match contents.split_last() {
+        Some((last, history)) if matches!(last.role, Some(Role::Model)) => history,
+        _ => bail!("上下文中没有调用工具 {} 的回复", call.name),
+    };
+    let mut contents: Vec<Value> = history.iter().filter_map(text_content).collect();
+    contents.push(json!({ "role": "model", "parts": [{ "functionCall": call }] }));
+    contents.push(json!({
+        "role": "user",
+        "parts": [{ "functionResponse": { "name": call.name, "response": { "output": output } } }],
+    }));
+    Ok(contents)
+}
+
+/// 按发送选项发送 `contents` 并解析回复，只请求调用工具时以 [`tool_call_message`] 作为回复文本
+fn send_rest_contents(gemini: &Gemini, contents: Vec<Value>, options: &SendOptions) -> Result<ChatReply> {
+    let mut body = json!({ "contents": contents, "generationConfig": {} });
+    if !options.modalities.is_empty() {
+        body["generationConfig"]["responseModalities"] = json!(options.modalities);
+    }
+    if let Some(budget) = options.thinking_budget {
+        body["generationConfig"]["thinkingConfig"] = json!({ "thinkingBudget": budget, "includeThoughts": true });
+    }
+    if !options.tools.is_empty() {
+        body["tools"] = function_declarations(&options.tools);
+    }
+    let value = generate_content(gemini, body, "发送消息")?;
+    let (mut answer, reasoning) = response_texts(&value);
+    let attachments = save_response_attachments(&value)?;
+    let tool_call = parse_function_call(&value);
+    if let Some(call) = tool_call.as_ref().filter(|_| answer.trim().is_empty()) {
+        answer = tool_call_message(call);
+    }
+    if answer.is_empty() && attachments.is_empty() {
+        bail!("回复为空")
+    }
+    Ok(ChatReply {
+        text: answer,
+        reasoning: (!reasoning.trim().is_empty()).then(|| reasoning.trim().to_string()),
+        attachments,
+        tool_call,
+        citations: None,
+    })
+}
+
+/// 取出回复中的文本，返回 (回复, 思考摘要)，`thought` 为 true 的部分为思考摘要
+fn response_texts(value: &Value) -> (String, String) {
+    let (mut answer, mut reasoning) = (String::new(), String::new());
+    for part in value["candidates"][0]["content"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        if let Some(text) = part["text"].as_str() {
+            if part["thought"].as_bool() == Some(true) {
+                reasoning.push_str(text);
+            } else {
+                answer.push_str(text);
+            }
+        }
+    }
+    (answer, reasoning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_instruction_round_trip() {
+        let body = json!({ "contents": [] });
+        let body = request_body(body, &GenerationConfig::default(), Some("Be brief.")).unwrap();
+        let parsed: Value = serde_json::from_str(&body.to_string()).unwrap();
+        assert_eq!(parsed["systemInstruction"]["parts"][0]["text"], "Be brief.");
+        let body = request_body(json!({ "contents": [] }), &GenerationConfig::default(), Some("")).unwrap();
+        assert!(body.get("systemInstruction").is_none());
+    }
+
+    #[test]
+    fn request_generation_config_is_kept() {
+        let body = json!({ "generationConfig": { "responseModalities": ["TEXT", "AUDIO"] } });
+        let body = request_body(body, &GenerationConfig::default(), None).unwrap();
+        assert_eq!(body["generationConfig"]["responseModalities"], json!(["TEXT", "AUDIO"]));
+    }
+
+    #[test]
+    fn contents_keep_roles_and_text() {
+        let history = [
+            Content {
+                parts: vec![Part::Text("hi".into())],
+                role: Some(Role::User),
+            },
+            Content {
+                parts: vec![Part::Text(String::new())],
+                role: Some(Role::Model),
+            },
+        ];
+        let question = Content {
+            parts: vec![Part::Text("again".into())],
+            role: Some(Role::User),
+        };
+        assert_eq!(
+            text_contents(&history, &question),
+            vec![
+                json!({ "role": "user", "parts": [{ "text": "hi" }] }),
+                json!({ "role": "user", "parts": [{ "text": "again" }] }),
+            ]
+        );
+    }
+
+    #[test]
+    fn tuned_model_resources() {
+        assert_eq!(model_resource("gemini-1.5-flash"), "models/gemini-1.5-flash");
+        assert_eq!(model_resource("models/gemini-1.5-flash"), "models/gemini-1.5-flash");
+        assert_eq!(model_resource("tunedModels/my-model"), "tunedModels/my-model");
+    }
+
+    #[test]
+    fn thoughts_are_separated() {
+        let value = json!({ "candidates": [{ "content": { "parts": [
+            { "text": "Considering the question.", "thought": true },
+            { "text": "The answer" },
+            { "text": " is 42." },
+        ] } }] });
+        assert_eq!(
+            response_texts(&value),
+            ("The answer is 42.".to_string(), "Considering the question.".to_string())
+        );
+    }
+
+    #[test]
+    fn tool_response_replaces_calling_reply() {
+        let call = ToolCall {
+            name: "list_dir".into(),
+            args: json!({ "path": "/tmp" }).as_object().cloned().unwrap(),
+        };
+        let history = [
+            Content {
+                parts: vec![Part::Text("What is in /tmp?".into())],
+                role: Some(Role::User),
+            },
+            Content {
+                parts: vec![Part::Text(tool_call_message(&call))],
+                role: Some(Role::Model),
+            },
+        ];
+        let contents = tool_response_contents(&history, &call, "a.txt\n").unwrap();
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[1]["parts"][0]["functionCall"]["args"]["path"], "/tmp");
+        assert_eq!(contents[2]["parts"][0]["functionResponse"]["name"], "list_dir");
+        assert_eq!(
+            contents[2]["parts"][0]["functionResponse"]["response"]["output"],
+            "a.txt\n"
+        );
+        assert!(tool_response_contents(&history[..1], &call, "").is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    env, fs,
+    path::{Path, MAIN_SEPARATOR},
+};
+
+use anyhow::Result;
+
+/// 将路径开头的 `~` 替换为用户主目录
+pub fn expand_tilde(path: &str) -> String {
+    let Some(rest) = path.strip_prefix('~') else {
+        return path.into();
+    };
+    if !rest.is_empty() && !rest.starts_with(['/', '\\']) {
+        return path.into();
+    }
+    match env::var("HOME").or_else(|_| env::var("USERPROFILE")) {
+        Ok(home) => format!("{}{}", home, rest),
+        Err(_) => path.into(),
+    }
+}
+
+/// 补全路径，返回补全后的路径以及所有候选项名称，
+/// 只有一个候选项时补全为完整名称，目录会追加分隔符，多个候选项时补全为公共前缀
+pub fn complete_path(input: &str) -> (String, Vec<String>) {
+    let path = expand_tilde(input);
+    // 拆分为所在目录与待补全的文件名前缀
+    let split = path.rfind(['/', '\\']).map(|index| index + 1).unwrap_or_default();
+    let (dir, prefix) = path.split_at(split);
+    let Ok(entries) = list_dir(if dir.is_empty() { "." } else { dir }) else {
+        return (path, vec![]);
+    };
+    let candidates: Vec<(String, bool)> = entries
+        .into_iter()
+        .filter(|(name, _)| name != "..")
+        // 没有输入 `.` 时不显示隐藏文件
+        .filter(|(name, _)| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    match candidates.as_slice() {
+        [] => (path, vec![]),
+        [(name, is_dir)] => {
+            let separator = if *is_dir {
+                MAIN_SEPARATOR.to_string()
+            } else {
+                String::new()
+            };
+            (format!("{}{}{}", dir, name, separator), vec![])
+        }
+        _ => {
+            let names: Vec<String> = candidates.into_iter().map(|(name, _)| name).collect();
+            let common = common_prefix(&names);
+            (format!("{}{}", dir, common), names)
+        }
+    }
+}
+
+/// 列出目录下的文件，返回名称以及是否为目录，目录排在前面，开头包含上级目录 `..`
+pub fn list_dir(dir: &str) -> Result<Vec<(String, bool)>> {
+    let mut entries: Vec<(String, bool)> = fs::read_dir(expand_tilde(dir))?
+        .flatten()
+        .map(|entry| {
+            let is_dir = entry.path().is_dir();
+            (entry.file_name().to_string_lossy().into_owned(), is_dir)
+        })
+        .collect();
+    entries.sort_by(|(a, a_dir), (b, b_dir)| b_dir.cmp(a_dir).then_with(|| a.cmp(b)));
+    entries.insert(0, ("..".into(), true));
+    Ok(entries)
+}
+
+/// 多个名称的最长公共前缀
+fn common_prefix(names: &[String]) -> String {
+    let Some(first) = names.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for name in &names[1..] {
+        while !name.starts_with(prefix) {
+            let mut chars = prefix.chars();
+            chars.next_back();
+            prefix = chars.as_str();
+        }
+    }
+    prefix.into()
+}
+
+/// 解析粘贴的文本，文本为已存在的文件路径或 `file://` URI 时返回文件路径，
+/// 文件管理器拖放的路径可能带有引号或转义的空格
+pub fn pasted_file_path(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.contains('\n') {
+        return None;
+    }
+    let text = ['\'', '"']
+        .iter()
+        .find_map(|quote| text.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(text);
+    let path = match text.strip_prefix("file://") {
+        Some(uri) => {
+            // 跳过主机名，`file://host/path` 与 `file:///path` 均指向本机路径
+            let path = percent_decode(&uri[uri.find('/')?..]);
+            // Windows 路径形如 `/C:/Users/...`
+            match path.as_bytes() {
+                [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+                _ => path,
+            }
+        }
+        None => expand_tilde(&text.replace("\\ ", " ")),
+    };
+    Path::new(&path).is_file().then_some(path)
+}
+
+/// 解码 URI 中的百分号编码
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,52 @@
+use anyhow::{bail, Result};
+use gemini_api::body::request::GenerationConfig;
+use serde_json::Value;
+
+use super::{http_utils::CLIENT, model_utils::model_resource};
+
+/// 检查密钥能否使用配置的模型与生成参数，返回需要提醒用户的问题
+///
+/// 接口不会返回密钥属于免费层级还是付费层级，因此以模型对该密钥公开的信息为准：
+/// 模型对该密钥不可用、最大输出长度或温度超出模型上限时给出提醒，避免第一次发送消息时才失败
+pub fn check_key_limits(key: &str, model: &str, options: &GenerationConfig) -> Result<Vec<String>> {
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/{}?key={}",
+        model_resource(model),
+        key
+    );
+    let response = CLIENT.get(url).send()?;
+    let status = response.status();
+    let text = response.text()?;
+    if text.contains("API_KEY_INVALID") {
+        bail!("密钥无效")
+    }
+    if !status.is_success() {
+        return Ok(vec![format!(
+            "Model {} is not available for this key ({})",
+            model, status
+        )]);
+    }
+    let value: Value = serde_json::from_str(&text)?;
+    let mut warnings = Vec::new();
+    if let (Some(max_output_tokens), Some(limit)) = (options.max_output_tokens, value["outputTokenLimit"].as_u64()) {
+        if max_output_tokens as u64 > limit {
+            warnings.push(format!(
+                "Max output tokens {} exceeds the {} limit of {}",
+                max_output_tokens, model, limit
+            ));
+        }
+    }
+    if let (Some(temperature), Some(limit)) = (options.temperature, value["maxTemperature"].as_f64()) {
+        if temperature as f64 > limit {
+            warnings.push(format!(
+                "Temperature {} exceeds the {} limit of {}",
+                temperature, model, limit
+            ));
+        }
+    }
+    // 模型信息中没有 topK 时表示该模型不使用 top-k 采样
+    if options.top_k.is_some_and(|top_k| top_k > 0) && value["topK"].is_null() {
+        warnings.push(format!("Top k is ignored by {}", model));
+    }
+    Ok(warnings)
+}
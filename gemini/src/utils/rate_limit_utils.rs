@@ -0,0 +1,118 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{LazyLock, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// 单个模型的速率限制，未配置的项不限制
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug)]
+pub struct RateLimit {
+    /// 每分钟请求次数上限
+    #[serde(default)]
+    pub rpm: Option<usize>,
+    /// 每分钟 token 数上限
+    #[serde(default)]
+    pub tpm: Option<i64>,
+}
+
+/// 速率限制的统计窗口
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// 最近一分钟内的请求时间与估算的 token 数，按时间先后排列
+type Requests = VecDeque<(Instant, i64)>;
+
+/// 各模型最近一分钟内的请求，所有会话与标签页共用
+static HISTORY: LazyLock<Mutex<HashMap<String, Requests>>> = LazyLock::new(Default::default);
+
+/// 在发送请求前按速率限制等待，等待期间约每秒调用一次 `on_wait` 汇报剩余秒数，
+/// 单个请求的 token 数超过每分钟上限时直接失败，等待结束后登记本次请求
+pub fn acquire(model: &str, limit: RateLimit, tokens: i64, mut on_wait: impl FnMut(u64)) -> Result<()> {
+    if limit.rpm.is_none() && limit.tpm.is_none() {
+        return Ok(());
+    }
+    loop {
+        let wait = {
+            let mut history = HISTORY.lock().unwrap();
+            let requests = history.entry(model.to_string()).or_default();
+            let now = Instant::now();
+            while requests.front().is_some_and(|(time, _)| now - *time >= WINDOW) {
+                requests.pop_front();
+            }
+            match wait_time(requests, limit, tokens, now)? {
+                Some(wait) => wait,
+                None => {
+                    requests.push_back((now, tokens));
+                    return Ok(());
+                }
+            }
+        };
+        on_wait(wait.as_secs() + u64::from(wait.subsec_nanos() > 0));
+        thread::sleep(wait.min(Duration::from_secs(1)));
+    }
+}
+
+/// 计算请求还需要等待的时间，不需要等待时返回 None
+fn wait_time(requests: &Requests, limit: RateLimit, tokens: i64, now: Instant) -> Result<Option<Duration>> {
+    // 等到第 index 个请求移出统计窗口
+    let expire = |index: usize| (requests[index].0 + WINDOW).saturating_duration_since(now);
+    let mut wait = Duration::ZERO;
+    if let Some(rpm) = limit.rpm.filter(|rpm| *rpm > 0) {
+        if requests.len() >= rpm {
+            wait = wait.max(expire(requests.len() - rpm));
+        }
+    }
+    if let Some(tpm) = limit.tpm.filter(|tpm| *tpm > 0) {
+        if tokens > tpm {
+            bail!("请求约 {} 个 token，超过每分钟 {} 个 token 的限制", tokens, tpm)
+        }
+        let mut used: i64 = requests.iter().map(|(_, tokens)| tokens).sum();
+        for (index, (_, request_tokens)) in requests.iter().enumerate() {
+            if used + tokens <= tpm {
+                break;
+            }
+            used -= request_tokens;
+            wait = wait.max(expire(index));
+        }
+    }
+    Ok((!wait.is_zero()).then_some(wait))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_oldest_request() {
+        let now = Instant::now();
+        let requests = VecDeque::from([
+            (now - Duration::from_secs(50), 100),
+            (now - Duration::from_secs(10), 100),
+        ]);
+        let rpm = RateLimit {
+            rpm: Some(2),
+            tpm: None,
+        };
+        assert_eq!(
+            wait_time(&requests, rpm, 10, now).unwrap(),
+            Some(Duration::from_secs(10))
+        );
+        let tpm = RateLimit {
+            rpm: None,
+            tpm: Some(250),
+        };
+        assert_eq!(wait_time(&requests, tpm, 50, now).unwrap(), None);
+        assert_eq!(
+            wait_time(&requests, tpm, 100, now).unwrap(),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            wait_time(&requests, tpm, 200, now).unwrap(),
+            Some(Duration::from_secs(50))
+        );
+        assert!(wait_time(&requests, tpm, 300, now).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::cmp::Reverse;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::model::db::Conversation;
+
+use super::{
+    db_utils::{delete_one, query_all},
+    store_utils::read_config,
+};
+
+/// 会话保留策略，两项都未配置时不删除任何会话，已锁定或置顶的会话总是保留
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct RetentionConfig {
+    /// 删除超过该天数未更新的会话，为 0 时不按天数删除
+    #[serde(default)]
+    pub days: Option<u32>,
+    /// 只保留最近更新的该数量的会话，已锁定或置顶的会话不计入
+    #[serde(default)]
+    pub conversations: Option<usize>,
+}
+
+/// 按配置文件中的保留策略删除旧会话，返回删除的会话数量
+pub fn apply_retention() -> Result<usize> {
+    let config = read_config().map(|store_data| store_data.retention).unwrap_or_default();
+    let expired = expired_conversations(query_all()?, &config, Local::now());
+    let count = expired.len();
+    for conversation in expired {
+        delete_one(conversation)?;
+    }
+    Ok(count)
+}
+
+/// 按保留策略选出需要删除的会话，天数为 0 时视为未配置
+fn expired_conversations(
+    conversations: Vec<Conversation>,
+    config: &RetentionConfig,
+    now: DateTime<Local>,
+) -> Vec<Conversation> {
+    let days = config.days.filter(|days| *days > 0);
+    if days.is_none() && config.conversations.is_none() {
+        return Vec::new();
+    }
+    let expire_time = days.map(|days| now - Duration::days(days.into()));
+    let keep = config.conversations.unwrap_or(usize::MAX);
+    // 未锁定且未置顶的会话按更新时间倒序排列，跳过最近的 keep 个
+    let mut candidates: Vec<_> = conversations
+        .into_iter()
+        .filter(|conversation| !conversation.locked && conversation.pin_order.is_none())
+        .collect();
+    candidates.sort_by_key(|conversation| Reverse(conversation.conversation_modify_time));
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(index, conversation)| {
+            *index >= keep || expire_time.is_some_and(|time| conversation.conversation_modify_time < time)
+        })
+        .map(|(_, conversation)| conversation)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 生成 age_days 天前更新的会话
+    fn conversation(id: &str, age_days: i64, locked: bool, pin_order: Option<i64>) -> Conversation {
+        let time = Local::now() - Duration::days(age_days);
+        Conversation {
+            conversation_id: id.into(),
+            conversation_title: id.into(),
+            conversation_start_time: time,
+            conversation_modify_time: time,
+            last_message: String::new(),
+            locked,
+            pin_order,
+            conversation_records: Vec::new(),
+        }
+    }
+
+    fn expired_ids(config: RetentionConfig) -> Vec<String> {
+        let conversations = vec![
+            conversation("pinned", 90, false, Some(0)),
+            conversation("new", 1, false, None),
+            conversation("locked", 80, true, None),
+            conversation("old", 60, false, None),
+            conversation("middle", 10, false, None),
+        ];
+        expired_conversations(conversations, &config, Local::now())
+            .into_iter()
+            .map(|conversation| conversation.conversation_id)
+            .collect()
+    }
+
+    #[test]
+    fn locked_and_pinned_are_kept() {
+        let config = RetentionConfig {
+            days: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(expired_ids(config), ["old"]);
+        let config = RetentionConfig {
+            conversations: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(expired_ids(config), ["new", "middle", "old"]);
+    }
+
+    #[test]
+    fn keep_counts_unpinned_only() {
+        // 置顶与锁定的会话不占用保留数量
+        let config = RetentionConfig {
+            conversations: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(expired_ids(config), ["old"]);
+        let config = RetentionConfig {
+            days: Some(5),
+            conversations: Some(2),
+        };
+        assert_eq!(expired_ids(config), ["middle", "old"]);
+    }
+
+    #[test]
+    fn zero_days_disables_expiry() {
+        assert!(expired_ids(RetentionConfig::default()).is_empty());
+        let config = RetentionConfig {
+            days: Some(0),
+            ..Default::default()
+        };
+        assert!(expired_ids(config).is_empty());
+        let config = RetentionConfig {
+            days: Some(0),
+            conversations: Some(2),
+        };
+        assert_eq!(expired_ids(config), ["old"]);
+    }
+}
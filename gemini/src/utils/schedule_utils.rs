@@ -0,0 +1,140 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeDelta};
+use gemini_api::{model::blocking::Gemini, param::LanguageModel};
+
+use crate::model::db::Schedule;
+
+use super::db_utils::{query_schedules, save_schedule};
+use super::usage_utils::track_usage;
+
+/// 解析首次执行时间，支持 `HH:MM`（今天或明天的该时刻）与 `YYYY-MM-DD HH:MM`，为空时立即执行
+pub fn parse_start_time(text: &str) -> Result<DateTime<Local>> {
+    let text = text.trim();
+    let now = Local::now();
+    if text.is_empty() {
+        return Ok(now);
+    }
+    if let Ok(time) = NaiveTime::parse_from_str(text, "%H:%M") {
+        let today = now
+            .date_naive()
+            .and_time(time)
+            .and_local_timezone(Local)
+            .single()
+            .context("无效的时间")?;
+        return Ok(if today > now { today } else { today + TimeDelta::days(1) });
+    }
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+        .ok()
+        .and_then(|time| time.and_local_timezone(Local).single())
+        .context("时间格式应为 HH:MM 或 YYYY-MM-DD HH:MM")
+}
+
+/// 解析重复间隔，返回分钟数，支持 `30m`、`2h`、`1d`，为空时只执行一次
+pub fn parse_repeat(text: &str) -> Result<i64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0);
+    }
+    let unit = text.chars().last().unwrap_or_default();
+    let number = match text[..text.len() - unit.len_utf8()].parse::<i64>() {
+        Ok(number) if number >= 0 => number,
+        _ => bail!("重复间隔格式应为 30m、2h 或 1d"),
+    };
+    match unit {
+        'm' => Ok(number),
+        'h' => Ok(number * 60),
+        'd' => Ok(number * 60 * 24),
+        _ => bail!("重复间隔格式应为 30m、2h 或 1d"),
+    }
+}
+
+/// 将分钟数格式化为重复间隔描述
+pub fn format_repeat(minutes: i64) -> String {
+    if minutes <= 0 {
+        "once".into()
+    } else if minutes % (60 * 24) == 0 {
+        format!("{}d", minutes / 60 / 24)
+    } else if minutes % 60 == 0 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// 取出到期的定时任务，并更新其下一次执行时间，只执行一次的任务执行后停用
+pub fn take_due_schedules() -> Result<Vec<Schedule>> {
+    let now = Local::now();
+    let mut due = Vec::new();
+    for mut schedule in query_schedules()? {
+        if !schedule.enabled || schedule.next_run_time > now {
+            continue;
+        }
+        due.push(schedule.clone());
+        if schedule.repeat_minutes > 0 {
+            // 跳过程序未运行期间错过的执行时间
+            while schedule.next_run_time <= now {
+                schedule.next_run_time += TimeDelta::minutes(schedule.repeat_minutes);
+            }
+        } else {
+            schedule.enabled = false;
+        }
+        save_schedule(&schedule)?;
+    }
+    Ok(due)
+}
+
+/// 生成定时任务实际发送的提示词，监视的文件内容附加在提示词后
+pub fn schedule_message(schedule: &Schedule) -> Result<String> {
+    if schedule.watch_file.trim().is_empty() {
+        return Ok(schedule.schedule_prompt.clone());
+    }
+    let content = fs::read_to_string(schedule.watch_file.trim())
+        .with_context(|| format!("无法读取文件 {}", schedule.watch_file))?;
+    Ok(format!(
+        "{}\n\n{}:\n```\n{}\n```",
+        schedule.schedule_prompt,
+        schedule.watch_file.trim(),
+        content.trim_end()
+    ))
+}
+
+/// 使用独立的客户端执行定时任务，不影响当前会话的上下文，系统指令与聊天时一致
+pub fn run_schedule(key: String, model: LanguageModel, system_instruction: String, message: String) -> Result<String> {
+    let mut gemini = Gemini::new(key, model);
+    gemini.set_system_instruction(system_instruction);
+    let (response, _) = gemini.send_simple_message(message)?;
+    track_usage(&gemini, &response);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repeat_intervals() {
+        assert_eq!(parse_repeat("").unwrap(), 0);
+        assert_eq!(parse_repeat(" 30m ").unwrap(), 30);
+        assert_eq!(parse_repeat("2h").unwrap(), 120);
+        assert_eq!(parse_repeat("1d").unwrap(), 1440);
+        // 未知单位、缺少数字、负数与多字节字符
+        for text in ["1w", "30", "m", "xh", "-5m", "1.5h", "3分"] {
+            assert!(parse_repeat(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn format_repeat_intervals() {
+        assert_eq!(format_repeat(0), "once");
+        assert_eq!(format_repeat(-5), "once");
+        assert_eq!(format_repeat(90), "90m");
+        assert_eq!(format_repeat(120), "2h");
+        assert_eq!(format_repeat(1440), "1d");
+        assert_eq!(format_repeat(1500), "25h");
+        for text in ["45m", "3h", "2d"] {
+            assert_eq!(format_repeat(parse_repeat(text).unwrap()), text);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Stdio,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+
+use super::{db_utils::generate_unique_id, tool_utils::template_command};
+
+/// 截图命令退出后等待截图文件写入的最长时间
+const SCREENSHOT_WAIT: Duration = Duration::from_secs(5);
+
+/// 未配置截图命令时使用的默认命令，仅 macOS 自带截图工具
+pub fn default_screenshot_command() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("screencapture -i {file}")
+    } else {
+        None
+    }
+}
+
+/// 使用配置的命令截图，命令中的 `{file}` 会被替换为截图文件路径，返回保存的截图路径
+pub fn capture_screenshot(command_template: &str) -> Result<PathBuf> {
+    if !command_template.contains("{file}") {
+        bail!("未配置截图命令，请在配置文件中设置包含 {{file}} 的 screenshot_command")
+    }
+    let exe_path = env::current_exe()?;
+    let dir = exe_path.parent().context("无法获取程序所在目录")?.join("data");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("screenshot_{}.png", generate_unique_id()));
+    let mut command = template_command(command_template, &[("file", &path.to_string_lossy())]);
+    // 不继承标准输入输出，避免破坏终端界面
+    let status = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()?;
+    if !status.success() {
+        bail!("截图命令执行失败：{}", status)
+    }
+    // 部分截图工具在退出后才写入文件
+    let started_at = Instant::now();
+    while !path.exists() {
+        if started_at.elapsed() > SCREENSHOT_WAIT {
+            bail!("截图文件不存在，可能已取消截图或 screenshot_command 配置有误")
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(path)
+}
@@ -0,0 +1,250 @@
+use std::{env, fs, sync::Arc, thread, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use gemini_api::{
+    body::{Content, Part, Role},
+    model::blocking::Gemini,
+};
+
+use crate::model::view::ChatReply;
+
+use super::{
+    grounding_utils::send_grounded_message,
+    model_utils::{is_tuned_model, send_rest_message, send_tool_response},
+    tool_utils::{LocalTool, ToolCall},
+    usage_utils::content_tokens,
+};
+
+/// 发送消息时的选项
+#[derive(Clone, Default)]
+pub struct SendOptions {
+    /// 是否使用 Google 搜索回答
+    pub search: bool,
+    /// 回复的输出类型，不为空时通过接口直接发送
+    pub modalities: Vec<String>,
+    /// 思考模型的思考预算，设置时通过接口直接发送，并返回思考摘要
+    pub thinking_budget: Option<i64>,
+    /// 可供模型调用的本地工具，不为空时不带图片的消息通过接口直接发送
+    pub tools: Arc<[LocalTool]>,
+}
+
+/// 聊天会话，界面通过它发送消息与读取上下文，不依赖具体的客户端，测试中可以使用模拟实现
+pub trait ChatSession {
+    /// 发送消息并将问答加入上下文，`image_path` 为附带的图片
+    fn send_chat_message(
+        &mut self,
+        message: String,
+        image_path: Option<String>,
+        options: &SendOptions,
+    ) -> Result<ChatReply>;
+
+    /// 将本地工具的执行结果发送给模型并将结果与回复加入上下文，`call` 为上一条回复请求的调用
+    fn send_tool_response(&mut self, call: &ToolCall, _output: String, _options: &SendOptions) -> Result<ChatReply> {
+        bail!("当前会话不支持调用工具 {}", call.name)
+    }
+
+    /// 会话上下文
+    fn history(&self) -> &[Content];
+
+    /// 估算上下文的 token 数
+    fn token_count(&self) -> i64 {
+        content_tokens(self.history())
+    }
+}
+
+impl ChatSession for Gemini {
+    fn send_chat_message(
+        &mut self,
+        message: String,
+        image_path: Option<String>,
+        options: &SendOptions,
+    ) -> Result<ChatReply> {
+        // 微调模型、多模态输出与思考预算通过接口直接发送，只支持文本输入，
+        // 本地工具只提供给不带图片的消息，带图片的消息仍通过客户端发送
+        let rest = is_tuned_model(&self.model.to_string())
+            || !options.modalities.is_empty()
+            || options.thinking_budget.is_some();
+        match image_path {
+            _ if options.search => send_grounded_message(self, message),
+            Some(_) if rest => Err(anyhow!("微调模型、多模态输出与思考预算不支持发送图片")),
+            None if rest || !options.tools.is_empty() => send_rest_message(self, message, options),
+            Some(image_path) => self
+                .send_image_message(image_path, message)
+                .map(|(text, _)| ChatReply::text(text)),
+            None => self.send_simple_message(message).map(|(text, _)| ChatReply::text(text)),
+        }
+    }
+
+    fn send_tool_response(&mut self, call: &ToolCall, output: String, options: &SendOptions) -> Result<ChatReply> {
+        send_tool_response(self, call, output, options)
+    }
+
+    fn history(&self) -> &[Content] {
+        &self.contents
+    }
+}
+
+/// 离线模式中脚本回复文件的环境变量，文件中的回复以单独一行的 `---` 分隔，按顺序循环使用
+const OFFLINE_REPLIES_ENV: &str = "GEMINI_OFFLINE_REPLIES";
+/// 离线模式中回复延迟（毫秒）的环境变量
+const OFFLINE_LATENCY_ENV: &str = "GEMINI_OFFLINE_LATENCY";
+/// 离线模式默认的回复延迟（毫秒）
+const DEFAULT_OFFLINE_LATENCY: u64 = 500;
+/// 离线模式中模拟请求失败的消息前缀，前缀之后的内容作为错误信息
+const OFFLINE_ERROR_PREFIX: &str = "!error ";
+
+/// 离线模式使用的模拟会话，不访问网络也不需要密钥，回复写入客户端的上下文，
+/// 用于在没有密钥或不消耗额度的情况下开发与测试界面
+pub struct OfflineSession<'a> {
+    /// 客户端的上下文
+    contents: &'a mut Vec<Content>,
+    /// 脚本回复，为空时回复消息本身
+    replies: Vec<String>,
+    /// 每次回复前的延迟
+    latency: Duration,
+}
+
+impl<'a> OfflineSession<'a> {
+    /// 使用环境变量中的脚本回复与延迟创建模拟会话
+    pub fn new(contents: &'a mut Vec<Content>) -> Self {
+        let replies = env::var(OFFLINE_REPLIES_ENV)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|script| parse_replies(&script))
+            .unwrap_or_default();
+        let latency = env::var(OFFLINE_LATENCY_ENV)
+            .ok()
+            .and_then(|ms| ms.trim().parse().ok())
+            .unwrap_or(DEFAULT_OFFLINE_LATENCY);
+        Self::with_replies(contents, replies, Duration::from_millis(latency))
+    }
+
+    /// 使用指定的脚本回复与延迟创建模拟会话
+    pub fn with_replies(contents: &'a mut Vec<Content>, replies: Vec<String>, latency: Duration) -> Self {
+        Self {
+            contents,
+            replies,
+            latency,
+        }
+    }
+}
+
+impl ChatSession for OfflineSession<'_> {
+    fn send_chat_message(
+        &mut self,
+        message: String,
+        _image_path: Option<String>,
+        _options: &SendOptions,
+    ) -> Result<ChatReply> {
+        thread::sleep(self.latency);
+        if let Some(error) = message.strip_prefix(OFFLINE_ERROR_PREFIX) {
+            bail!("{}", error)
+        }
+        let turns = self
+            .contents
+            .iter()
+            .filter(|content| matches!(content.role, Some(Role::Model)))
+            .count();
+        let reply = if self.replies.is_empty() {
+            format!("Offline reply to:\n\n{}", message)
+        } else {
+            self.replies[turns % self.replies.len()].clone()
+        };
+        self.contents.push(Content {
+            parts: vec![Part::Text(message)],
+            role: Some(Role::User),
+        });
+        self.contents.push(Content {
+            parts: vec![Part::Text(reply.clone())],
+            role: Some(Role::Model),
+        });
+        Ok(ChatReply::text(reply))
+    }
+
+    fn history(&self) -> &[Content] {
+        self.contents
+    }
+}
+
+/// 解析脚本回复文件，回复以单独一行的 `---` 分隔
+fn parse_replies(script: &str) -> Vec<String> {
+    let mut replies = vec![String::new()];
+    for line in script.lines() {
+        if line.trim() == "---" {
+            replies.push(String::new());
+        } else if let Some(reply) = replies.last_mut() {
+            reply.push_str(line);
+            reply.push('\n');
+        }
+    }
+    replies
+        .into_iter()
+        .map(|reply| reply.trim().to_string())
+        .filter(|reply| !reply.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 原样返回消息的模拟会话
+    struct EchoSession(Vec<Content>);
+
+    impl ChatSession for EchoSession {
+        fn send_chat_message(
+            &mut self,
+            message: String,
+            _image_path: Option<String>,
+            _options: &SendOptions,
+        ) -> Result<ChatReply> {
+            for (text, role) in [(message.clone(), Role::User), (message.clone(), Role::Model)] {
+                self.0.push(Content {
+                    parts: vec![Part::Text(text)],
+                    role: Some(role),
+                });
+            }
+            Ok(ChatReply::text(message))
+        }
+
+        fn history(&self) -> &[Content] {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn mock_session_counts_history() {
+        let mut session = EchoSession(Vec::new());
+        assert_eq!(session.token_count(), 0);
+        let reply = session
+            .send_chat_message("12345678".into(), None, &SendOptions::default())
+            .unwrap();
+        assert_eq!(reply.text, "12345678");
+        assert_eq!(session.history().len(), 2);
+        assert_eq!(session.token_count(), 4);
+    }
+
+    #[test]
+    fn offline_session_replays_script() {
+        let mut contents = Vec::new();
+        let replies = parse_replies("first\n---\nsecond\nline\n---\n");
+        assert_eq!(replies, ["first", "second\nline"]);
+        let mut session = OfflineSession::with_replies(&mut contents, replies, Duration::ZERO);
+        let options = SendOptions::default();
+        assert_eq!(
+            session.send_chat_message("a".into(), None, &options).unwrap().text,
+            "first"
+        );
+        assert_eq!(
+            session.send_chat_message("b".into(), None, &options).unwrap().text,
+            "second\nline"
+        );
+        assert_eq!(
+            session.send_chat_message("c".into(), None, &options).unwrap().text,
+            "first"
+        );
+        let error = session.send_chat_message("!error 503 Service Unavailable".into(), None, &options);
+        assert_eq!(error.unwrap_err().to_string(), "503 Service Unavailable");
+        assert_eq!(contents.len(), 6);
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::http_utils::CLIENT;
+
+/// GitHub gist 接口地址
+const GIST_API: &str = "https://api.github.com/gists";
+
+/// 分享服务配置，配置了 GitHub token 时上传为 gist，否则上传到通用的粘贴服务
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ShareConfig {
+    /// GitHub token，需要 gist 权限
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// gist 是否公开
+    #[serde(default)]
+    pub public: bool,
+    /// 通用粘贴服务地址，以 POST 请求发送 Markdown 文本，返回链接或包含 url 字段的 JSON
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+/// 上传 Markdown 文本，返回分享链接
+pub fn share_markdown(config: &ShareConfig, file_name: &str, markdown: String) -> Result<String> {
+    if let Some(token) = config.github_token.as_ref().filter(|token| !token.trim().is_empty()) {
+        upload_gist(token.trim(), config.public, file_name, markdown)
+    } else if let Some(endpoint) = config.endpoint.as_ref().filter(|endpoint| !endpoint.trim().is_empty()) {
+        upload_paste(endpoint.trim(), markdown)
+    } else {
+        bail!("未配置分享服务，请在配置文件中设置 share.github_token 或 share.endpoint")
+    }
+}
+
+/// 上传为 GitHub gist
+fn upload_gist(token: &str, public: bool, file_name: &str, markdown: String) -> Result<String> {
+    let body = json!({
+        "description": file_name.trim_end_matches(".md"),
+        "public": public,
+        "files": { file_name: { "content": markdown } },
+    });
+    let response = CLIENT
+        .post(GIST_API)
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("User-Agent", "gemini-tui")
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()?;
+    let status = response.status();
+    let text = response.text()?;
+    if !status.is_success() {
+        bail!("上传 gist 失败：{} {}", status, text)
+    }
+    let value: Value = serde_json::from_str(&text)?;
+    value["html_url"]
+        .as_str()
+        .map(String::from)
+        .context("gist 响应中缺少 html_url")
+}
+
+/// 上传到通用的粘贴服务
+fn upload_paste(endpoint: &str, markdown: String) -> Result<String> {
+    let response = CLIENT
+        .post(endpoint)
+        .header("Content-Type", "text/markdown; charset=utf-8")
+        .body(markdown)
+        .send()?;
+    let status = response.status();
+    let text = response.text()?;
+    if !status.is_success() {
+        bail!("上传失败：{} {}", status, text)
+    }
+    // 兼容直接返回链接与返回 JSON 两种形式
+    match serde_json::from_str::<Value>(&text) {
+        Ok(value) => value["url"].as_str().map(String::from).context("响应中缺少 url 字段"),
+        Err(_) => Ok(text.trim().to_string()),
+    }
+}
@@ -1,15 +1,20 @@
 use std::{
+    collections::HashMap,
     env,
-    fs::File,
+    fs::{self, File},
     io::{Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{bail, Result};
 use gemini_api::{body::request::GenerationConfig, param::LanguageModel};
 use serde::{Deserialize, Serialize};
 
-use super::db_utils::current_db_version;
+use super::{
+    clipboard_utils::ClipboardConfig, db_utils::current_db_version, http_utils::HttpConfig,
+    rate_limit_utils::RateLimit, retention_utils::RetentionConfig, share_utils::ShareConfig,
+    template_utils::ConversationTemplate, tool_utils::LocalTool,
+};
 
 /// 存储配置数据
 #[derive(Serialize, Deserialize, Default, Clone)]
@@ -19,10 +24,95 @@ pub(crate) struct StoreData {
     pub system_instruction: Option<String>,
     pub options: GenerationConfig,
     pub db_version: Option<String>,
+    /// 本地工具列表
+    #[serde(default)]
+    pub tools: Vec<LocalTool>,
+    /// 代码审查提示词
+    #[serde(default)]
+    pub review_prompt: Option<String>,
+    /// 翻译源语言
+    #[serde(default)]
+    pub source_language: Option<String>,
+    /// 翻译目标语言
+    #[serde(default)]
+    pub target_language: Option<String>,
+    /// 复制最后一条回复的快捷键，如 `ctrl+shift+c`
+    #[serde(default)]
+    pub copy_reply_key: Option<String>,
+    /// Tab 切换焦点的顺序，为空时使用默认顺序
+    #[serde(default)]
+    pub focus_order: Vec<String>,
+    /// 分享服务配置
+    #[serde(default)]
+    pub share: ShareConfig,
+    /// 朗读命令模板，`{text}` 会被替换为朗读的文本，不包含占位符时通过标准输入传入
+    #[serde(default)]
+    pub tts_command: Option<String>,
+    /// 录音命令模板，`{file}` 会被替换为录音文件路径
+    #[serde(default)]
+    pub record_command: Option<String>,
+    /// 截图命令模板，`{file}` 会被替换为截图文件路径
+    #[serde(default)]
+    pub screenshot_command: Option<String>,
+    /// 无障碍模式，适配屏幕阅读器
+    #[serde(default)]
+    pub accessible: bool,
+    /// 界面配色主题，default/high-contrast/deuteranopia/monochrome
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// 是否使用 Nerd Font 图标
+    #[serde(default)]
+    pub icons: bool,
+    /// 超过该行数的消息默认折叠，为 0 时不折叠，未配置时使用默认值
+    #[serde(default)]
+    pub collapse_lines: Option<u16>,
+    /// 生成的会话标题的最大显示宽度，中日韩文字占两列，未配置时使用默认值
+    #[serde(default)]
+    pub title_max_width: Option<usize>,
+    /// 使用其他模型重新提问时，若原回复被评价为差，则附带原回复与原因
+    #[serde(default)]
+    pub rating_feedback: bool,
+    /// 图片生成模型
+    #[serde(default)]
+    pub image_model: Option<String>,
+    /// `gemini live` 使用的 Live API 模型
+    #[serde(default)]
+    pub live_model: Option<String>,
+    /// 主模型返回 503 或过载时自动改用的模型，为空时不切换
+    #[serde(default)]
+    pub fallback_model: Option<String>,
+    /// 聊天回复的输出类型，如 `["TEXT", "AUDIO"]`，为空时只输出文本
+    #[serde(default)]
+    pub response_modalities: Vec<String>,
+    /// 思考模型的思考预算（token 数），设置后回复附带可展开的思考摘要，`0` 关闭思考，`-1` 由模型决定
+    #[serde(default)]
+    pub thinking_budget: Option<i64>,
+    /// 每日请求次数上限，用于在用量统计页面显示进度
+    #[serde(default)]
+    pub daily_request_limit: Option<u64>,
+    /// 各模型的每分钟请求次数与 token 数上限，以模型名称为键，超出时在发送聊天消息前等待
+    #[serde(default)]
+    pub rate_limits: HashMap<String, RateLimit>,
+    /// 网络连接配置
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// 剪贴板配置
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    /// 会话模板
+    #[serde(default)]
+    pub templates: Vec<ConversationTemplate>,
+    /// 会话保留策略
+    #[serde(default)]
+    pub retention: RetentionConfig,
 }
 
 /// 配置文件名
 const CONFIG_FILE_NAME: &str = "gemini.json";
+/// 导出的配置文件名
+pub(crate) const EXPORT_FILE_NAME: &str = "gemini-config.json";
+/// 导出配置时替代密钥的文本
+const REDACTED_KEY: &str = "<redacted>";
 
 /// 保存配置
 pub(crate) fn save_config(store_data: StoreData) -> Result<()> {
@@ -33,6 +123,25 @@ pub(crate) fn save_config(store_data: StoreData) -> Result<()> {
     Ok(())
 }
 
+/// 将配置导出到文件，`include_key` 为 false 时隐藏密钥，数据库版本不导出
+pub(crate) fn export_config(mut store_data: StoreData, path: &Path, include_key: bool) -> Result<()> {
+    if !include_key {
+        store_data.key = REDACTED_KEY.into();
+    }
+    store_data.db_version = None;
+    fs::write(path, serde_json::to_string_pretty(&store_data)?)?;
+    Ok(())
+}
+
+/// 从文件导入配置，导出时隐藏了密钥的配置导入后密钥为空
+pub(crate) fn import_config(path: &Path) -> Result<StoreData> {
+    let mut store_data: StoreData = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if store_data.key == REDACTED_KEY {
+        store_data.key.clear();
+    }
+    Ok(store_data)
+}
+
 /// 保存数据库版本变更
 pub(crate) fn update_db_version_into_profile() -> Result<()> {
     let mut config = read_config()?;
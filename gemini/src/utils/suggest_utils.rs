@@ -0,0 +1,123 @@
+use std::{env, path::Path};
+
+use anyhow::{bail, Result};
+
+/// 上一条命令，由 shell 集成脚本在每条命令执行后设置
+const LAST_COMMAND_ENV: &str = "GEMINI_LAST_COMMAND";
+/// 上一条命令的退出状态
+const LAST_STATUS_ENV: &str = "GEMINI_LAST_STATUS";
+
+/// zsh 集成脚本，按 Alt+g 将命令行中的描述替换为建议的命令，命令行为空时修正上一条失败的命令
+const ZSH_WIDGET: &str = r#"# gemini suggest: eval "$(gemini suggest --init zsh)"
+_gemini_precmd() {
+  export GEMINI_LAST_STATUS=$?
+  export GEMINI_LAST_COMMAND="$(fc -ln -1 2>/dev/null)"
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd _gemini_precmd
+_gemini_suggest_widget() {
+  local suggestion
+  zle -M "asking gemini..."
+  suggestion="$(gemini suggest "$BUFFER" 2>/dev/null)"
+  zle -M ""
+  if [[ -n "$suggestion" ]]; then
+    BUFFER="$suggestion"
+    CURSOR=${#BUFFER}
+  fi
+  zle redisplay
+}
+zle -N _gemini_suggest_widget
+bindkey '\eg' _gemini_suggest_widget
+"#;
+
+/// fish 集成脚本，按 Alt+g 将命令行中的描述替换为建议的命令，命令行为空时修正上一条失败的命令
+const FISH_WIDGET: &str = r#"# gemini suggest: gemini suggest --init fish | source
+function __gemini_postexec --on-event fish_postexec
+    set -gx GEMINI_LAST_STATUS $status
+    set -gx GEMINI_LAST_COMMAND $argv[1]
+end
+function __gemini_suggest
+    set -l suggestion (gemini suggest (commandline) 2>/dev/null | string collect)
+    if test -n "$suggestion"
+        commandline -r -- $suggestion
+    end
+    commandline -f repaint
+end
+bind \eg __gemini_suggest
+"#;
+
+/// 生成指定 shell 的集成脚本
+pub fn shell_widget(shell: &str) -> Result<&'static str> {
+    match shell {
+        "zsh" => Ok(ZSH_WIDGET),
+        "fish" => Ok(FISH_WIDGET),
+        _ => bail!("不支持的 shell {}，可选值为 zsh、fish", shell),
+    }
+}
+
+/// 当前使用的 shell 名称
+fn current_shell() -> String {
+    if cfg!(windows) {
+        return "PowerShell".into();
+    }
+    env::var("SHELL")
+        .ok()
+        .and_then(|shell| {
+            Path::new(&shell)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or("sh".into())
+}
+
+/// 构造请求命令建议的消息，描述为空时根据上一条失败的命令请求修正后的命令
+pub fn suggest_message(description: &str) -> Result<String> {
+    let shell = current_shell();
+    let rules = format!(
+        "Reply with a single {} command for {} and nothing else: no explanation, no Markdown, no code fence.",
+        shell,
+        env::consts::OS
+    );
+    if !description.trim().is_empty() {
+        return Ok(format!("{}\n\nTask: {}", rules, description.trim()));
+    }
+    let command = env::var(LAST_COMMAND_ENV).unwrap_or_default();
+    let status = env::var(LAST_STATUS_ENV).unwrap_or_default();
+    if command.trim().is_empty() || status.trim() == "0" {
+        bail!("用法: gemini suggest \"描述\"，或在上一条命令执行失败后不带参数运行")
+    }
+    Ok(format!(
+        "{}\n\nThis command failed with exit status {}, give the corrected command:\n{}",
+        rules,
+        status.trim(),
+        command.trim()
+    ))
+}
+
+/// 整理模型回复，去掉可能存在的代码块围栏与提示符，只保留命令本身
+pub fn clean_command(response: &str) -> String {
+    let lines: Vec<&str> = response
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .map(|line| line.strip_prefix("$ ").unwrap_or(line))
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_command_strips_fence_and_prompt() {
+        assert_eq!(clean_command("```bash\n$ ls -la\n```"), "ls -la");
+        assert_eq!(clean_command("git status\n"), "git status");
+        assert_eq!(clean_command("  ```\ncargo build\n```  "), "cargo build");
+        // 多行命令的每一行都去掉提示符
+        assert_eq!(clean_command("```sh\n$ cd app\n$ make\n```"), "cd app\nmake");
+        // 命令中间的 $ 保持不变
+        assert_eq!(clean_command("echo $ HOME"), "echo $ HOME");
+        assert_eq!(clean_command("```\n```"), "");
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::model::view::{ChatMessage, Sender};
+
+use super::store_utils::{read_config, save_config};
+
+/// 会话模板，用于创建预先填充了开场消息的会话
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ConversationTemplate {
+    /// 模板名称，同时作为新会话的标题
+    pub name: String,
+    /// 系统提示词，为空时使用配置中的系统提示词
+    #[serde(default)]
+    pub system_instruction: Option<String>,
+    /// 开场消息
+    #[serde(default)]
+    pub messages: Vec<TemplateMessage>,
+}
+
+/// 模板中的一条消息
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct TemplateMessage {
+    /// 发送者，`user` 或 `model`
+    pub role: String,
+    /// 消息内容
+    pub text: String,
+}
+
+impl ConversationTemplate {
+    /// 由会话的消息创建模板，本地备注不会保存到模板中
+    pub fn from_messages(name: String, system_instruction: Option<String>, messages: &[ChatMessage]) -> Self {
+        let messages = messages
+            .iter()
+            .filter_map(|message| {
+                let role = match message.sender {
+                    Sender::User(_) => "user",
+                    Sender::Bot => "model",
+                    Sender::Note | Sender::Never => return None,
+                };
+                Some(TemplateMessage {
+                    role: role.into(),
+                    text: message.message.clone(),
+                })
+            })
+            .collect();
+        Self {
+            name,
+            system_instruction: system_instruction.filter(|instruction| !instruction.is_empty()),
+            messages,
+        }
+    }
+
+    /// 模板中的开场消息，发送时间为当前时间
+    pub fn chat_messages(&self) -> Vec<ChatMessage> {
+        self.messages
+            .iter()
+            .map(|message| ChatMessage {
+                success: true,
+                sender: if message.role == "model" {
+                    Sender::Bot
+                } else {
+                    Sender::User("".into())
+                },
+                message: message.text.clone(),
+                date_time: Local::now(),
+                record_id: None,
+                attachments: Vec::new(),
+                raw: None,
+            })
+            .collect()
+    }
+}
+
+/// 读取配置中的所有模板
+pub fn read_templates() -> Vec<ConversationTemplate> {
+    read_config().map(|config| config.templates).unwrap_or_default()
+}
+
+/// 保存模板，同名模板会被覆盖
+pub fn save_template(template: ConversationTemplate) -> Result<()> {
+    let mut config = read_config()?;
+    match config.templates.iter_mut().find(|item| item.name == template.name) {
+        Some(item) => *item = template,
+        None => config.templates.push(template),
+    }
+    save_config(config)
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Local};
+
+/// 将时间格式化为相对当前时间的描述，超过一周则显示日期
+pub fn relative_time(time: DateTime<Local>) -> String {
+    let elapsed = Local::now().signed_duration_since(time);
+    if elapsed.num_minutes() < 1 {
+        "just now".into()
+    } else if elapsed.num_hours() < 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if elapsed.num_days() < 7 {
+        format!("{}d ago", elapsed.num_days())
+    } else {
+        time.format("%Y/%m/%d").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    #[test]
+    fn relative_descriptions() {
+        let now = Local::now();
+        assert_eq!(relative_time(now), "just now");
+        assert_eq!(relative_time(now - TimeDelta::minutes(5)), "5m ago");
+        assert_eq!(relative_time(now - TimeDelta::hours(3)), "3h ago");
+        assert_eq!(relative_time(now - TimeDelta::days(2)), "2d ago");
+        let old = now - TimeDelta::days(30);
+        assert_eq!(relative_time(old), old.format("%Y/%m/%d").to_string());
+    }
+}
@@ -0,0 +1,213 @@
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// 工具输出最大字符数
+const MAX_TOOL_OUTPUT_LEN: usize = 4000;
+
+/// 本地工具定义
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct LocalTool {
+    /// 工具名称
+    pub name: String,
+    /// 工具描述
+    pub description: String,
+    /// 命令模板，使用 {参数名} 作为占位符
+    pub command: String,
+    /// 参数列表
+    #[serde(default)]
+    pub parameters: Vec<ToolParameter>,
+}
+
+/// 工具参数定义
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ToolParameter {
+    /// 参数名称
+    pub name: String,
+    /// 参数描述
+    pub description: String,
+}
+
+/// 模型请求的工具调用
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ToolCall {
+    /// 工具名称
+    pub name: String,
+    /// 调用参数
+    #[serde(default)]
+    pub args: Map<String, Value>,
+}
+
+/// 将工具转换为请求体中 `tools` 字段使用的函数声明，参数均为字符串且必须提供
+pub fn function_declarations(tools: &[LocalTool]) -> Value {
+    let declarations: Vec<Value> = tools
+        .iter()
+        .map(|tool| {
+            let mut declaration = json!({ "name": tool.name, "description": tool.description });
+            if !tool.parameters.is_empty() {
+                let properties: Map<String, Value> = tool
+                    .parameters
+                    .iter()
+                    .map(|p| {
+                        (
+                            p.name.clone(),
+                            json!({ "type": "STRING", "description": p.description }),
+                        )
+                    })
+                    .collect();
+                let required: Vec<&str> = tool.parameters.iter().map(|p| p.name.as_str()).collect();
+                declaration["parameters"] = json!({ "type": "OBJECT", "properties": properties, "required": required });
+            }
+            declaration
+        })
+        .collect();
+    json!([{ "functionDeclarations": declarations }])
+}
+
+/// 从接口响应中取出模型请求的第一个函数调用
+pub fn parse_function_call(value: &Value) -> Option<ToolCall> {
+    value["candidates"][0]["content"]["parts"]
+        .as_array()?
+        .iter()
+        .find_map(|part| serde_json::from_value(part.get("functionCall")?.clone()).ok())
+}
+
+/// 函数调用在聊天记录与上下文中显示的文本
+pub fn tool_call_message(call: &ToolCall) -> String {
+    format!("Calling tool `{}` with {}", call.name, Value::Object(call.args.clone()))
+}
+
+/// 根据工具名称查找工具定义
+pub fn find_tool<'a>(tools: &'a [LocalTool], name: &str) -> Option<&'a LocalTool> {
+    tools.iter().find(|tool| tool.name == name)
+}
+
+/// 将调用参数填充到命令模板中
+fn render_command(tool: &LocalTool, call: &ToolCall) -> Command {
+    let values: Vec<(&str, String)> = tool
+        .parameters
+        .iter()
+        .map(|parameter| {
+            let value = match call.args.get(&parameter.name) {
+                Some(Value::String(s)) => s.clone(),
+                Some(v) => v.to_string(),
+                None => String::new(),
+            };
+            (parameter.name.as_str(), value)
+        })
+        .collect();
+    let values: Vec<(&str, &str)> = values.iter().map(|(name, value)| (*name, value.as_str())).collect();
+    template_command(&tool.command, &values)
+}
+
+/// 根据命令模板创建命令，模板中的 `{名称}` 占位符会被替换为对应的值
+///
+/// 非 Windows 系统通过 sh 执行，值转义后代入；cmd 无法可靠地转义 `%`、`&` 等字符，
+/// 因此 Windows 上不经过 shell，模板按空白拆分为程序与参数，值原样代入参数
+pub fn template_command(template: &str, values: &[(&str, &str)]) -> Command {
+    let fill = |text: &str, quote: fn(&str) -> String| {
+        values.iter().fold(text.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &quote(value))
+        })
+    };
+    if cfg!(windows) {
+        let mut words = template.split_whitespace().map(|word| fill(word, str::to_string));
+        let mut command = Command::new(words.next().unwrap_or_default());
+        command.args(words);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", &fill(template, shell_quote)]);
+        command
+    }
+}
+
+/// 执行模型请求的工具，返回截断后的输出
+pub fn run_tool(tool: &LocalTool, call: &ToolCall) -> Result<String> {
+    command_output(render_command(tool, call))
+}
+
+/// 通过 shell 执行用户输入的命令，返回截断后的输出
+pub fn run_command(command: &str) -> Result<String> {
+    let command = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    command_output(command)
+}
+
+/// 在后台执行命令，不读取终端输入，合并标准输出与标准错误并截断
+fn command_output(mut command: Command) -> Result<String> {
+    let output = match command.stdin(Stdio::null()).output() {
+        Ok(output) => output,
+        Err(e) => bail!("执行命令失败: {}", e),
+    };
+    let mut result = String::from_utf8_lossy(&output.stdout).to_string();
+    result.push_str(&String::from_utf8_lossy(&output.stderr));
+    if result.chars().count() > MAX_TOOL_OUTPUT_LEN {
+        result = result.chars().take(MAX_TOOL_OUTPUT_LEN).collect();
+        result.push_str("\n...(truncated)");
+    }
+    Ok(result)
+}
+
+/// 工具执行结果在聊天记录与上下文中显示的文本
+pub fn tool_output_message(name: &str, output: &str) -> String {
+    format!("Output of tool `{}`:\n```\n{}\n```", name, output.trim_end())
+}
+
+/// 对参数进行转义，避免被 sh 解释
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tools_are_declared_as_functions() {
+        let tools = [
+            LocalTool {
+                name: "list_dir".into(),
+                description: "List files in a directory".into(),
+                command: "ls -la {path}".into(),
+                parameters: vec![ToolParameter {
+                    name: "path".into(),
+                    description: "directory path".into(),
+                }],
+            },
+            LocalTool {
+                name: "date".into(),
+                description: "Current date".into(),
+                command: "date".into(),
+                parameters: Vec::new(),
+            },
+        ];
+        let declarations = function_declarations(&tools);
+        let list_dir = &declarations[0]["functionDeclarations"][0];
+        assert_eq!(list_dir["parameters"]["properties"]["path"]["type"], "STRING");
+        assert_eq!(list_dir["parameters"]["required"], json!(["path"]));
+        assert!(declarations[0]["functionDeclarations"][1].get("parameters").is_none());
+    }
+
+    #[test]
+    fn function_call_is_parsed_from_response() {
+        let value = json!({ "candidates": [{ "content": { "parts": [
+            { "text": "Let me look." },
+            { "functionCall": { "name": "list_dir", "args": { "path": "/tmp" } } },
+        ] } }] });
+        let call = parse_function_call(&value).unwrap();
+        assert_eq!(call.name, "list_dir");
+        assert_eq!(call.args["path"], "/tmp");
+        let value = json!({ "candidates": [{ "content": { "parts": [{ "text": "```tool_call\n{}\n```" }] } }] });
+        assert!(parse_function_call(&value).is_none());
+    }
+}
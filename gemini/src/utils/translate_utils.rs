@@ -0,0 +1,34 @@
+use anyhow::Result;
+use gemini_api::{model::blocking::Gemini, param::LanguageModel};
+
+use super::usage_utils::track_usage;
+
+/// 自动检测源语言
+pub const AUTO_LANGUAGE: &str = "auto";
+/// 默认目标语言
+pub const DEFAULT_TARGET_LANGUAGE: &str = "English";
+
+/// 翻译角色的系统指令
+pub fn translation_system_instruction(source: &str, target: &str) -> String {
+    let source = if source.trim().is_empty() || source.trim() == AUTO_LANGUAGE {
+        "the detected source language".to_string()
+    } else {
+        source.trim().to_string()
+    };
+    format!(
+        "You are a professional translator. Translate the user's text from {} into {}. \
+Keep the original formatting, Markdown and code blocks unchanged, do not translate code, \
+and reply with only the translation.",
+        source,
+        target.trim()
+    )
+}
+
+/// 使用独立的客户端翻译文本，不影响当前会话的上下文
+pub fn translate(key: String, model: LanguageModel, source: &str, target: &str, text: String) -> Result<String> {
+    let mut translator = Gemini::new(key, model);
+    translator.set_system_instruction(translation_system_instruction(source, target));
+    let (translation, _) = translator.send_simple_message(text)?;
+    track_usage(&translator, &translation);
+    Ok(translation.trim_end().to_string())
+}
@@ -0,0 +1,49 @@
+use std::{
+    io::Write,
+    process::{Child, Stdio},
+};
+
+use anyhow::{bail, Result};
+
+use super::{markdown_utils::parse_code_blocks, tool_utils::template_command};
+
+/// 使用配置的命令朗读文本，命令中的 `{text}` 会被替换为文本，否则文本通过标准输入传入
+///
+/// 命令在后台运行，返回子进程以便停止朗读
+pub fn speak(command_template: &str, text: &str) -> Result<Child> {
+    if command_template.trim().is_empty() {
+        bail!("未配置朗读命令，请在配置文件中设置 tts_command")
+    }
+    let text = speakable_text(text);
+    let use_stdin = !command_template.contains("{text}");
+    let mut command = template_command(command_template, &[("text", &text)]);
+    // 不继承标准输出，避免破坏终端界面
+    let mut child = command
+        .stdin(if use_stdin { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    Ok(child)
+}
+
+/// 去掉不适合朗读的内容：代码块以及 Markdown 标记符号
+fn speakable_text(text: &str) -> String {
+    let blocks = parse_code_blocks(text);
+    text.lines()
+        .enumerate()
+        .filter(|(i, _)| {
+            !blocks
+                .iter()
+                .any(|block| (block.start_line..=block.end_line).contains(i))
+        })
+        .map(|(_, line)| {
+            line.trim_start_matches(['#', '>', '-', ' '])
+                .replace(['*', '`', '|'], "")
+        })
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,102 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use gemini_api::body::{Content, Part};
+use gemini_api::model::blocking::Gemini;
+use serde_json::Value;
+
+use crate::model::db::Usage;
+
+use super::db_utils::record_usage;
+
+/// 一张图片大约占用的 token 数
+const IMAGE_TOKENS: i64 = 258;
+
+/// 估算文本的 token 数，ASCII 字符约 4 个一个 token，其他字符约 1 个一个 token
+///
+/// 客户端不会返回 usageMetadata，通过客户端发送的请求只能按字符数估算
+pub fn estimate_tokens(text: &str) -> i64 {
+    let ascii = text.chars().filter(char::is_ascii).count() as i64;
+    let other = text.chars().count() as i64 - ascii;
+    other + (ascii + 3) / 4
+}
+
+/// 估算会话上下文的 token 数
+pub fn content_tokens(contents: &[Content]) -> i64 {
+    contents
+        .iter()
+        .flat_map(|content| content.parts.iter())
+        .map(|part| match part {
+            Part::Text(text) => estimate_tokens(text),
+            _ => IMAGE_TOKENS,
+        })
+        .sum()
+}
+
+/// 记录一次通过客户端发送的请求的估算用量，上下文中已包含本次回复，记录失败不影响请求
+pub fn track_usage(gemini: &Gemini, response: &str) {
+    let response_tokens = estimate_tokens(response);
+    let prompt_tokens = (content_tokens(&gemini.contents) - response_tokens).max(0);
+    let _ = record_usage(&gemini.model.to_string(), prompt_tokens, response_tokens);
+}
+
+/// 根据 REST 接口响应中的 usageMetadata 记录用量
+pub fn track_usage_metadata(model: &str, response: &Value) {
+    let metadata = &response["usageMetadata"];
+    let prompt_tokens = metadata["promptTokenCount"].as_i64().unwrap_or_default();
+    let response_tokens = metadata["candidatesTokenCount"].as_i64().unwrap_or_default();
+    let _ = record_usage(model, prompt_tokens, response_tokens);
+}
+
+/// 统计周期内的用量合计
+#[derive(Debug, Clone, Default)]
+pub struct UsageTotal {
+    /// 周期标签
+    pub label: String,
+    /// 请求次数
+    pub requests: u64,
+    /// token 数
+    pub tokens: u64,
+}
+
+/// 按天统计最近若干天的用量，没有用量的日期记为 0
+pub fn daily_totals(usages: &[Usage], days: i64) -> Vec<UsageTotal> {
+    let today = Local::now().date_naive();
+    (0..days)
+        .rev()
+        .map(|i| {
+            let date = today - Duration::days(i);
+            total_between(usages, date, date, date.format("%m/%d").to_string())
+        })
+        .collect()
+}
+
+/// 按周统计最近若干周的用量，每周从周一开始
+pub fn weekly_totals(usages: &[Usage], weeks: i64) -> Vec<UsageTotal> {
+    let today = Local::now().date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    (0..weeks)
+        .rev()
+        .map(|i| {
+            let start = monday - Duration::weeks(i);
+            let end = start + Duration::days(6);
+            total_between(usages, start, end, start.format("%m/%d").to_string())
+        })
+        .collect()
+}
+
+/// 统计日期范围（包含两端）内的用量
+fn total_between(usages: &[Usage], start: NaiveDate, end: NaiveDate, label: String) -> UsageTotal {
+    usages
+        .iter()
+        .filter(|usage| usage.usage_date >= start && usage.usage_date <= end)
+        .fold(
+            UsageTotal {
+                label,
+                ..Default::default()
+            },
+            |mut total, usage| {
+                total.requests += usage.request_count as u64;
+                total.tokens += (usage.prompt_tokens + usage.response_tokens) as u64;
+                total
+            },
+        )
+}
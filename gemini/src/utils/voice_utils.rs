@@ -0,0 +1,86 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use gemini_api::param::LanguageModel;
+use serde_json::json;
+
+use super::{db_utils::generate_unique_id, model_utils::call_model, tool_utils::template_command};
+
+/// 语音识别使用的提示词
+pub const TRANSCRIBE_PROMPT: &str = "Transcribe this audio. If it is a question or an instruction, \
+reply with only the transcription, without answering it or adding any explanation.";
+
+/// 正在进行的录音
+pub struct Recording {
+    /// 录音进程
+    child: Child,
+    /// 录音文件路径
+    pub path: PathBuf,
+}
+
+/// 使用配置的命令开始录音，命令中的 `{file}` 会被替换为录音文件路径
+pub fn start_recording(command_template: &str) -> Result<Recording> {
+    if !command_template.contains("{file}") {
+        bail!("未配置录音命令，请在配置文件中设置包含 {{file}} 的 record_command")
+    }
+    let path = env::temp_dir().join(format!("gemini-voice-{}.wav", generate_unique_id()));
+    // 使用 exec 替换 shell 进程，以便停止录音时信号能直接送达录音程序，Windows 上不经过 shell
+    let command_template = if cfg!(windows) {
+        command_template.to_string()
+    } else {
+        format!("exec {}", command_template)
+    };
+    let mut command = template_command(&command_template, &[("file", &path.to_string_lossy())]);
+    // 不继承标准输入输出，避免破坏终端界面
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(Recording { child, path })
+}
+
+impl Recording {
+    /// 停止录音，返回录音文件路径
+    pub fn stop(mut self) -> Result<PathBuf> {
+        // 发送中断信号，让录音程序写完文件头后退出
+        if cfg!(windows)
+            || !Command::new("kill")
+                .args(["-INT", &self.child.id().to_string()])
+                .status()
+                .is_ok_and(|status| status.success())
+        {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+        if !self.path.exists() {
+            bail!("录音文件不存在，请检查 record_command 配置")
+        }
+        Ok(self.path)
+    }
+}
+
+/// 将录音作为音频片段发送给 Gemini，返回识别结果，识别完成后删除录音文件
+pub fn transcribe(key: &str, model: &LanguageModel, path: PathBuf) -> Result<String> {
+    let audio = fs::read(&path);
+    let _ = fs::remove_file(&path);
+    let body = json!({
+        "contents": [{
+            "role": "user",
+            "parts": [
+                { "inline_data": { "mime_type": "audio/wav", "data": STANDARD.encode(audio?) } },
+                { "text": TRANSCRIBE_PROMPT },
+            ],
+        }],
+    });
+    let value = call_model(key, &model.to_string(), "generateContent", &body, "语音识别")?;
+    value["candidates"][0]["content"]["parts"][0]["text"]
+        .as_str()
+        .map(|text| text.trim().to_string())
+        .context("语音识别结果为空")
+}